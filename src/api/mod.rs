@@ -1,6 +1,24 @@
+mod polygon;
+mod provider;
 mod rest;
+mod tradier;
 mod websocket;
 
+pub use polygon::PolygonClient;
+pub use provider::{provider_from_config, OptionDataProvider};
+pub use tradier::TradierClient;
+
+pub use rest::Account;
+pub use rest::ChainSymbolFilter;
+pub use rest::LatestOptionTradesResponse;
 pub use rest::OptionGreeks;
+pub use rest::OptionSnapshot;
 pub use rest::RestClient;
+pub use rest::SnapshotFeedCoverage;
+pub use rest::StockBar;
+pub use rest::StockBarsResponse;
+pub use websocket::OptionBar as StreamOptionBar;
+pub use websocket::OptionTrade as StreamOptionTrade;
+pub use websocket::StreamSelection;
 pub use websocket::WebSocketClient;
+pub use websocket::WsState;
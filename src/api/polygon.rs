@@ -0,0 +1,500 @@
+use super::provider::OptionDataProvider;
+use super::rest::{
+    ChainSymbolFilter, OptionBar, OptionGreeks, OptionLastQuote, OptionLastTrade, OptionSnapshot,
+};
+use crate::config::PolygonConfig;
+use crate::error::{OptionsError, Result};
+use crate::models::OptionType;
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+const SNAPSHOT_PAGE_LIMIT: u32 = 250;
+const CONTRACTS_PAGE_LIMIT: u32 = 1000;
+const MAX_SNAPSHOT_PAGES: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct SnapshotPage {
+    #[serde(default)]
+    results: Vec<PolygonOptionSnapshot>,
+    next_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContractsPage {
+    #[serde(default)]
+    results: Vec<PolygonReferenceContract>,
+    next_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolygonReferenceContract {
+    expiration_date: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolygonOptionSnapshot {
+    details: PolygonContractDetails,
+    day: Option<PolygonDay>,
+    greeks: Option<PolygonGreeks>,
+    implied_volatility: Option<f64>,
+    last_quote: Option<PolygonLastQuote>,
+    last_trade: Option<PolygonLastTrade>,
+    underlying_asset: Option<PolygonUnderlying>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolygonContractDetails {
+    contract_type: String,
+    expiration_date: String,
+    strike_price: f64,
+    ticker: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolygonDay {
+    open: Option<f64>,
+    high: Option<f64>,
+    low: Option<f64>,
+    close: Option<f64>,
+    volume: Option<f64>,
+    vwap: Option<f64>,
+    last_updated: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolygonGreeks {
+    delta: Option<f64>,
+    gamma: Option<f64>,
+    theta: Option<f64>,
+    vega: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolygonLastQuote {
+    bid: f64,
+    ask: f64,
+    #[serde(default)]
+    bid_size: f64,
+    #[serde(default)]
+    ask_size: f64,
+    last_updated: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolygonLastTrade {
+    price: f64,
+    #[serde(default)]
+    size: f64,
+    sip_timestamp: Option<i64>,
+    exchange: Option<i64>,
+    #[serde(default)]
+    conditions: Vec<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolygonUnderlying {
+    #[serde(default)]
+    ticker: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LastQuoteResponse {
+    results: Option<PolygonNbbo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolygonNbbo {
+    #[serde(rename = "p")]
+    bid: f64,
+    #[serde(rename = "P")]
+    ask: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrevCloseResponse {
+    #[serde(default)]
+    results: Vec<PolygonAgg>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolygonAgg {
+    c: f64,
+}
+
+fn from_nanos(nanos: Option<i64>) -> DateTime<Utc> {
+    nanos
+        .map(DateTime::<Utc>::from_timestamp_nanos)
+        .unwrap_or_else(Utc::now)
+}
+
+impl From<PolygonOptionSnapshot> for OptionSnapshot {
+    fn from(s: PolygonOptionSnapshot) -> Self {
+        let symbol = s
+            .details
+            .ticker
+            .strip_prefix("O:")
+            .unwrap_or(&s.details.ticker)
+            .to_string();
+
+        // Polygon does not publish rho, so it is left as NaN rather than a fake zero.
+        let greeks = s.greeks.and_then(|g| {
+            Some(OptionGreeks {
+                delta: g.delta?,
+                gamma: g.gamma?,
+                theta: g.theta?,
+                vega: g.vega?,
+                rho: f64::NAN,
+            })
+        });
+
+        let daily_bar = s.day.and_then(|d| {
+            Some(OptionBar {
+                t: from_nanos(d.last_updated),
+                o: d.open.unwrap_or(f64::NAN),
+                h: d.high.unwrap_or(f64::NAN),
+                l: d.low.unwrap_or(f64::NAN),
+                c: d.close?,
+                v: d.volume.unwrap_or(0.0) as u64,
+                n: None,
+                vw: d.vwap.unwrap_or(f64::NAN),
+            })
+        });
+
+        OptionSnapshot {
+            symbol,
+            underlying_symbol: s.underlying_asset.map(|u| u.ticker).unwrap_or_default(),
+            strike_price: s.details.strike_price,
+            expiration_date: s.details.expiration_date,
+            contract_type: s.details.contract_type,
+            last_trade: s.last_trade.map(|t| OptionLastTrade {
+                t: from_nanos(t.sip_timestamp),
+                price: t.price,
+                size: t.size as u64,
+                conditions: t.conditions.iter().map(|c| c.to_string()).collect(),
+                exchange_code: t.exchange.map(|x| x.to_string()).unwrap_or_default(),
+                condition: None,
+            }),
+            last_quote: s.last_quote.map(|q| OptionLastQuote {
+                t: from_nanos(q.last_updated),
+                bid: q.bid,
+                ask: q.ask,
+                size_bid: q.bid_size as u64,
+                size_ask: q.ask_size as u64,
+                bid_exchange: None,
+                ask_exchange: None,
+                condition: None,
+            }),
+            greeks,
+            implied_volatility: s.implied_volatility,
+            daily_bar,
+            minute_bar: None,
+            prev_daily_bar: None,
+        }
+    }
+}
+
+pub struct PolygonClient {
+    client: reqwest::Client,
+    config: PolygonConfig,
+}
+
+impl PolygonClient {
+    pub fn new(mut config: PolygonConfig) -> Self {
+        config.base_url = config.base_url.trim_end_matches('/').to_string();
+        Self {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    pub fn parse_snapshot_page(body: &str) -> Result<(Vec<OptionSnapshot>, Option<String>)> {
+        let page = serde_json::from_str::<SnapshotPage>(body).map_err(|e| {
+            OptionsError::ParseError(format!("Failed to parse Polygon option snapshots: {}", e))
+        })?;
+        let snapshots = page.results.into_iter().map(OptionSnapshot::from).collect();
+        Ok((snapshots, page.next_url.filter(|u| !u.is_empty())))
+    }
+
+    pub fn parse_contracts_page(body: &str) -> Result<(Vec<NaiveDate>, Option<String>)> {
+        let page = serde_json::from_str::<ContractsPage>(body).map_err(|e| {
+            OptionsError::ParseError(format!("Failed to parse Polygon option contracts: {}", e))
+        })?;
+        let expirations = page
+            .results
+            .iter()
+            .filter_map(|c| NaiveDate::parse_from_str(&c.expiration_date, "%Y-%m-%d").ok())
+            .collect();
+        Ok((expirations, page.next_url.filter(|u| !u.is_empty())))
+    }
+
+    async fn get_text(&self, url: &str) -> Result<String> {
+        let resp = self
+            .client
+            .get(url)
+            .bearer_auth(&self.config.api_key)
+            .send()
+            .await
+            .map_err(|e| OptionsError::Other(format!("Polygon request failed: {}", e)))?;
+
+        let status = resp.status();
+        let text = resp
+            .text()
+            .await
+            .map_err(|e| OptionsError::Other(format!("Failed to get response text: {}", e)))?;
+        if !status.is_success() {
+            return Err(OptionsError::Other(format!(
+                "Polygon request failed with status {}: {}",
+                status, text
+            )));
+        }
+        Ok(text)
+    }
+
+    pub async fn get_option_chain_snapshots(
+        &self,
+        underlying_symbol: &str,
+        filter: &ChainSymbolFilter,
+    ) -> Result<Vec<OptionSnapshot>> {
+        let mut query_params = vec![format!("limit={}", SNAPSHOT_PAGE_LIMIT)];
+        if let Some(v) = &filter.expiration_date {
+            query_params.push(format!("expiration_date={}", v));
+        }
+        if let Some(v) = &filter.expiration_date_gte {
+            query_params.push(format!("expiration_date.gte={}", v));
+        }
+        if let Some(v) = &filter.expiration_date_lte {
+            query_params.push(format!("expiration_date.lte={}", v));
+        }
+        if let Some(v) = filter.strike_price_gte {
+            query_params.push(format!("strike_price.gte={}", v));
+        }
+        if let Some(v) = filter.strike_price_lte {
+            query_params.push(format!("strike_price.lte={}", v));
+        }
+        if let Some(t) = filter.option_type {
+            let t = match t {
+                OptionType::Call => "call",
+                OptionType::Put => "put",
+            };
+            query_params.push(format!("contract_type={}", t));
+        }
+
+        let mut url = Some(format!(
+            "{}/v3/snapshot/options/{}?{}",
+            self.config.base_url,
+            underlying_symbol,
+            query_params.join("&")
+        ));
+        let mut snapshots = Vec::new();
+        for _ in 0..MAX_SNAPSHOT_PAGES {
+            let Some(page_url) = url.take() else {
+                break;
+            };
+            let body = self.get_text(&page_url).await?;
+            let (page, next_url) = Self::parse_snapshot_page(&body)?;
+            debug!(
+                "Fetched {} Polygon option snapshots for {}",
+                page.len(),
+                underlying_symbol
+            );
+            snapshots.extend(page);
+            url = next_url;
+        }
+        if url.is_some() {
+            warn!(
+                "Stopped paging Polygon snapshots for {} after {} pages",
+                underlying_symbol, MAX_SNAPSHOT_PAGES
+            );
+        }
+
+        Ok(snapshots)
+    }
+
+    // The reference endpoint lists contracts without quotes or greeks, so it is far
+    // lighter than paging the full snapshot chain just to collect expiration dates.
+    pub async fn get_expirations(&self, underlying_symbol: &str) -> Result<Vec<NaiveDate>> {
+        let today = Utc::now().date_naive();
+        let mut url = Some(format!(
+            "{}/v3/reference/options/contracts?underlying_ticker={}&expired=false&expiration_date.gte={}&limit={}",
+            self.config.base_url,
+            underlying_symbol,
+            today.format("%Y-%m-%d"),
+            CONTRACTS_PAGE_LIMIT
+        ));
+        let mut expirations = Vec::new();
+        for _ in 0..MAX_SNAPSHOT_PAGES {
+            let Some(page_url) = url.take() else {
+                break;
+            };
+            let body = self.get_text(&page_url).await?;
+            let (page, next_url) = Self::parse_contracts_page(&body)?;
+            expirations.extend(page);
+            url = next_url;
+        }
+        if url.is_some() {
+            warn!(
+                "Stopped paging Polygon contracts for {} after {} pages",
+                underlying_symbol, MAX_SNAPSHOT_PAGES
+            );
+        }
+
+        expirations.retain(|d| *d >= today);
+        expirations.sort();
+        expirations.dedup();
+        Ok(expirations)
+    }
+
+    pub async fn get_underlying_price(&self, symbol: &str) -> Result<f64> {
+        let url = format!("{}/v2/last/nbbo/{}", self.config.base_url, symbol);
+        match self.get_text(&url).await {
+            Ok(body) => {
+                let data = serde_json::from_str::<LastQuoteResponse>(&body).map_err(|e| {
+                    OptionsError::ParseError(format!("Failed to parse Polygon quote: {}", e))
+                })?;
+                if let Some(nbbo) = data.results.filter(|q| q.bid > 0.0 && q.ask > 0.0) {
+                    return Ok((nbbo.bid + nbbo.ask) / 2.0);
+                }
+            }
+            Err(e) => debug!("Polygon NBBO unavailable for {}: {}", symbol, e),
+        }
+
+        // The NBBO endpoint needs a real-time stocks plan; fall back to the previous close.
+        let url = format!("{}/v2/aggs/ticker/{}/prev", self.config.base_url, symbol);
+        let body = self.get_text(&url).await?;
+        let data = serde_json::from_str::<PrevCloseResponse>(&body).map_err(|e| {
+            OptionsError::ParseError(format!("Failed to parse Polygon previous close: {}", e))
+        })?;
+        data.results.first().map(|agg| agg.c).ok_or_else(|| {
+            OptionsError::Other(format!("No Polygon price available for {}", symbol))
+        })
+    }
+}
+
+impl OptionDataProvider for PolygonClient {
+    fn option_chain_snapshots<'a>(
+        &'a self,
+        symbol: &'a str,
+        filter: &'a ChainSymbolFilter,
+    ) -> BoxFuture<'a, Result<Vec<OptionSnapshot>>> {
+        self.get_option_chain_snapshots(symbol, filter).boxed()
+    }
+
+    fn underlying_quote<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, Result<f64>> {
+        self.get_underlying_price(symbol).boxed()
+    }
+
+    fn expirations<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, Result<Vec<NaiveDate>>> {
+        self.get_expirations(symbol).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SNAPSHOT_PAGE: &str = r#"{
+        "status": "OK",
+        "request_id": "6a7e466379af0a71039d60cc78e72282",
+        "next_url": "https://api.polygon.io/v3/snapshot/options/SPY?cursor=YXA9MTAwMA",
+        "results": [
+            {
+                "break_even_price": 536.15,
+                "day": {
+                    "change": 0.4, "change_percent": 5.2, "close": 8.15, "high": 8.3,
+                    "last_updated": 1718913600000000000, "low": 7.6, "open": 7.75,
+                    "previous_close": 7.75, "volume": 1520, "vwap": 7.98
+                },
+                "details": {
+                    "contract_type": "call", "exercise_style": "american",
+                    "expiration_date": "2024-06-21", "shares_per_contract": 100,
+                    "strike_price": 528, "ticker": "O:SPY240621C00528000"
+                },
+                "greeks": {"delta": 0.61, "gamma": 0.021, "theta": -0.35, "vega": 0.44},
+                "implied_volatility": 0.142,
+                "last_quote": {
+                    "ask": 8.2, "ask_size": 40, "bid": 8.1, "bid_size": 25,
+                    "last_updated": 1718913599000000000, "midpoint": 8.15, "timeframe": "REAL-TIME"
+                },
+                "last_trade": {
+                    "conditions": [209], "exchange": 316, "price": 8.15,
+                    "sip_timestamp": 1718913598000000000, "size": 3, "timeframe": "REAL-TIME"
+                },
+                "open_interest": 10482,
+                "underlying_asset": {"price": 533.2, "ticker": "SPY", "timeframe": "REAL-TIME"}
+            },
+            {
+                "details": {
+                    "contract_type": "put", "expiration_date": "2024-06-21",
+                    "strike_price": 512.5, "ticker": "O:SPY240621P00512500"
+                },
+                "greeks": {},
+                "underlying_asset": {"ticker": "SPY"}
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn parses_snapshot_page() {
+        let (snapshots, next_url) = PolygonClient::parse_snapshot_page(SNAPSHOT_PAGE).unwrap();
+        assert_eq!(
+            next_url.as_deref(),
+            Some("https://api.polygon.io/v3/snapshot/options/SPY?cursor=YXA9MTAwMA")
+        );
+        assert_eq!(snapshots.len(), 2);
+
+        let call = &snapshots[0];
+        assert_eq!(call.symbol, "SPY240621C00528000");
+        assert_eq!(call.underlying_symbol, "SPY");
+        assert_eq!(call.strike_price, 528.0);
+        assert_eq!(call.contract_type, "call");
+        assert_eq!(call.implied_volatility, Some(0.142));
+        let greeks = call.greeks.as_ref().unwrap();
+        assert_eq!((greeks.delta, greeks.vega), (0.61, 0.44));
+        assert!(greeks.rho.is_nan());
+        let quote = call.last_quote.as_ref().unwrap();
+        assert_eq!((quote.bid, quote.ask, quote.size_bid), (8.1, 8.2, 25));
+        assert_eq!(call.last_trade.as_ref().unwrap().size, 3);
+        assert_eq!(call.daily_bar.as_ref().unwrap().c, 8.15);
+
+        let put = &snapshots[1];
+        assert_eq!(put.symbol, "SPY240621P00512500");
+        assert_eq!(put.strike_price, 512.5);
+        assert!(put.greeks.is_none());
+        assert!(put.last_quote.is_none());
+        assert!(put.daily_bar.is_none());
+    }
+
+    #[test]
+    fn parses_last_page_and_rejects_garbage() {
+        let (snapshots, next_url) =
+            PolygonClient::parse_snapshot_page(r#"{"status":"OK","results":[],"next_url":""}"#)
+                .unwrap();
+        assert!(snapshots.is_empty());
+        assert!(next_url.is_none());
+        assert!(PolygonClient::parse_snapshot_page("<html>").is_err());
+    }
+
+    #[test]
+    fn parses_contract_expirations() {
+        let body = r#"{
+            "results": [
+                {"expiration_date": "2024-06-21", "ticker": "O:SPY240621C00528000"},
+                {"expiration_date": "2024-06-28", "ticker": "O:SPY240628P00500000"},
+                {"expiration_date": "bad", "ticker": "O:SPY"}
+            ],
+            "next_url": "https://api.polygon.io/v3/reference/options/contracts?cursor=abc"
+        }"#;
+        let (expirations, next_url) = PolygonClient::parse_contracts_page(body).unwrap();
+        assert_eq!(
+            expirations,
+            vec![
+                NaiveDate::from_ymd_opt(2024, 6, 21).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 6, 28).unwrap(),
+            ]
+        );
+        assert!(next_url.is_some());
+    }
+}
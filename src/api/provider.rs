@@ -0,0 +1,105 @@
+use super::polygon::PolygonClient;
+use super::rest::{ChainSymbolFilter, OptionSnapshot, RestClient};
+use super::tradier::TradierClient;
+use crate::config::{Config, DataProviderKind};
+use crate::error::{OptionsError, Result};
+use crate::models::OptionContract;
+use chrono::NaiveDate;
+use futures::future::BoxFuture;
+use futures::{FutureExt, TryStreamExt};
+
+const ALPACA_SNAPSHOT_FEED: &str = "indicative";
+const ALPACA_SNAPSHOT_PAGE_LIMIT: u32 = 1000;
+
+pub trait OptionDataProvider: Send + Sync {
+    fn option_chain_snapshots<'a>(
+        &'a self,
+        symbol: &'a str,
+        filter: &'a ChainSymbolFilter,
+    ) -> BoxFuture<'a, Result<Vec<OptionSnapshot>>>;
+
+    fn underlying_quote<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, Result<f64>>;
+
+    fn expirations<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, Result<Vec<NaiveDate>>> {
+        async move {
+            let filter = ChainSymbolFilter {
+                expiration_date_gte: Some(chrono::Utc::now().format("%Y-%m-%d").to_string()),
+                ..Default::default()
+            };
+            let snapshots = self.option_chain_snapshots(symbol, &filter).await?;
+            let mut expirations: Vec<NaiveDate> = snapshots
+                .iter()
+                .filter_map(|s| OptionContract::from_occ_symbol(&s.symbol))
+                .map(|c| c.expiration.date_naive())
+                .collect();
+            expirations.sort();
+            expirations.dedup();
+            Ok(expirations)
+        }
+        .boxed()
+    }
+}
+
+impl OptionDataProvider for RestClient {
+    fn option_chain_snapshots<'a>(
+        &'a self,
+        symbol: &'a str,
+        filter: &'a ChainSymbolFilter,
+    ) -> BoxFuture<'a, Result<Vec<OptionSnapshot>>> {
+        self.stream_option_chain_snapshots(
+            symbol,
+            Some(ALPACA_SNAPSHOT_FEED),
+            Some(ALPACA_SNAPSHOT_PAGE_LIMIT),
+            filter,
+        )
+        .try_collect()
+        .boxed()
+    }
+
+    fn underlying_quote<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, Result<f64>> {
+        async move {
+            let resp = self
+                .get_latest_single_stock_quote(symbol, None, None)
+                .await?;
+            Ok((resp.quote.bid + resp.quote.ask) / 2.0)
+        }
+        .boxed()
+    }
+
+    fn expirations<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, Result<Vec<NaiveDate>>> {
+        async move {
+            let filter = ChainSymbolFilter {
+                expiration_date_gte: Some(chrono::Utc::now().format("%Y-%m-%d").to_string()),
+                ..Default::default()
+            };
+            let chain = self.get_all_options_contracts(symbol, &filter).await?;
+            let mut expirations: Vec<NaiveDate> = chain
+                .iter()
+                .filter_map(|c| OptionContract::from_occ_symbol(&c.symbol))
+                .map(|c| c.expiration.date_naive())
+                .collect();
+            expirations.sort();
+            expirations.dedup();
+            Ok(expirations)
+        }
+        .boxed()
+    }
+}
+
+pub fn provider_from_config(config: &Config) -> Result<Box<dyn OptionDataProvider>> {
+    match config.data_provider {
+        DataProviderKind::Alpaca => Ok(Box::new(RestClient::new(config.alpaca.clone()))),
+        DataProviderKind::Polygon => {
+            let polygon = config.polygon.clone().ok_or_else(|| {
+                OptionsError::ConfigError("Polygon provider selected without config".to_string())
+            })?;
+            Ok(Box::new(PolygonClient::new(polygon)))
+        }
+        DataProviderKind::Tradier => {
+            let tradier = config.tradier.clone().ok_or_else(|| {
+                OptionsError::ConfigError("Tradier provider selected without config".to_string())
+            })?;
+            Ok(Box::new(TradierClient::new(tradier)))
+        }
+    }
+}
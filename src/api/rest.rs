@@ -1,15 +1,84 @@
-use crate::config::AlpacaConfig;
+use crate::config::{AlpacaConfig, HttpOptions};
 use crate::error::{OptionsError, Result};
+use crate::models::OptionType;
 use chrono::{DateTime, Utc};
 use governor::{Quota, RateLimiter};
 use nonzero_ext::nonzero;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
+
+static REQUEST_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+const CONTRACTS_PAGE_LIMIT: u32 = 10000;
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const INITIAL_BACKOFF: std::time::Duration = std::time::Duration::from_secs(1);
+const MAX_BACKOFF: std::time::Duration = std::time::Duration::from_secs(30);
+const MAX_CONTRACT_PAGES: usize = 100;
+
+fn next_request_id() -> String {
+    let seq = REQUEST_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    format!("{:x}-{:06x}", nanos, seq)
+}
+
+fn retry_after(resp: &reqwest::Response) -> Option<std::time::Duration> {
+    resp.headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(std::time::Duration::from_secs)
+}
+
+fn network_error(e: reqwest::Error) -> OptionsError {
+    OptionsError::Network {
+        url: e.url().map(|u| u.to_string()).unwrap_or_default(),
+        message: e.without_url().to_string(),
+    }
+}
+
+// Statuses callers can act on become typed errors; anything else is left for
+// the call site to report along with the response body. Alpaca answers 403 for
+// plan and feed entitlements rather than bad keys, so its body is kept.
+async fn check_status(resp: reqwest::Response) -> Result<reqwest::Response> {
+    let url = resp.url().to_string();
+    match resp.status() {
+        reqwest::StatusCode::UNAUTHORIZED => Err(OptionsError::Unauthorized { url }),
+        reqwest::StatusCode::FORBIDDEN => Err(OptionsError::Forbidden {
+            url,
+            body: resp.text().await.unwrap_or_default(),
+        }),
+        reqwest::StatusCode::NOT_FOUND => Err(OptionsError::NotFound { url }),
+        reqwest::StatusCode::TOO_MANY_REQUESTS => Err(OptionsError::RateLimited {
+            retry_after: retry_after(&resp),
+        }),
+        _ => Ok(resp),
+    }
+}
+
+// Alpaca sends account balances as decimal strings.
+fn f64_from_string_or_number<'de, D>(deserializer: D) -> std::result::Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        Number(f64),
+        String(String),
+    }
+
+    match Value::deserialize(deserializer)? {
+        Value::Number(n) => Ok(n),
+        Value::String(s) => s.trim().parse().map_err(serde::de::Error::custom),
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Account {
     pub id: String,
+    #[serde(deserialize_with = "f64_from_string_or_number")]
     pub equity: f64,
 }
 
@@ -52,6 +121,17 @@ pub struct OptionContractsResponse {
     pub next_page_token: Option<String>,
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct ChainSymbolFilter {
+    pub expiration_date: Option<String>,
+    pub expiration_date_gte: Option<String>,
+    pub expiration_date_lte: Option<String>,
+    pub strike_price_gte: Option<f64>,
+    pub strike_price_lte: Option<f64>,
+    pub option_type: Option<OptionType>,
+    pub sort_by_atm: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptionBar {
     pub t: DateTime<Utc>,
@@ -70,6 +150,26 @@ pub struct OptionBarsResponse {
     pub next_page_token: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockBar {
+    pub t: DateTime<Utc>,
+    pub o: f64,
+    pub h: f64,
+    pub l: f64,
+    pub c: f64,
+    pub v: u64,
+    pub vw: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockBarsResponse {
+    #[serde(default)]
+    pub bars: Vec<StockBar>,
+    #[serde(default)]
+    pub symbol: String,
+    pub next_page_token: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptionTrade {
     pub t: DateTime<Utc>,
@@ -136,6 +236,12 @@ pub struct OptionLastTrade {
     pub condition: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatestOptionTradesResponse {
+    #[serde(default)]
+    pub trades: std::collections::HashMap<String, OptionLastTrade>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptionLastQuote {
     pub t: DateTime<Utc>,
@@ -176,7 +282,9 @@ pub struct OptionSnapshot {
     pub expiration_date: String,
     #[serde(default)]
     pub contract_type: String,
+    #[serde(alias = "latestTrade")]
     pub last_trade: Option<OptionLastTrade>,
+    #[serde(alias = "latestQuote")]
     pub last_quote: Option<OptionLastQuote>,
     pub greeks: Option<OptionGreeks>,
     #[serde(rename = "impliedVolatility")]
@@ -189,6 +297,51 @@ pub struct OptionSnapshot {
     pub prev_daily_bar: Option<OptionBar>,
 }
 
+impl OptionSnapshot {
+    pub fn has_greeks(&self) -> bool {
+        self.greeks.is_some()
+    }
+
+    pub fn has_implied_volatility(&self) -> bool {
+        self.implied_volatility
+            .is_some_and(|iv| iv.is_finite() && iv > 0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SnapshotFeedCoverage {
+    pub inspected: usize,
+    pub with_greeks: usize,
+    pub with_implied_volatility: usize,
+}
+
+impl SnapshotFeedCoverage {
+    pub fn observe(&mut self, snapshot: &OptionSnapshot) {
+        self.inspected += 1;
+        if snapshot.has_greeks() {
+            self.with_greeks += 1;
+        }
+        if snapshot.has_implied_volatility() {
+            self.with_implied_volatility += 1;
+        }
+    }
+
+    pub fn from_snapshots<'a, I>(snapshots: I, sample_size: usize) -> Self
+    where
+        I: IntoIterator<Item = &'a OptionSnapshot>,
+    {
+        let mut coverage = Self::default();
+        for snapshot in snapshots.into_iter().take(sample_size) {
+            coverage.observe(snapshot);
+        }
+        coverage
+    }
+
+    pub fn lacks_vendor_analytics(&self) -> bool {
+        self.inspected > 0 && self.with_greeks == 0 && self.with_implied_volatility == 0
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptionSnapshotsResponse {
     pub snapshots: std::collections::HashMap<String, OptionSnapshot>,
@@ -205,35 +358,106 @@ pub struct RestClient {
             governor::clock::DefaultClock,
         >,
     >,
+    max_retries: u32,
 }
 
 impl RestClient {
     pub fn new(config: AlpacaConfig) -> Self {
+        Self::with_client(config, reqwest::Client::new())
+    }
+
+    pub fn with_http_options(config: AlpacaConfig, options: &HttpOptions) -> Result<Self> {
+        let mut builder = reqwest::Client::builder()
+            .timeout(options.timeout)
+            .danger_accept_invalid_certs(options.danger_accept_invalid_certs);
+
+        if let Some(proxy) = &options.proxy {
+            let proxy = reqwest::Proxy::all(proxy).map_err(|e| {
+                OptionsError::ConfigError(format!("Invalid proxy '{}': {}", proxy, e))
+            })?;
+            builder = builder.proxy(proxy);
+        }
+
+        let client = builder.build().map_err(|e| {
+            OptionsError::ConfigError(format!("Failed to build HTTP client: {}", e))
+        })?;
+
+        Ok(Self::with_client(config, client))
+    }
+
+    /// Points both the trading host (`base_url` and `paper_url`, which the
+    /// account and contract endpoints use) and the market data host elsewhere.
+    pub fn with_base_url(mut config: AlpacaConfig, base_url: &str, data_url: &str) -> Self {
+        config.base_url = base_url.trim_end_matches('/').to_string();
+        config.paper_url = config.base_url.clone();
+        config.data_url = data_url.trim_end_matches('/').to_string();
+        Self::new(config)
+    }
+
+    fn with_client(config: AlpacaConfig, client: reqwest::Client) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client,
             config,
             limiter: Arc::new(governor::RateLimiter::direct(Quota::per_minute(nonzero!(
                 200u32
             )))),
+            max_retries: DEFAULT_MAX_RETRIES,
         }
     }
 
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
     async fn auth(&self, req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
         self.limiter.until_ready().await;
 
-        req.header("APCA-API-KEY-ID", &self.config.api_key)
+        let req = req
+            .header("APCA-API-KEY-ID", &self.config.api_key)
             .header("APCA-API-SECRET-KEY", &self.config.api_secret)
+            .header(reqwest::header::USER_AGENT, &self.config.user_agent);
+
+        if self.config.send_request_id {
+            let request_id = next_request_id();
+            debug!("Sending request with X-Request-Id {}", request_id);
+            req.header("X-Request-Id", request_id)
+        } else {
+            req
+        }
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let mut backoff = INITIAL_BACKOFF;
+        for attempt in 0..self.max_retries {
+            let Some(retry) = request.try_clone() else {
+                break;
+            };
+            let resp = retry.send().await.map_err(network_error)?;
+            if resp.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                return check_status(resp).await;
+            }
+
+            let wait = retry_after(&resp).unwrap_or(backoff).min(MAX_BACKOFF);
+            warn!(
+                "Rate limited by {} (attempt {}/{}), retrying in {:?}",
+                resp.url(),
+                attempt + 1,
+                self.max_retries,
+                wait
+            );
+            tokio::time::sleep(wait).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            self.limiter.until_ready().await;
+        }
+        let resp = request.send().await.map_err(network_error)?;
+        check_status(resp).await
     }
 
     pub async fn get_account(&self) -> Result<Account> {
         debug!("Getting account information");
         let url = format!("{}/v2/account", self.config.paper_url);
-        let resp = self
-            .auth(self.client.get(&url))
-            .await
-            .send()
-            .await
-            .map_err(|e| OptionsError::Other(format!("Request failed: {}", e)))?;
+        let resp = self.send(self.auth(self.client.get(&url)).await).await?;
         let acc = resp
             .json::<Account>()
             .await
@@ -247,12 +471,7 @@ impl RestClient {
         if let Some(class) = asset_class {
             url.push_str(&format!("?asset_class={}", class));
         }
-        let resp = self
-            .auth(self.client.get(&url))
-            .await
-            .send()
-            .await
-            .map_err(|e| OptionsError::Other(format!("Request failed: {}", e)))?;
+        let resp = self.send(self.auth(self.client.get(&url)).await).await?;
         let assets = resp
             .json::<Vec<Asset>>()
             .await
@@ -306,12 +525,12 @@ impl RestClient {
         }
 
         let resp = self
-            .auth(self.client.get(&url))
-            .await
-            .timeout(std::time::Duration::from_secs(30))
-            .send()
-            .await
-            .map_err(|e| OptionsError::Other(format!("Failed to get options chain: {}", e)))?;
+            .send(
+                self.auth(self.client.get(&url))
+                    .await
+                    .timeout(std::time::Duration::from_secs(30)),
+            )
+            .await?;
 
         let data = resp.json::<OptionContractsResponse>().await.map_err(|e| {
             OptionsError::ParseError(format!("Failed to parse options chain: {}", e))
@@ -331,6 +550,130 @@ impl RestClient {
         Ok(data)
     }
 
+    async fn get_options_contracts_page(
+        &self,
+        symbol: &str,
+        filter: &ChainSymbolFilter,
+        page_token: Option<&str>,
+    ) -> Result<OptionContractsResponse> {
+        let mut url = format!(
+            "{}/v2/options/contracts?underlying_symbols={}&limit={}",
+            self.config.paper_url, symbol, CONTRACTS_PAGE_LIMIT
+        );
+
+        if let Some(date) = &filter.expiration_date {
+            url.push_str(&format!("&expiration_date={}", date));
+        }
+        if let Some(date) = &filter.expiration_date_gte {
+            url.push_str(&format!("&expiration_date_gte={}", date));
+        }
+        if let Some(date) = &filter.expiration_date_lte {
+            url.push_str(&format!("&expiration_date_lte={}", date));
+        }
+        if let Some(strike) = filter.strike_price_gte {
+            url.push_str(&format!("&strike_price_gte={}", strike));
+        }
+        if let Some(strike) = filter.strike_price_lte {
+            url.push_str(&format!("&strike_price_lte={}", strike));
+        }
+        if let Some(option_type) = filter.option_type {
+            let kind = match option_type {
+                OptionType::Call => "call",
+                OptionType::Put => "put",
+            };
+            url.push_str(&format!("&type={}", kind));
+        }
+        if let Some(token) = page_token {
+            url.push_str(&format!("&page_token={}", token));
+        }
+
+        let resp = self.send(self.auth(self.client.get(&url)).await).await?;
+
+        resp.json::<OptionContractsResponse>()
+            .await
+            .map_err(|e| OptionsError::ParseError(format!("Failed to parse options chain: {}", e)))
+    }
+
+    pub async fn get_all_options_contracts(
+        &self,
+        symbol: &str,
+        filter: &ChainSymbolFilter,
+    ) -> Result<Vec<OptionContract>> {
+        let mut contracts = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        for page in 1..=MAX_CONTRACT_PAGES {
+            let data = self
+                .get_options_contracts_page(symbol, filter, page_token.as_deref())
+                .await?;
+            debug!(
+                "Fetched page {} with {} contracts for {}",
+                page,
+                data.results.len(),
+                symbol
+            );
+            contracts.extend(data.results);
+
+            match data.next_page_token {
+                Some(token) if !token.is_empty() => page_token = Some(token),
+                _ => {
+                    info!("Got {} option contracts for {}", contracts.len(), symbol);
+                    return Ok(contracts);
+                }
+            }
+        }
+
+        warn!(
+            "Stopped paginating option contracts for {} after {} pages ({} contracts)",
+            symbol,
+            MAX_CONTRACT_PAGES,
+            contracts.len()
+        );
+        Ok(contracts)
+    }
+
+    pub async fn chain_symbols(
+        &self,
+        symbol: &str,
+        filter: &ChainSymbolFilter,
+        max: Option<usize>,
+    ) -> Result<Vec<String>> {
+        let chain = self.get_all_options_contracts(symbol, filter).await?;
+
+        let mut contracts: Vec<crate::models::OptionContract> = chain
+            .iter()
+            .filter_map(|c| crate::models::OptionContract::from_occ_symbol(&c.symbol))
+            .filter(|c| filter.option_type.is_none() || filter.option_type == Some(c.option_type))
+            .collect();
+
+        if filter.sort_by_atm && !contracts.is_empty() {
+            let quote = self
+                .get_latest_single_stock_quote(symbol, None, None)
+                .await?;
+            let spot = (quote.quote.bid + quote.quote.ask) / 2.0;
+            contracts.sort_by(|a, b| {
+                (a.strike - spot)
+                    .abs()
+                    .partial_cmp(&(b.strike - spot).abs())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then_with(|| a.expiration.cmp(&b.expiration))
+            });
+        }
+
+        let symbols = contracts
+            .into_iter()
+            .map(|c| c.option_symbol)
+            .take(max.unwrap_or(usize::MAX))
+            .collect::<Vec<_>>();
+
+        debug!(
+            "Built {} subscription symbols for {}",
+            symbols.len(),
+            symbol
+        );
+        Ok(symbols)
+    }
+
     pub async fn get_options_bars(
         &self,
         symbols: &[&str],
@@ -367,12 +710,7 @@ impl RestClient {
             url.push_str(&format!("&sort={}", sort_order));
         }
 
-        let resp = self
-            .auth(self.client.get(&url))
-            .await
-            .send()
-            .await
-            .map_err(|e| OptionsError::Other(format!("Failed to get options bars: {}", e)))?;
+        let resp = self.send(self.auth(self.client.get(&url)).await).await?;
 
         let data = resp.json::<OptionBarsResponse>().await.map_err(|e| {
             OptionsError::ParseError(format!("Failed to parse options bars: {}", e))
@@ -381,6 +719,46 @@ impl RestClient {
         Ok(data)
     }
 
+    pub async fn get_stock_bars(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        timeframe: &str,
+        limit: Option<u32>,
+        page_token: Option<&str>,
+    ) -> Result<StockBarsResponse> {
+        debug!(
+            "Getting stock bars for {} from {} to {}",
+            symbol, start, end
+        );
+        let mut url = format!(
+            "{}/v2/stocks/{}/bars?start={}&end={}&timeframe={}",
+            self.config.data_url,
+            symbol,
+            start.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            end.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            timeframe
+        );
+
+        if let Some(limit_val) = limit {
+            url.push_str(&format!("&limit={}", limit_val));
+        }
+
+        if let Some(token) = page_token {
+            url.push_str(&format!("&page_token={}", token));
+        }
+
+        let resp = self.send(self.auth(self.client.get(&url)).await).await?;
+
+        let data = resp
+            .json::<StockBarsResponse>()
+            .await
+            .map_err(|e| OptionsError::ParseError(format!("Failed to parse stock bars: {}", e)))?;
+
+        Ok(data)
+    }
+
     pub async fn get_options_trades(
         &self,
         symbols: &[&str],
@@ -417,12 +795,7 @@ impl RestClient {
             url.push_str(&format!("&sort={}", sort_order));
         }
 
-        let resp = self
-            .auth(self.client.get(&url))
-            .await
-            .send()
-            .await
-            .map_err(|e| OptionsError::Other(format!("Failed to get options trades: {}", e)))?;
+        let resp = self.send(self.auth(self.client.get(&url)).await).await?;
 
         let data = resp.json::<OptionTradesResponse>().await.map_err(|e| {
             OptionsError::ParseError(format!("Failed to parse options trades: {}", e))
@@ -439,12 +812,7 @@ impl RestClient {
             self.config.data_url, symbols_str
         );
 
-        let resp = self
-            .auth(self.client.get(&url))
-            .await
-            .send()
-            .await
-            .map_err(|e| OptionsError::Other(format!("Failed to get options quotes: {}", e)))?;
+        let resp = self.send(self.auth(self.client.get(&url)).await).await?;
 
         let data = resp.json::<OptionQuotesResponse>().await.map_err(|e| {
             OptionsError::ParseError(format!("Failed to parse options quotes: {}", e))
@@ -484,10 +852,7 @@ impl RestClient {
         }
 
         let request = self.auth(self.client.get(&url)).await;
-        let resp = request
-            .send()
-            .await
-            .map_err(|e| OptionsError::Other(format!("Failed to get option snapshots: {}", e)))?;
+        let resp = self.send(request).await?;
 
         let data = resp.json::<OptionSnapshotsResponse>().await.map_err(|e| {
             OptionsError::ParseError(format!("Failed to parse option snapshots: {}", e))
@@ -558,9 +923,7 @@ impl RestClient {
         }
 
         let request = self.auth(self.client.get(&url)).await;
-        let resp = request.send().await.map_err(|e| {
-            OptionsError::Other(format!("Failed to get option chain snapshots: {}", e))
-        })?;
+        let resp = self.send(request).await?;
 
         if !resp.status().is_success() {
             let status = resp.status();
@@ -598,6 +961,66 @@ impl RestClient {
         Ok(data)
     }
 
+    pub fn stream_option_chain_snapshots<'a>(
+        &'a self,
+        underlying_symbol: &'a str,
+        feed: Option<&'a str>,
+        limit: Option<u32>,
+        filter: &'a ChainSymbolFilter,
+    ) -> impl futures::Stream<Item = Result<OptionSnapshot>> + 'a {
+        let option_type = filter.option_type.map(|t| match t {
+            OptionType::Call => "call",
+            OptionType::Put => "put",
+        });
+        let state = (
+            std::collections::VecDeque::<OptionSnapshot>::new(),
+            Some(None::<String>),
+        );
+
+        futures::stream::unfold(state, move |(mut buffer, mut next_page)| async move {
+            loop {
+                if let Some(snapshot) = buffer.pop_front() {
+                    return Some((Ok(snapshot), (buffer, next_page)));
+                }
+                let page_token = next_page.take()?;
+                let page = self
+                    .get_option_chain_snapshots(
+                        underlying_symbol,
+                        feed,
+                        limit,
+                        None,
+                        page_token.as_deref(),
+                        option_type,
+                        filter.strike_price_gte,
+                        filter.strike_price_lte,
+                        filter.expiration_date.as_deref(),
+                        filter.expiration_date_gte.as_deref(),
+                        filter.expiration_date_lte.as_deref(),
+                        None,
+                    )
+                    .await;
+
+                match page {
+                    Ok(data) => {
+                        debug!(
+                            "Streaming {} option snapshots for {}",
+                            data.snapshots.len(),
+                            underlying_symbol
+                        );
+                        next_page = data.next_page_token.filter(|t| !t.is_empty()).map(Some);
+                        buffer.extend(data.snapshots.into_iter().map(|(symbol, mut snapshot)| {
+                            if snapshot.symbol.is_empty() {
+                                snapshot.symbol = symbol;
+                            }
+                            snapshot
+                        }));
+                    }
+                    Err(e) => return Some((Err(e), (buffer, None))),
+                }
+            }
+        })
+    }
+
     pub async fn get_options_condition_codes(&self, tick_type: &str) -> Result<serde_json::Value> {
         debug!(
             "Getting options condition codes for tick type: {}",
@@ -609,10 +1032,7 @@ impl RestClient {
         );
 
         let request = self.auth(self.client.get(&url)).await;
-        let resp = request
-            .send()
-            .await
-            .map_err(|e| OptionsError::Other(format!("Failed to get condition codes: {}", e)))?;
+        let resp = self.send(request).await?;
 
         let data = resp.json::<serde_json::Value>().await.map_err(|e| {
             OptionsError::ParseError(format!("Failed to parse condition codes: {}", e))
@@ -626,10 +1046,7 @@ impl RestClient {
         let url = format!("{}/v1beta1/options/meta/exchanges", self.config.data_url);
 
         let request = self.auth(self.client.get(&url)).await;
-        let resp = request
-            .send()
-            .await
-            .map_err(|e| OptionsError::Other(format!("Failed to get exchange codes: {}", e)))?;
+        let resp = self.send(request).await?;
 
         let data = resp.json::<serde_json::Value>().await.map_err(|e| {
             OptionsError::ParseError(format!("Failed to parse exchange codes: {}", e))
@@ -638,7 +1055,10 @@ impl RestClient {
         Ok(data)
     }
 
-    pub async fn get_latest_options_trades(&self, symbols: &[&str]) -> Result<serde_json::Value> {
+    pub async fn get_latest_options_trades(
+        &self,
+        symbols: &[&str],
+    ) -> Result<LatestOptionTradesResponse> {
         debug!("Getting latest option trades for symbols: {:?}", symbols);
         let symbols_str = symbols.join(",");
         let url = format!(
@@ -647,14 +1067,14 @@ impl RestClient {
         );
 
         let request = self.auth(self.client.get(&url)).await;
-        let resp = request
-            .send()
-            .await
-            .map_err(|e| OptionsError::Other(format!("Failed to get latest trades: {}", e)))?;
+        let resp = self.send(request).await?;
 
-        let data = resp.json::<serde_json::Value>().await.map_err(|e| {
-            OptionsError::ParseError(format!("Failed to parse latest trades: {}", e))
-        })?;
+        let data = resp
+            .json::<LatestOptionTradesResponse>()
+            .await
+            .map_err(|e| {
+                OptionsError::ParseError(format!("Failed to parse latest trades: {}", e))
+            })?;
 
         Ok(data)
     }
@@ -666,10 +1086,7 @@ impl RestClient {
         );
 
         let request = self.auth(self.client.get(&url)).await;
-        let resp = request
-            .send()
-            .await
-            .map_err(|e| OptionsError::Other(format!("Failed to get stock snapshot: {}", e)))?;
+        let resp = self.send(request).await?;
 
         let data = resp.json::<serde_json::Value>().await.map_err(|e| {
             OptionsError::ParseError(format!("Failed to parse stock snapshot: {}", e))
@@ -689,9 +1106,7 @@ impl RestClient {
         );
 
         let request = self.auth(self.client.get(&url)).await;
-        let resp = request.send().await.map_err(|e| {
-            OptionsError::Other(format!("Failed to get latest stock quotes: {}", e))
-        })?;
+        let resp = self.send(request).await?;
 
         let data = resp
             .json::<LatestStockQuotesResponse>()
@@ -728,10 +1143,7 @@ impl RestClient {
         }
 
         let request = self.auth(self.client.get(&url)).await;
-        let resp = request
-            .send()
-            .await
-            .map_err(|e| OptionsError::Other(format!("Failed to get latest stock quote: {}", e)))?;
+        let resp = self.send(request).await?;
 
         let data = resp.json::<SingleStockQuoteResponse>().await.map_err(|e| {
             OptionsError::ParseError(format!("Failed to parse latest stock quote: {}", e))
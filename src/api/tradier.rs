@@ -0,0 +1,453 @@
+use super::provider::OptionDataProvider;
+use super::rest::{
+    ChainSymbolFilter, OptionBar, OptionGreeks, OptionLastQuote, OptionLastTrade, OptionSnapshot,
+};
+use crate::config::TradierConfig;
+use crate::error::{OptionsError, Result};
+use crate::models::OptionType;
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde::Deserialize;
+use tracing::debug;
+
+// Tradier collapses single-element arrays into a bare object.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> OneOrMany<T> {
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            OneOrMany::One(item) => vec![item],
+            OneOrMany::Many(items) => items,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChainResponse {
+    options: Option<ChainOptions>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChainOptions {
+    option: Option<OneOrMany<TradierOption>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TradierOption {
+    symbol: String,
+    #[serde(default)]
+    underlying: String,
+    strike: f64,
+    expiration_date: String,
+    option_type: String,
+    last: Option<f64>,
+    open: Option<f64>,
+    high: Option<f64>,
+    low: Option<f64>,
+    close: Option<f64>,
+    #[serde(default)]
+    volume: u64,
+    #[serde(default)]
+    last_volume: u64,
+    trade_date: Option<i64>,
+    bid: Option<f64>,
+    ask: Option<f64>,
+    #[serde(default)]
+    bidsize: u64,
+    #[serde(default)]
+    asksize: u64,
+    bidexch: Option<String>,
+    askexch: Option<String>,
+    bid_date: Option<i64>,
+    ask_date: Option<i64>,
+    greeks: Option<TradierGreeks>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TradierGreeks {
+    delta: Option<f64>,
+    gamma: Option<f64>,
+    theta: Option<f64>,
+    vega: Option<f64>,
+    rho: Option<f64>,
+    mid_iv: Option<f64>,
+    smv_vol: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExpirationsResponse {
+    expirations: Option<ExpirationDates>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExpirationDates {
+    date: Option<OneOrMany<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuotesResponse {
+    quotes: Option<Quotes>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Quotes {
+    quote: Option<OneOrMany<TradierQuote>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TradierQuote {
+    bid: Option<f64>,
+    ask: Option<f64>,
+    last: Option<f64>,
+}
+
+fn from_millis(millis: Option<i64>) -> DateTime<Utc> {
+    millis
+        .and_then(DateTime::<Utc>::from_timestamp_millis)
+        .unwrap_or_else(Utc::now)
+}
+
+impl From<TradierOption> for OptionSnapshot {
+    fn from(o: TradierOption) -> Self {
+        let (greeks, implied_volatility) = match o.greeks {
+            Some(g) => {
+                let greeks = (|| {
+                    Some(OptionGreeks {
+                        delta: g.delta?,
+                        gamma: g.gamma?,
+                        theta: g.theta?,
+                        vega: g.vega?,
+                        rho: g.rho?,
+                    })
+                })();
+                (greeks, g.mid_iv.or(g.smv_vol))
+            }
+            None => (None, None),
+        };
+
+        let last_quote = match (o.bid, o.ask) {
+            (Some(bid), Some(ask)) => Some(OptionLastQuote {
+                t: from_millis(o.bid_date.max(o.ask_date)),
+                bid,
+                ask,
+                size_bid: o.bidsize,
+                size_ask: o.asksize,
+                bid_exchange: o.bidexch,
+                ask_exchange: o.askexch,
+                condition: None,
+            }),
+            _ => None,
+        };
+
+        let last_trade = o.last.map(|price| OptionLastTrade {
+            t: from_millis(o.trade_date),
+            price,
+            size: o.last_volume,
+            conditions: Vec::new(),
+            exchange_code: String::new(),
+            condition: None,
+        });
+
+        let daily_bar = o.close.or(o.last).map(|c| OptionBar {
+            t: from_millis(o.trade_date),
+            o: o.open.unwrap_or(f64::NAN),
+            h: o.high.unwrap_or(f64::NAN),
+            l: o.low.unwrap_or(f64::NAN),
+            c,
+            v: o.volume,
+            n: None,
+            vw: f64::NAN,
+        });
+
+        OptionSnapshot {
+            symbol: o.symbol,
+            underlying_symbol: o.underlying,
+            strike_price: o.strike,
+            expiration_date: o.expiration_date,
+            contract_type: o.option_type,
+            last_trade,
+            last_quote,
+            greeks,
+            implied_volatility,
+            daily_bar,
+            minute_bar: None,
+            prev_daily_bar: None,
+        }
+    }
+}
+
+pub struct TradierClient {
+    client: reqwest::Client,
+    config: TradierConfig,
+}
+
+impl TradierClient {
+    pub fn new(config: TradierConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    pub fn parse_chain(body: &str) -> Result<Vec<OptionSnapshot>> {
+        let data = serde_json::from_str::<ChainResponse>(body).map_err(|e| {
+            OptionsError::ParseError(format!("Failed to parse Tradier option chain: {}", e))
+        })?;
+        Ok(data
+            .options
+            .and_then(|o| o.option)
+            .map(OneOrMany::into_vec)
+            .unwrap_or_default()
+            .into_iter()
+            .map(OptionSnapshot::from)
+            .collect())
+    }
+
+    async fn get_text(&self, path: &str, query: &[(&str, &str)]) -> Result<String> {
+        let url = format!("{}{}", self.config.base_url(), path);
+        let resp = self
+            .client
+            .get(&url)
+            .query(query)
+            .bearer_auth(&self.config.token)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .send()
+            .await
+            .map_err(|e| OptionsError::Other(format!("Tradier request failed: {}", e)))?;
+
+        let status = resp.status();
+        let text = resp
+            .text()
+            .await
+            .map_err(|e| OptionsError::Other(format!("Failed to get response text: {}", e)))?;
+        if !status.is_success() {
+            return Err(OptionsError::Other(format!(
+                "Tradier request to {} failed with status {}: {}",
+                path, status, text
+            )));
+        }
+        Ok(text)
+    }
+
+    pub async fn get_expirations(&self, symbol: &str) -> Result<Vec<NaiveDate>> {
+        let body = self
+            .get_text("/v1/markets/options/expirations", &[("symbol", symbol)])
+            .await?;
+        let data = serde_json::from_str::<ExpirationsResponse>(&body).map_err(|e| {
+            OptionsError::ParseError(format!("Failed to parse Tradier expirations: {}", e))
+        })?;
+
+        let mut expirations: Vec<NaiveDate> = data
+            .expirations
+            .and_then(|e| e.date)
+            .map(OneOrMany::into_vec)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+            .collect();
+        expirations.sort();
+        Ok(expirations)
+    }
+
+    pub async fn get_option_chain(
+        &self,
+        symbol: &str,
+        expiration: NaiveDate,
+    ) -> Result<Vec<OptionSnapshot>> {
+        let expiration = expiration.format("%Y-%m-%d").to_string();
+        let body = self
+            .get_text(
+                "/v1/markets/options/chains",
+                &[
+                    ("symbol", symbol),
+                    ("expiration", &expiration),
+                    ("greeks", "true"),
+                ],
+            )
+            .await?;
+        Self::parse_chain(&body)
+    }
+
+    // The chains endpoint serves one expiration per request, so date ranges are
+    // expanded against the expirations list and strike/type filters are applied here.
+    pub async fn get_option_chain_snapshots(
+        &self,
+        symbol: &str,
+        filter: &ChainSymbolFilter,
+    ) -> Result<Vec<OptionSnapshot>> {
+        let parse_date = |d: &Option<String>| {
+            d.as_deref()
+                .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        };
+        let expirations = match parse_date(&filter.expiration_date) {
+            Some(date) => vec![date],
+            None => {
+                let gte = parse_date(&filter.expiration_date_gte);
+                let lte = parse_date(&filter.expiration_date_lte);
+                self.get_expirations(symbol)
+                    .await?
+                    .into_iter()
+                    .filter(|d| gte.is_none_or(|gte| *d >= gte))
+                    .filter(|d| lte.is_none_or(|lte| *d <= lte))
+                    .collect()
+            }
+        };
+
+        let option_type = filter.option_type.map(|t| match t {
+            OptionType::Call => "call",
+            OptionType::Put => "put",
+        });
+        let mut snapshots = Vec::new();
+        for expiration in expirations {
+            let chain = self.get_option_chain(symbol, expiration).await?;
+            debug!(
+                "Fetched {} Tradier options for {} exp {}",
+                chain.len(),
+                symbol,
+                expiration
+            );
+            snapshots.extend(chain.into_iter().filter(|s| {
+                filter.strike_price_gte.is_none_or(|k| s.strike_price >= k)
+                    && filter.strike_price_lte.is_none_or(|k| s.strike_price <= k)
+                    && option_type.is_none_or(|t| s.contract_type == t)
+            }));
+        }
+
+        Ok(snapshots)
+    }
+
+    pub async fn get_underlying_price(&self, symbol: &str) -> Result<f64> {
+        let body = self
+            .get_text("/v1/markets/quotes", &[("symbols", symbol)])
+            .await?;
+        let data = serde_json::from_str::<QuotesResponse>(&body).map_err(|e| {
+            OptionsError::ParseError(format!("Failed to parse Tradier quote: {}", e))
+        })?;
+
+        let quote = data
+            .quotes
+            .and_then(|q| q.quote)
+            .map(OneOrMany::into_vec)
+            .unwrap_or_default()
+            .into_iter()
+            .next()
+            .ok_or_else(|| OptionsError::Other(format!("No Tradier quote for {}", symbol)))?;
+        match (quote.bid, quote.ask, quote.last) {
+            (Some(bid), Some(ask), _) if bid > 0.0 && ask > 0.0 => Ok((bid + ask) / 2.0),
+            (_, _, Some(last)) => Ok(last),
+            _ => Err(OptionsError::Other(format!(
+                "No Tradier price available for {}",
+                symbol
+            ))),
+        }
+    }
+}
+
+impl OptionDataProvider for TradierClient {
+    fn option_chain_snapshots<'a>(
+        &'a self,
+        symbol: &'a str,
+        filter: &'a ChainSymbolFilter,
+    ) -> BoxFuture<'a, Result<Vec<OptionSnapshot>>> {
+        self.get_option_chain_snapshots(symbol, filter).boxed()
+    }
+
+    fn underlying_quote<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, Result<f64>> {
+        self.get_underlying_price(symbol).boxed()
+    }
+
+    fn expirations<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, Result<Vec<NaiveDate>>> {
+        async move {
+            let today = Utc::now().date_naive();
+            let mut expirations = self.get_expirations(symbol).await?;
+            expirations.retain(|d| *d >= today);
+            Ok(expirations)
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAIN: &str = r#"{
+        "options": {
+            "option": [
+                {
+                    "symbol": "VXX190517P00016000", "description": "VXX May 17 2019 $16.00 Put",
+                    "exch": "Z", "type": "option", "last": 0.25, "change": null, "volume": 12,
+                    "open": null, "high": null, "low": null, "close": null,
+                    "bid": 0.2, "ask": 0.3, "underlying": "VXX", "strike": 16.0,
+                    "change_percentage": null, "average_volume": 0, "last_volume": 2,
+                    "trade_date": 1557172318000, "prevclose": null, "week_52_high": 0.0,
+                    "week_52_low": 0.0, "bidsize": 55, "bidexch": "C", "bid_date": 1557172618000,
+                    "asksize": 1011, "askexch": "Z", "ask_date": 1557172618000,
+                    "open_interest": 0, "contract_size": 100, "expiration_date": "2019-05-17",
+                    "expiration_type": "standard", "option_type": "put", "root_symbol": "VXX",
+                    "greeks": {
+                        "delta": -0.211, "gamma": 0.142, "theta": -0.011, "vega": 0.012,
+                        "rho": -0.001, "phi": 0.0, "bid_iv": 0.55, "mid_iv": 0.58, "ask_iv": 0.61,
+                        "smv_vol": 0.6, "updated_at": "2019-05-06 19:59:15"
+                    }
+                },
+                {
+                    "symbol": "VXX190517C00016000", "underlying": "VXX", "strike": 16.0,
+                    "expiration_date": "2019-05-17", "option_type": "call",
+                    "last": null, "bid": 12.5, "ask": null, "greeks": null
+                }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn parses_chain_with_greeks() {
+        let chain = TradierClient::parse_chain(CHAIN).unwrap();
+        assert_eq!(chain.len(), 2);
+
+        let put = &chain[0];
+        assert_eq!(put.symbol, "VXX190517P00016000");
+        assert_eq!(put.underlying_symbol, "VXX");
+        assert_eq!(put.strike_price, 16.0);
+        assert_eq!(put.contract_type, "put");
+        assert_eq!(put.implied_volatility, Some(0.58));
+        let greeks = put.greeks.as_ref().unwrap();
+        assert_eq!((greeks.delta, greeks.rho), (-0.211, -0.001));
+        let quote = put.last_quote.as_ref().unwrap();
+        assert_eq!((quote.bid, quote.ask, quote.size_ask), (0.2, 0.3, 1011));
+        assert_eq!(quote.t.timestamp_millis(), 1557172618000);
+        let trade = put.last_trade.as_ref().unwrap();
+        assert_eq!((trade.price, trade.size), (0.25, 2));
+        assert_eq!(put.daily_bar.as_ref().unwrap().c, 0.25);
+
+        let call = &chain[1];
+        assert!(call.greeks.is_none());
+        assert!(call.implied_volatility.is_none());
+        assert!(call.last_quote.is_none());
+        assert!(call.last_trade.is_none());
+    }
+
+    #[test]
+    fn parses_single_option_and_empty_chains() {
+        let single = r#"{"options": {"option": {
+            "symbol": "SPY240621C00528000", "underlying": "SPY", "strike": 528.0,
+            "expiration_date": "2024-06-21", "option_type": "call", "bid": 8.1, "ask": 8.2,
+            "greeks": {"delta": 0.61, "gamma": 0.02, "theta": -0.3, "vega": 0.4, "rho": 0.1, "mid_iv": 0.14}
+        }}}"#;
+        let chain = TradierClient::parse_chain(single).unwrap();
+        assert_eq!(chain.len(), 1);
+        assert_eq!(chain[0].symbol, "SPY240621C00528000");
+        assert_eq!(chain[0].greeks.as_ref().unwrap().delta, 0.61);
+
+        assert!(TradierClient::parse_chain(r#"{"options": null}"#)
+            .unwrap()
+            .is_empty());
+        assert!(TradierClient::parse_chain("not json").is_err());
+    }
+}
@@ -1,25 +1,24 @@
 use crate::config::AlpacaConfig;
 use crate::error::{OptionsError, Result};
-use crate::models::{OptionContract, OptionQuote as ModelOptionQuote, OptionType};
+use crate::models::{
+    MidPriceSmoother, OptionContract, OptionQuote as ModelOptionQuote, OptionType,
+};
 use chrono::{DateTime, Utc};
+use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::{mpsc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex, MutexGuard};
 use tracing::{debug, info, warn};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "T")]
-pub enum MarketData {
-    #[serde(rename = "q")]
-    OptionQuote(OptionQuote),
-    #[serde(rename = "t")]
-    OptionTrade(OptionTrade),
-    #[serde(rename = "b")]
-    OptionBar(OptionBar),
-}
+const DEFAULT_MAX_RECONNECTS: u32 = 5;
+const DEFAULT_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(60);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptionQuote {
+    #[serde(alias = "S")]
     pub s: String,
     pub bp: f64,
     pub bs: u64,
@@ -29,29 +28,40 @@ pub struct OptionQuote {
     pub t: DateTime<Utc>,
     #[serde(default)]
     pub up: f64,
+    #[serde(default)]
     pub option_symbol: String,
+    #[serde(default)]
     pub strike: f64,
-    pub expiration: DateTime<Utc>,
-    pub option_type: OptionType,
+    #[serde(default)]
+    pub expiration: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub option_type: Option<OptionType>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptionTrade {
+    #[serde(rename = "S")]
     pub s: String,
     pub p: f64,
+    #[serde(alias = "s")]
     pub sz: u64,
     pub t: DateTime<Utc>,
     pub x: String,
     #[serde(default)]
     pub up: f64,
+    #[serde(default)]
     pub option_symbol: String,
+    #[serde(default)]
     pub strike: f64,
-    pub expiration: DateTime<Utc>,
-    pub option_type: OptionType,
+    #[serde(default)]
+    pub expiration: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub option_type: Option<OptionType>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptionBar {
+    #[serde(alias = "S")]
     pub s: String,
     pub o: f64,
     pub h: f64,
@@ -59,15 +69,53 @@ pub struct OptionBar {
     pub c: f64,
     pub v: u64,
     pub t: DateTime<Utc>,
+    #[serde(default)]
     pub vw: f64,
     #[serde(default)]
     pub up: f64,
+    #[serde(default)]
     pub option_symbol: String,
+    #[serde(default)]
     pub strike: f64,
-    pub expiration: DateTime<Utc>,
-    pub option_type: OptionType,
+    #[serde(default)]
+    pub expiration: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub option_type: Option<OptionType>,
 }
 
+fn resolve_contract(symbol: &str, option_symbol: &str) -> Option<OptionContract> {
+    if option_symbol.is_empty() {
+        OptionContract::from_occ_symbol(symbol)
+    } else {
+        OptionContract::from_occ_symbol(option_symbol)
+    }
+}
+
+macro_rules! impl_symbol_fields {
+    ($($ty:ty),*) => {
+        $(
+            impl $ty {
+                pub fn contract(&self) -> Option<OptionContract> {
+                    resolve_contract(&self.s, &self.option_symbol)
+                }
+
+                pub fn fill_from_symbol(&mut self) -> bool {
+                    let Some(contract) = self.contract() else {
+                        return false;
+                    };
+                    self.option_symbol = contract.option_symbol;
+                    self.strike = contract.strike;
+                    self.expiration = Some(contract.expiration);
+                    self.option_type = Some(contract.option_type);
+                    true
+                }
+            }
+        )*
+    };
+}
+
+impl_symbol_fields!(OptionQuote, OptionTrade, OptionBar);
+
 #[derive(Debug, Serialize)]
 struct Auth {
     action: String,
@@ -108,260 +156,286 @@ impl Subscribe {
         self
     }
 
-    #[allow(dead_code)]
     fn option_trades(mut self, symbols: Vec<String>) -> Self {
         self.trades = Some(symbols);
         self
     }
 
-    #[allow(dead_code)]
     fn option_bars(mut self, symbols: Vec<String>) -> Self {
         self.bars = Some(symbols);
         self
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamSelection {
+    pub quotes: bool,
+    pub trades: bool,
+    pub bars: bool,
+}
+
+impl Default for StreamSelection {
+    fn default() -> Self {
+        Self {
+            quotes: true,
+            trades: false,
+            bars: false,
+        }
+    }
+}
+
+impl StreamSelection {
+    fn subscribe_message(&self, symbols: &[String]) -> Subscribe {
+        let mut msg = Subscribe::new();
+        if self.quotes {
+            msg = msg.option_quotes(symbols.to_vec());
+        }
+        if self.trades {
+            msg = msg.option_trades(symbols.to_vec());
+        }
+        if self.bars {
+            msg = msg.option_bars(symbols.to_vec());
+        }
+        msg
+    }
+}
+
 pub struct WebSocketClient {
     config: AlpacaConfig,
     data_sender: mpsc::Sender<ModelOptionQuote>,
     data_receiver: Arc<Mutex<mpsc::Receiver<ModelOptionQuote>>>,
+    quote_stream_active: Arc<AtomicBool>,
+    trade_sender: mpsc::Sender<OptionTrade>,
+    trade_receiver: Arc<Mutex<mpsc::Receiver<OptionTrade>>>,
+    bar_sender: mpsc::Sender<OptionBar>,
+    bar_receiver: Arc<Mutex<mpsc::Receiver<OptionBar>>>,
     notification_tx: Arc<tokio::sync::broadcast::Sender<()>>,
+    mid_smoothing_alpha: Option<f64>,
+    max_reconnects: u32,
+    reconnect_backoff: Duration,
+    underlying_prices: Arc<DashMap<String, f64>>,
+    state_tx: Arc<tokio::sync::broadcast::Sender<WsState>>,
+    underlying_state_tx: Arc<tokio::sync::broadcast::Sender<WsState>>,
 }
 
 impl WebSocketClient {
     pub fn new(config: AlpacaConfig) -> Self {
         let (data_sender, data_receiver) = mpsc::channel(1000);
+        let (trade_sender, trade_receiver) = mpsc::channel(1000);
+        let (bar_sender, bar_receiver) = mpsc::channel(1000);
         let (notification_tx, _) = tokio::sync::broadcast::channel(100);
+        let (state_tx, _) = tokio::sync::broadcast::channel(16);
+        let (underlying_state_tx, _) = tokio::sync::broadcast::channel(16);
 
         Self {
             config,
             data_sender,
             data_receiver: Arc::new(Mutex::new(data_receiver)),
+            quote_stream_active: Arc::new(AtomicBool::new(false)),
+            trade_sender,
+            trade_receiver: Arc::new(Mutex::new(trade_receiver)),
+            bar_sender,
+            bar_receiver: Arc::new(Mutex::new(bar_receiver)),
             notification_tx: Arc::new(notification_tx),
+            mid_smoothing_alpha: None,
+            max_reconnects: DEFAULT_MAX_RECONNECTS,
+            reconnect_backoff: DEFAULT_RECONNECT_BACKOFF,
+            underlying_prices: Arc::new(DashMap::new()),
+            state_tx: Arc::new(state_tx),
+            underlying_state_tx: Arc::new(underlying_state_tx),
         }
     }
 
+    pub fn with_reconnect(mut self, max_reconnects: u32, backoff: Duration) -> Self {
+        self.max_reconnects = max_reconnects;
+        self.reconnect_backoff = backoff;
+        self
+    }
+
+    pub fn with_mid_smoothing(mut self, alpha: Option<f64>) -> Self {
+        self.mid_smoothing_alpha = alpha;
+        self
+    }
+
+    pub fn mid_smoothing(&self) -> Option<f64> {
+        self.mid_smoothing_alpha
+    }
+
     pub fn get_notification_channel(&self) -> tokio::sync::broadcast::Receiver<()> {
         self.notification_tx.subscribe()
     }
 
-    pub async fn connect(&self, symbols: Vec<String>) -> Result<()> {
-        use futures::{SinkExt, StreamExt};
-        use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
-        use url::Url;
+    /// State of the options session started by `connect`/`connect_streams`.
+    pub fn state_channel(&self) -> tokio::sync::broadcast::Receiver<WsState> {
+        self.state_tx.subscribe()
+    }
 
-        info!("Connecting to Alpaca WebSocket for options data");
-        debug!("Options symbols to subscribe: {:?}", symbols);
+    /// State of the stock quote session started by `subscribe_underlying`.
+    pub fn underlying_state_channel(&self) -> tokio::sync::broadcast::Receiver<WsState> {
+        self.underlying_state_tx.subscribe()
+    }
 
-        if symbols.is_empty() {
-            return Err(OptionsError::WebSocketError(
-                "No symbols provided for subscription".to_string(),
-            ));
-        }
+    pub async fn connect(&self, symbols: Vec<String>) -> Result<()> {
+        self.connect_streams(symbols, StreamSelection::default())
+            .await
+    }
 
+    fn ws_url(&self, path: &str) -> Result<String> {
         let data_url = &self.config.data_url;
-        let ws_domain = if data_url.starts_with("https://") {
-            data_url
-                .strip_prefix("https://")
-                .unwrap_or("data.alpaca.markets")
+        let ws_url = if let Some(domain) = data_url.strip_prefix("https://") {
+            format!("wss://{}/{}", domain, path)
+        } else if let Some(domain) = data_url.strip_prefix("http://") {
+            format!("ws://{}/{}", domain, path)
         } else {
-            "data.alpaca.markets"
+            format!("wss://data.alpaca.markets/{}", path)
         };
-
-        let ws_url = format!("wss://{}/v1beta1/options", ws_domain);
         info!("Using WebSocket URL: {}", ws_url);
 
-        let url = Url::parse(&ws_url).map_err(|e| {
+        let url = url::Url::parse(&ws_url).map_err(|e| {
             OptionsError::WebSocketError(format!("Failed to parse WebSocket URL: {}", e))
         })?;
+        Ok(url.to_string())
+    }
 
-        let sender = self.data_sender.clone();
-        let api_key = self.config.api_key.clone();
-        let api_secret = self.config.api_secret.clone();
-        let symbols_clone = symbols.clone();
-        let notification_tx = self.notification_tx.clone();
-
-        fn get_status_from_error(
-            err: &tokio_tungstenite::tungstenite::Error,
-        ) -> Option<reqwest::StatusCode> {
-            use tokio_tungstenite::tungstenite::Error;
-            match err {
-                Error::Http(response) => {
-                    Some(reqwest::StatusCode::from_u16(response.status().as_u16()).ok()?)
-                }
-                _ => None,
-            }
+    fn session(
+        &self,
+        url: String,
+        kind: SessionKind,
+        symbols: Vec<String>,
+        streams: StreamSelection,
+    ) -> StreamSession {
+        StreamSession {
+            url,
+            kind,
+            api_key: self.config.api_key.clone(),
+            api_secret: self.config.api_secret.clone(),
+            symbols,
+            streams,
+            sender: self.data_sender.clone(),
+            trade_sender: self.trade_sender.clone(),
+            bar_sender: self.bar_sender.clone(),
+            notification_tx: self.notification_tx.clone(),
+            underlying_prices: self.underlying_prices.clone(),
+            state_tx: match kind {
+                SessionKind::Options => self.state_tx.clone(),
+                SessionKind::Underlying => self.underlying_state_tx.clone(),
+            },
+            smoother: match self.mid_smoothing_alpha {
+                Some(alpha) => MidPriceSmoother::new(alpha),
+                None => MidPriceSmoother::disabled(),
+            },
         }
+    }
+
+    fn spawn_session(&self, mut session: StreamSession) {
+        let max_reconnects = self.max_reconnects;
+        let reconnect_backoff = self.reconnect_backoff;
 
         tokio::spawn(async move {
             info!(
-                "Starting options data stream for {} symbols",
-                symbols_clone.len()
+                "Starting {:?} data stream for {} symbols",
+                session.kind,
+                session.symbols.len()
             );
 
-            let url_str = url.to_string();
-            let (ws_stream, response) = match connect_async(url_str).await {
-                Ok(conn) => conn,
-                Err(e) => {
-                    let error_msg = format!("Failed to connect to WebSocket: {}", e);
-                    warn!("{}", error_msg);
-
-                    if let Some(status) = get_status_from_error(&e) {
-                        warn!(
-                            "HTTP error: {} {}",
-                            status.as_u16(),
-                            status.canonical_reason().unwrap_or("Unknown")
-                        );
-
-                        if status == reqwest::StatusCode::NOT_FOUND {
-                            warn!("The WebSocket endpoint was not found (404). This could be because:");
-                            warn!("1. The WebSocket URL is incorrect");
-                            warn!("2. The Alpaca API has changed");
-                            warn!("3. Your Alpaca subscription doesn't include options data");
-                        } else if status == reqwest::StatusCode::UNAUTHORIZED {
-                            warn!("Authentication failed (401). Please check your API key and secret.");
-                        } else if status == reqwest::StatusCode::FORBIDDEN {
-                            warn!("Access forbidden (403). Your account may not have access to options data.");
-                        }
+            let mut attempt = 0;
+            loop {
+                match session.run(&mut attempt).await {
+                    SessionEnd::Stopped(reason) => {
+                        session.set_state(WsState::Error(reason));
+                        break;
                     }
-
-                    return;
+                    SessionEnd::Disconnected => session.set_state(WsState::Disconnected),
                 }
-            };
-
-            info!("WebSocket connected with status: {}", response.status());
-            debug!("WebSocket response headers: {:?}", response.headers());
-
-            info!("WebSocket connected");
+                if attempt >= max_reconnects {
+                    warn!(
+                        "Giving up on WebSocket after {} reconnect attempts",
+                        max_reconnects
+                    );
+                    session.set_state(WsState::Error(format!(
+                        "Gave up after {} reconnect attempts",
+                        max_reconnects
+                    )));
+                    break;
+                }
+                attempt += 1;
+                let wait = reconnect_backoff
+                    .saturating_mul(1 << (attempt - 1).min(6))
+                    .min(MAX_RECONNECT_BACKOFF);
+                warn!(
+                    "WebSocket disconnected, reconnecting in {:?} (attempt {}/{})",
+                    wait, attempt, max_reconnects
+                );
+                tokio::time::sleep(wait).await;
+            }
 
-            let (mut write, mut read) = ws_stream.split();
+            info!("WebSocket connection closed");
+        });
+    }
 
-            let auth_msg = Auth::new(api_key, api_secret);
-            let auth_json = match serde_json::to_string(&auth_msg) {
-                Ok(json) => json,
-                Err(e) => {
-                    warn!("Failed to serialize auth message: {}", e);
-                    return;
-                }
-            };
+    pub async fn subscribe_underlying(&self, symbols: Vec<String>, feed: &str) -> Result<()> {
+        info!("Connecting to Alpaca WebSocket for underlying quotes");
+        debug!("Underlying symbols to subscribe: {:?}", symbols);
 
-            if let Err(e) = write.send(Message::Text(auth_json.into())).await {
-                warn!("Failed to send auth message: {}", e);
-                return;
-            }
+        if symbols.is_empty() {
+            return Err(OptionsError::WebSocketError(
+                "No symbols provided for subscription".to_string(),
+            ));
+        }
 
-            let subscribe_msg = Subscribe::new().option_quotes(symbols_clone);
-            let subscribe_json = match serde_json::to_string(&subscribe_msg) {
-                Ok(json) => json,
-                Err(e) => {
-                    warn!("Failed to serialize subscribe message: {}", e);
-                    return;
-                }
-            };
+        let url = self.ws_url(&format!("v2/{}", feed))?;
+        let session = self.session(
+            url,
+            SessionKind::Underlying,
+            symbols,
+            StreamSelection::default(),
+        );
+        self.spawn_session(session);
 
-            if let Err(e) = write.send(Message::Text(subscribe_json.into())).await {
-                warn!("Failed to send subscribe message: {}", e);
-                return;
-            }
+        Ok(())
+    }
 
-            while let Some(msg) = read.next().await {
-                match msg {
-                    Ok(Message::Text(text)) => {
-                        debug!("Received text message");
+    pub fn underlying_price(&self, symbol: &str) -> Option<f64> {
+        self.underlying_prices.get(symbol).map(|p| *p)
+    }
 
-                        if text.contains(r#""T":"q""#) {
-                            if let Ok(quote) = serde_json::from_str::<OptionQuote>(&text) {
-                                if let Some(contract) =
-                                    OptionContract::from_occ_symbol(&quote.option_symbol)
-                                {
-                                    let model_quote = ModelOptionQuote::new(
-                                        contract,
-                                        quote.bp,
-                                        quote.ap,
-                                        (quote.bp + quote.ap) / 2.0,
-                                        0,
-                                        0,
-                                        quote.up,
-                                    );
-
-                                    match sender.try_send(model_quote) {
-                                        Ok(_) => {
-                                            if let Err(e) = notification_tx.send(()) {
-                                                debug!("Failed to send notification: {}", e);
-                                            }
-                                        }
-                                        Err(mpsc::error::TrySendError::Full(model_quote)) => {
-                                            if sender.send(model_quote).await.is_err() {
-                                                warn!("Failed to send quote to channel");
-                                                break;
-                                            }
-                                            if let Err(e) = notification_tx.send(()) {
-                                                debug!("Failed to send notification: {}", e);
-                                            }
-                                        }
-                                        Err(_) => {
-                                            warn!("Failed to send quote to channel");
-                                            break;
-                                        }
-                                    }
-                                }
-                                continue;
-                            }
-                        }
+    pub async fn connect_streams(
+        &self,
+        symbols: Vec<String>,
+        streams: StreamSelection,
+    ) -> Result<()> {
+        info!("Connecting to Alpaca WebSocket for options data");
+        debug!("Options symbols to subscribe: {:?}", symbols);
 
-                        match serde_json::from_str::<serde_json::Value>(&text) {
-                            Ok(json) => {
-                                if let Some(msg_type) = json.get("T") {
-                                    match msg_type.as_str() {
-                                        Some("q") => debug!("Quote message fell back to slow path"),
-                                        Some("t") => debug!("Received option trade"),
-                                        Some("b") => debug!("Received option bar"),
-                                        Some("subscription") => info!("Subscription confirmed"),
-                                        Some("error") => warn!("Received error: {}", json),
-                                        Some(t) => debug!("Received unknown message type: {}", t),
-                                        None => debug!("Received message without type"),
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                warn!("Failed to parse message: {}", e);
-                            }
-                        }
-                    }
-                    Ok(Message::Binary(_)) => {
-                        debug!("Received binary message");
-                    }
-                    Ok(Message::Ping(data)) => {
-                        if let Err(e) = write.send(Message::Pong(data)).await {
-                            warn!("Failed to send pong: {}", e);
-                            break;
-                        }
-                    }
-                    Ok(Message::Pong(_)) => {
-                        debug!("Received pong");
-                    }
-                    Ok(Message::Close(_)) => {
-                        info!("WebSocket closed");
-                        break;
-                    }
-                    Ok(Message::Frame(_)) => {
-                        debug!("Received frame message");
-                    }
-                    Err(e) => {
-                        warn!("WebSocket error: {}", e);
-                        break;
-                    }
-                }
-            }
+        if symbols.is_empty() {
+            return Err(OptionsError::WebSocketError(
+                "No symbols provided for subscription".to_string(),
+            ));
+        }
+        if !streams.quotes && !streams.trades && !streams.bars {
+            return Err(OptionsError::WebSocketError(
+                "No streams selected for subscription".to_string(),
+            ));
+        }
 
-            info!("WebSocket connection closed");
-        });
+        let url = self.ws_url("v1beta1/options")?;
+        let session = self.session(url, SessionKind::Options, symbols, streams);
+        self.spawn_session(session);
 
         Ok(())
     }
 
+    async fn quote_receiver(&self) -> Result<MutexGuard<'_, mpsc::Receiver<ModelOptionQuote>>> {
+        if self.quote_stream_active.load(Ordering::Acquire) {
+            return Err(OptionsError::WebSocketError(
+                "Quotes are being consumed by quote_stream".to_string(),
+            ));
+        }
+        Ok(self.data_receiver.lock().await)
+    }
+
     pub async fn next_option_quote(&self) -> Result<Option<ModelOptionQuote>> {
-        let mut receiver = self.data_receiver.lock().await;
+        let mut receiver = self.quote_receiver().await?;
 
         match receiver.recv().await {
             Some(quote) => Ok(Some(quote)),
@@ -369,11 +443,44 @@ impl WebSocketClient {
         }
     }
 
+    /// Single-consumer stream over the quote channel. While it is alive the
+    /// other quote methods return an error instead of waiting, and a second
+    /// stream cannot be opened.
+    pub fn quote_stream(&self) -> Result<impl futures::Stream<Item = ModelOptionQuote>> {
+        if self.quote_stream_active.swap(true, Ordering::AcqRel) {
+            return Err(OptionsError::WebSocketError(
+                "A quote stream is already active".to_string(),
+            ));
+        }
+        let active = QuoteStreamGuard(self.quote_stream_active.clone());
+        let receiver = self.data_receiver.clone().try_lock_owned().map_err(|_| {
+            OptionsError::WebSocketError("Quotes are already being consumed".to_string())
+        })?;
+
+        Ok(futures::stream::unfold(
+            (receiver, active),
+            |(mut receiver, active)| async move {
+                let quote = receiver.recv().await?;
+                Some((quote, (receiver, active)))
+            },
+        ))
+    }
+
+    pub async fn next_option_trade(&self) -> Result<Option<OptionTrade>> {
+        let mut receiver = self.trade_receiver.lock().await;
+        Ok(receiver.recv().await)
+    }
+
+    pub async fn next_option_bar(&self) -> Result<Option<OptionBar>> {
+        let mut receiver = self.bar_receiver.lock().await;
+        Ok(receiver.recv().await)
+    }
+
     pub async fn next_option_quotes_batch(
         &self,
         max_batch_size: usize,
     ) -> Result<Vec<ModelOptionQuote>> {
-        let mut receiver = self.data_receiver.lock().await;
+        let mut receiver = self.quote_receiver().await?;
         let mut quotes = Vec::with_capacity(max_batch_size);
 
         if let Some(quote) = receiver.recv().await {
@@ -396,7 +503,7 @@ impl WebSocketClient {
     where
         F: FnMut(ModelOptionQuote) -> Result<()>,
     {
-        let mut receiver = self.data_receiver.lock().await;
+        let mut receiver = self.quote_receiver().await?;
 
         while let Some(quote) = receiver.recv().await {
             callback(quote)?;
@@ -406,21 +513,634 @@ impl WebSocketClient {
     }
 }
 
-impl From<OptionQuote> for ModelOptionQuote {
-    fn from(quote: OptionQuote) -> Self {
+struct QuoteStreamGuard(Arc<AtomicBool>);
+
+impl Drop for QuoteStreamGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionKind {
+    Options,
+    Underlying,
+}
+
+#[derive(Debug, Deserialize)]
+struct StockQuote {
+    #[serde(rename = "S")]
+    symbol: String,
+    bp: f64,
+    ap: f64,
+}
+
+enum SessionEnd {
+    Disconnected,
+    Stopped(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WsState {
+    Connecting,
+    Authenticated,
+    Subscribed,
+    Disconnected,
+    Error(String),
+}
+
+struct StreamSession {
+    url: String,
+    kind: SessionKind,
+    api_key: String,
+    api_secret: String,
+    symbols: Vec<String>,
+    streams: StreamSelection,
+    sender: mpsc::Sender<ModelOptionQuote>,
+    trade_sender: mpsc::Sender<OptionTrade>,
+    bar_sender: mpsc::Sender<OptionBar>,
+    notification_tx: Arc<tokio::sync::broadcast::Sender<()>>,
+    underlying_prices: Arc<DashMap<String, f64>>,
+    state_tx: Arc<tokio::sync::broadcast::Sender<WsState>>,
+    smoother: MidPriceSmoother,
+}
+
+fn get_status_from_error(
+    err: &tokio_tungstenite::tungstenite::Error,
+) -> Option<reqwest::StatusCode> {
+    use tokio_tungstenite::tungstenite::Error;
+    match err {
+        Error::Http(response) => {
+            Some(reqwest::StatusCode::from_u16(response.status().as_u16()).ok()?)
+        }
+        _ => None,
+    }
+}
+
+fn forward_event<T>(sender: &mpsc::Sender<T>, event: T, kind: &str) {
+    match sender.try_send(event) {
+        Ok(_) => {}
+        Err(mpsc::error::TrySendError::Full(_)) => {
+            debug!("Option {} channel full, dropping event", kind)
+        }
+        Err(mpsc::error::TrySendError::Closed(_)) => {
+            debug!("Option {} channel closed, dropping event", kind)
+        }
+    }
+}
+
+impl StreamSession {
+    fn set_state(&self, state: WsState) {
+        if let Err(e) = self.state_tx.send(state) {
+            debug!("No listeners for WebSocket state: {}", e);
+        }
+    }
+
+    async fn run(&mut self, attempt: &mut u32) -> SessionEnd {
+        use futures::{SinkExt, StreamExt};
+        use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+
+        self.set_state(WsState::Connecting);
+
+        let (ws_stream, response) = match connect_async(self.url.as_str()).await {
+            Ok(conn) => conn,
+            Err(e) => {
+                let error_msg = format!("Failed to connect to WebSocket: {}", e);
+                warn!("{}", error_msg);
+
+                if let Some(status) = get_status_from_error(&e) {
+                    warn!(
+                        "HTTP error: {} {}",
+                        status.as_u16(),
+                        status.canonical_reason().unwrap_or("Unknown")
+                    );
+
+                    if status == reqwest::StatusCode::NOT_FOUND {
+                        warn!("The WebSocket endpoint was not found (404). This could be because:");
+                        warn!("1. The WebSocket URL is incorrect");
+                        warn!("2. The Alpaca API has changed");
+                        warn!("3. Your Alpaca subscription doesn't include options data");
+                    } else if status == reqwest::StatusCode::UNAUTHORIZED {
+                        warn!("Authentication failed (401). Please check your API key and secret.");
+                    } else if status == reqwest::StatusCode::FORBIDDEN {
+                        warn!("Access forbidden (403). Your account may not have access to options data.");
+                    }
+
+                    if status.is_client_error() {
+                        return SessionEnd::Stopped(error_msg);
+                    }
+                }
+
+                return SessionEnd::Disconnected;
+            }
+        };
+
+        info!("WebSocket connected with status: {}", response.status());
+        debug!("WebSocket response headers: {:?}", response.headers());
+
+        info!("WebSocket connected");
+
+        let (mut write, mut read) = ws_stream.split();
+
+        let auth_msg = Auth::new(self.api_key.clone(), self.api_secret.clone());
+        let auth_json = match serde_json::to_string(&auth_msg) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Failed to serialize auth message: {}", e);
+                return SessionEnd::Stopped(format!("Failed to serialize auth message: {}", e));
+            }
+        };
+
+        if let Err(e) = write.send(Message::Text(auth_json.into())).await {
+            warn!("Failed to send auth message: {}", e);
+            return SessionEnd::Disconnected;
+        }
+
+        let subscribe_msg = self.streams.subscribe_message(&self.symbols);
+        let subscribe_json = match serde_json::to_string(&subscribe_msg) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Failed to serialize subscribe message: {}", e);
+                return SessionEnd::Stopped(format!(
+                    "Failed to serialize subscribe message: {}",
+                    e
+                ));
+            }
+        };
+
+        if let Err(e) = write.send(Message::Text(subscribe_json.into())).await {
+            warn!("Failed to send subscribe message: {}", e);
+            return SessionEnd::Disconnected;
+        }
+
+        while let Some(msg) = read.next().await {
+            match msg {
+                Ok(Message::Text(text)) => {
+                    debug!("Received text message");
+
+                    let messages = match serde_json::from_str::<serde_json::Value>(&text) {
+                        Ok(serde_json::Value::Array(items)) => items,
+                        Ok(item) => vec![item],
+                        Err(e) => {
+                            warn!("Failed to parse message: {}", e);
+                            continue;
+                        }
+                    };
+
+                    for json in messages {
+                        let Some(msg_type) = json.get("T").and_then(|t| t.as_str()) else {
+                            debug!("Received message without type");
+                            continue;
+                        };
+
+                        match msg_type {
+                            "q" if self.kind == SessionKind::Underlying => {
+                                match serde_json::from_value::<StockQuote>(json) {
+                                    Ok(q) if q.bp > 0.0 && q.ap > 0.0 => {
+                                        self.underlying_prices
+                                            .insert(q.symbol, (q.bp + q.ap) / 2.0);
+                                    }
+                                    Ok(_) => {}
+                                    Err(e) => debug!("Failed to parse stock quote: {}", e),
+                                }
+                            }
+                            "q" => {
+                                let mut quote = match serde_json::from_value::<OptionQuote>(json) {
+                                    Ok(quote) => quote,
+                                    Err(e) => {
+                                        debug!("Failed to parse option quote: {}", e);
+                                        continue;
+                                    }
+                                };
+                                if !quote.fill_from_symbol() {
+                                    debug!("Unrecognized option symbol in quote: {}", quote.s);
+                                    continue;
+                                }
+
+                                let mut model_quote = match ModelOptionQuote::try_from(quote) {
+                                    Ok(model_quote) => model_quote,
+                                    Err(e) => {
+                                        debug!("Failed to convert option quote: {}", e);
+                                        continue;
+                                    }
+                                };
+                                if model_quote.underlying_price <= 0.0 {
+                                    if let Some(price) =
+                                        self.underlying_prices.get(&model_quote.contract.symbol)
+                                    {
+                                        model_quote.underlying_price = *price;
+                                    }
+                                }
+                                let model_quote = if self.smoother.is_enabled() {
+                                    self.smoother.smooth_quote(&model_quote)
+                                } else {
+                                    model_quote
+                                };
+
+                                match self.sender.try_send(model_quote) {
+                                    Ok(_) => {
+                                        if let Err(e) = self.notification_tx.send(()) {
+                                            debug!("Failed to send notification: {}", e);
+                                        }
+                                    }
+                                    Err(mpsc::error::TrySendError::Full(model_quote)) => {
+                                        if self.sender.send(model_quote).await.is_err() {
+                                            warn!("Failed to send quote to channel");
+                                            return SessionEnd::Stopped(
+                                                "Quote channel closed".to_string(),
+                                            );
+                                        }
+                                        if let Err(e) = self.notification_tx.send(()) {
+                                            debug!("Failed to send notification: {}", e);
+                                        }
+                                    }
+                                    Err(_) => {
+                                        warn!("Failed to send quote to channel");
+                                        return SessionEnd::Stopped(
+                                            "Quote channel closed".to_string(),
+                                        );
+                                    }
+                                }
+                            }
+                            "t" => match serde_json::from_value::<OptionTrade>(json) {
+                                Ok(mut trade) => {
+                                    trade.fill_from_symbol();
+                                    debug!("Received option trade {} @ {}", trade.s, trade.p);
+                                    forward_event(&self.trade_sender, trade, "trade");
+                                }
+                                Err(e) => debug!("Failed to parse option trade: {}", e),
+                            },
+                            "b" => match serde_json::from_value::<OptionBar>(json) {
+                                Ok(mut bar) => {
+                                    bar.fill_from_symbol();
+                                    debug!("Received option bar {} close {}", bar.s, bar.c);
+                                    forward_event(&self.bar_sender, bar, "bar");
+                                }
+                                Err(e) => debug!("Failed to parse option bar: {}", e),
+                            },
+                            "success" => {
+                                if json.get("msg").and_then(|m| m.as_str()) == Some("authenticated")
+                                {
+                                    info!("WebSocket authenticated");
+                                    self.set_state(WsState::Authenticated);
+                                }
+                            }
+                            "subscription" => {
+                                info!("Subscription confirmed");
+                                *attempt = 0;
+                                self.set_state(WsState::Subscribed);
+                            }
+                            "error" => {
+                                warn!("Received error: {}", json);
+                                // Bad credentials and the connection limit will fail
+                                // the same way on every reconnect.
+                                let code = json.get("code").and_then(|c| c.as_u64());
+                                if matches!(code, Some(401 | 402 | 406)) {
+                                    return SessionEnd::Stopped(json.to_string());
+                                }
+                                self.set_state(WsState::Error(json.to_string()));
+                            }
+                            t => debug!("Received unknown message type: {}", t),
+                        }
+                    }
+                }
+                Ok(Message::Binary(_)) => {
+                    debug!("Received binary message");
+                }
+                Ok(Message::Ping(data)) => {
+                    if let Err(e) = write.send(Message::Pong(data)).await {
+                        warn!("Failed to send pong: {}", e);
+                        return SessionEnd::Disconnected;
+                    }
+                }
+                Ok(Message::Pong(_)) => {
+                    debug!("Received pong");
+                }
+                Ok(Message::Close(_)) => {
+                    info!("WebSocket closed");
+                    return SessionEnd::Disconnected;
+                }
+                Ok(Message::Frame(_)) => {
+                    debug!("Received frame message");
+                }
+                Err(e) => {
+                    warn!("WebSocket error: {}", e);
+                    return SessionEnd::Disconnected;
+                }
+            }
+        }
+
+        SessionEnd::Disconnected
+    }
+}
+
+impl TryFrom<OptionQuote> for ModelOptionQuote {
+    type Error = OptionsError;
+
+    fn try_from(quote: OptionQuote) -> Result<Self> {
         let mid_price = (quote.bp + quote.ap) / 2.0;
 
-        if let Some(contract) = OptionContract::from_occ_symbol(&quote.option_symbol) {
-            Self::new(contract, quote.bp, quote.ap, mid_price, 0, 0, quote.up)
-        } else {
-            let contract = OptionContract::new(
+        let contract = match quote.contract() {
+            Some(contract) => contract,
+            None => OptionContract::new(
                 quote.s.clone(),
-                quote.option_type,
+                quote.option_type.unwrap_or(OptionType::Call),
                 quote.strike,
-                quote.expiration,
-            );
+                quote.expiration.unwrap_or_else(Utc::now),
+            )?,
+        };
+
+        let mut model = Self::new(contract, quote.bp, quote.ap, mid_price, 0, 0, quote.up);
+        model.timestamp = quote.t;
+        Ok(model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{SinkExt, StreamExt};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::protocol::Message;
+
+    const QUOTE: &str = r#"[{"T":"q","S":"AAPL240621C00190000","bp":1.0,"bs":1,"ap":1.2,"as":1,"t":"2024-06-01T14:30:00Z"}]"#;
+
+    fn test_config(addr: std::net::SocketAddr) -> AlpacaConfig {
+        AlpacaConfig {
+            api_key: "key".to_string(),
+            api_secret: "secret".to_string(),
+            base_url: String::new(),
+            data_url: format!("http://{}", addr),
+            paper_url: String::new(),
+            user_agent: String::new(),
+            send_request_id: false,
+        }
+    }
+
+    // Accepts one client, reads auth + subscribe, then sends `frames` and drops the socket.
+    async fn serve_once(listener: &TcpListener, frames: &[&str]) {
+        let (tcp, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+        for _ in 0..2 {
+            ws.next().await.unwrap().unwrap();
+        }
+        for frame in frames {
+            ws.send(Message::Text((*frame).into())).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn reconnects_after_dropped_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = WebSocketClient::new(test_config(listener.local_addr().unwrap()))
+            .with_reconnect(3, Duration::from_millis(10));
+        client
+            .connect(vec!["AAPL240621C00190000".to_string()])
+            .await
+            .unwrap();
+
+        let subscribed = r#"[{"T":"subscription","quotes":["AAPL240621C00190000"]}]"#;
+        serve_once(&listener, &[subscribed, QUOTE]).await;
+        serve_once(&listener, &[subscribed, QUOTE]).await;
+
+        for _ in 0..2 {
+            let quote = tokio::time::timeout(Duration::from_secs(5), client.next_option_quote())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            assert_eq!(quote.contract.option_symbol, "AAPL240621C00190000");
+            assert!((quote.bid - 1.0).abs() < 1e-12);
+        }
+    }
+
+    fn model_quote(bid: f64) -> ModelOptionQuote {
+        let mut quote: OptionQuote = serde_json::from_str::<Vec<OptionQuote>>(QUOTE)
+            .unwrap()
+            .remove(0);
+        quote.bp = bid;
+        ModelOptionQuote::try_from(quote).unwrap()
+    }
+
+    #[tokio::test]
+    async fn quote_stream_collects_quotes() {
+        let client = WebSocketClient::new(test_config(([127, 0, 0, 1], 0).into()));
+        for i in 0..5 {
+            client
+                .data_sender
+                .send(model_quote(i as f64))
+                .await
+                .unwrap();
+        }
+
+        let stream = client.quote_stream().unwrap();
+        let bids: Vec<f64> = stream.take(5).map(|q| q.bid).collect().await;
+        assert_eq!(bids, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[tokio::test]
+    async fn quote_stream_is_single_consumer() {
+        let client = WebSocketClient::new(test_config(([127, 0, 0, 1], 0).into()));
+        client.data_sender.send(model_quote(1.0)).await.unwrap();
+
+        let stream = client.quote_stream().unwrap();
+        assert!(client.quote_stream().is_err());
+        assert!(client.next_option_quote().await.is_err());
+        assert!(client.next_option_quotes_batch(10).await.is_err());
+        drop(stream);
+
+        let quote = client.next_option_quote().await.unwrap().unwrap();
+        assert_eq!(quote.bid, 1.0);
+    }
+
+    #[tokio::test]
+    async fn stops_on_auth_failure() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = WebSocketClient::new(test_config(listener.local_addr().unwrap()))
+            .with_reconnect(3, Duration::from_millis(10));
+        let mut states = client.state_channel();
+        client
+            .connect(vec!["AAPL240621C00190000".to_string()])
+            .await
+            .unwrap();
+
+        serve_once(
+            &listener,
+            &[r#"[{"T":"error","code":402,"msg":"auth failed"}]"#],
+        )
+        .await;
+
+        let state = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let WsState::Error(msg) = states.recv().await.unwrap() {
+                    break msg;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert!(state.contains("402"));
+        let reconnect = tokio::time::timeout(Duration::from_millis(200), listener.accept()).await;
+        assert!(reconnect.is_err());
+    }
+
+    #[test]
+    fn parses_real_alpaca_frames() {
+        let quote = r#"{"T":"q","S":"SPXW240327P04925000","t":"2024-03-12T11:59:38.897261568Z","bx":"C","bp":9.46,"bs":53,"ax":"C","ap":9.66,"as":38,"c":"A"}"#;
+        let mut quote: OptionQuote = serde_json::from_str(quote).unwrap();
+        assert!(quote.fill_from_symbol());
+        assert_eq!(quote.option_symbol, "SPXW240327P04925000");
+        assert_eq!(quote.strike, 4925.0);
+        assert_eq!(quote.option_type, Some(OptionType::Put));
+        assert_eq!(
+            quote.expiration.map(|e| e.date_naive()),
+            chrono::NaiveDate::from_ymd_opt(2024, 3, 27)
+        );
+        assert_eq!((quote.bs, quote.as_size), (53, 38));
+
+        let trade = r#"{"T":"t","S":"AAPL240315C00172500","t":"2024-03-11T13:35:35.13312256Z","p":2.84,"s":1,"x":"N","c":"S"}"#;
+        let mut trade: OptionTrade = serde_json::from_str(trade).unwrap();
+        assert!(trade.fill_from_symbol());
+        assert_eq!(trade.strike, 172.5);
+        assert_eq!(trade.option_type, Some(OptionType::Call));
+        assert_eq!((trade.p, trade.sz), (2.84, 1));
+
+        let bar = r#"{"T":"b","S":"SPXW240327P04925000","o":9.5,"h":9.7,"l":9.4,"c":9.6,"v":120,"t":"2024-03-12T12:00:00Z","n":14,"vw":9.55}"#;
+        let mut bar: OptionBar = serde_json::from_str(bar).unwrap();
+        assert!(bar.fill_from_symbol());
+        assert_eq!(bar.strike, 4925.0);
+        assert_eq!(bar.v, 120);
+
+        let mut unknown: OptionQuote = serde_json::from_str(
+            r#"{"T":"q","S":"NOTANOPTION","t":"2024-03-12T11:59:38Z","bp":1.0,"bs":1,"ap":1.1,"as":1}"#,
+        )
+        .unwrap();
+        assert!(!unknown.fill_from_symbol());
+    }
+
+    #[tokio::test]
+    async fn routes_trades_and_bars_to_their_channels() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = WebSocketClient::new(test_config(listener.local_addr().unwrap()))
+            .with_reconnect(0, Duration::from_millis(10));
+        client
+            .connect(vec!["AAPL240621C00190000".to_string()])
+            .await
+            .unwrap();
+
+        let subscribed = r#"[{"T":"subscription","quotes":["AAPL240621C00190000"]}]"#;
+        let frames = r#"[{"T":"t","S":"AAPL240621C00190000","t":"2024-06-01T14:30:01.5Z","p":2.84,"s":3,"x":"N","c":"S"},{"T":"b","S":"AAPL240621P00185000","o":1.1,"h":1.3,"l":1.0,"c":1.2,"v":40,"t":"2024-06-01T14:31:00Z","n":6,"vw":1.15}]"#;
+        serve_once(&listener, &[subscribed, frames, QUOTE]).await;
+
+        let wait = Duration::from_secs(5);
+        let trade = tokio::time::timeout(wait, client.next_option_trade())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(trade.option_symbol, "AAPL240621C00190000");
+        assert_eq!((trade.p, trade.sz), (2.84, 3));
+
+        let bar = tokio::time::timeout(wait, client.next_option_bar())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(bar.option_symbol, "AAPL240621P00185000");
+        assert_eq!(bar.option_type, Some(OptionType::Put));
+        assert_eq!(bar.v, 40);
+
+        let quote = tokio::time::timeout(wait, client.next_option_quote())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(quote.contract.option_symbol, "AAPL240621C00190000");
+    }
+
+    #[tokio::test]
+    async fn option_quotes_pick_up_the_underlying_price() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = WebSocketClient::new(test_config(listener.local_addr().unwrap()))
+            .with_reconnect(0, Duration::from_millis(10));
+        let mut option_states = client.state_channel();
+        let mut underlying_states = client.underlying_state_channel();
+
+        client
+            .subscribe_underlying(vec!["AAPL".to_string()], "iex")
+            .await
+            .unwrap();
+        serve_once(
+            &listener,
+            &[r#"[{"T":"q","S":"AAPL","bp":189.9,"bs":1,"ap":190.1,"as":1,"t":"2024-06-01T14:30:00Z"}]"#],
+        )
+        .await;
+        let price = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Some(price) = client.underlying_price("AAPL") {
+                    break price;
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!((price - 190.0).abs() < 1e-9);
+
+        // The underlying session's states stay off the options channel.
+        assert_eq!(underlying_states.recv().await.unwrap(), WsState::Connecting);
+        assert!(option_states.try_recv().is_err());
+
+        client
+            .connect(vec!["AAPL240621C00190000".to_string()])
+            .await
+            .unwrap();
+        serve_once(&listener, &[QUOTE]).await;
+        let quote = tokio::time::timeout(Duration::from_secs(5), client.next_option_quote())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!((quote.underlying_price - 190.0).abs() < 1e-9);
+        assert_eq!(option_states.recv().await.unwrap(), WsState::Connecting);
+    }
+
+    #[tokio::test]
+    async fn reports_handshake_states_in_order() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = WebSocketClient::new(test_config(listener.local_addr().unwrap()))
+            .with_reconnect(0, Duration::from_millis(10));
+        let mut states = client.state_channel();
+        client
+            .connect(vec!["AAPL240621C00190000".to_string()])
+            .await
+            .unwrap();
+
+        serve_once(
+            &listener,
+            &[
+                r#"[{"T":"success","msg":"connected"}]"#,
+                r#"[{"T":"success","msg":"authenticated"}]"#,
+                r#"[{"T":"subscription","quotes":["AAPL240621C00190000"]}]"#,
+            ],
+        )
+        .await;
 
-            Self::new(contract, quote.bp, quote.ap, mid_price, 0, 0, quote.up)
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            let state = tokio::time::timeout(Duration::from_secs(5), states.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            seen.push(state);
         }
+        assert_eq!(
+            seen,
+            vec![
+                WsState::Connecting,
+                WsState::Authenticated,
+                WsState::Subscribed
+            ]
+        );
     }
 }
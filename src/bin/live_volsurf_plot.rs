@@ -1,16 +1,17 @@
 use eframe::egui;
-use egui_plot::{GridMark, Line, Plot, PlotPoints, Points, VLine};
+use egui_plot::{GridMark, Legend, Line, Plot, PlotPoints, Points, VLine};
+use options_rs::api::ChainSymbolFilter;
 use options_rs::api::OptionGreeks;
-use options_rs::api::RestClient;
+use options_rs::api::SnapshotFeedCoverage;
+use options_rs::api::{provider_from_config, OptionDataProvider};
 use options_rs::config::Config;
-use options_rs::error::{OptionsError, Result};
+use options_rs::error::{IvError, OptionsError, Result};
 use options_rs::models::volatility::ImpliedVolatility;
 use options_rs::models::volatility::VolatilitySurface;
-use options_rs::models::{OptionContract, OptionQuote};
+use options_rs::models::{ExpirationKind, OptionContract, OptionQuote, OptionType, SurfaceDiff};
 use options_rs::utils::{self};
 use std::cmp::Ordering;
 
-use chrono::TimeZone;
 use dashmap::DashMap;
 use once_cell::sync::Lazy;
 use serde_json::Value;
@@ -18,11 +19,8 @@ use std::sync::Arc;
 use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
-static SURFACE_CACHE: Lazy<
-    DashMap<(String, chrono::DateTime<chrono::Utc>), Arc<VolatilitySurface>>,
-> = Lazy::new(|| DashMap::new());
-
-static RISK_FREE_RATE_CACHE: Lazy<DashMap<chrono::NaiveDate, f64>> = Lazy::new(|| DashMap::new());
+static SURFACE_CACHE: Lazy<DashMap<SurfaceCacheKey, Arc<VolatilitySurface>>> =
+    Lazy::new(|| DashMap::new());
 
 static CONTRACT_METADATA_CACHE: Lazy<DashMap<chrono::NaiveDate, DashMap<String, OptionContract>>> =
     Lazy::new(|| DashMap::new());
@@ -30,9 +28,34 @@ static CONTRACT_METADATA_CACHE: Lazy<DashMap<chrono::NaiveDate, DashMap<String,
 static RATE_LIMIT_RESET: Lazy<std::sync::Mutex<Option<chrono::DateTime<chrono::Utc>>>> =
     Lazy::new(|| std::sync::Mutex::new(None));
 
+static FEED_ANALYTICS_NOTICE: std::sync::Once = std::sync::Once::new();
+
+const FEED_PROBE_SNAPSHOTS: usize = 25;
+
+const SPARSE_GRID_FILL_RATIO: f64 = 0.5;
+
+const DEFAULT_RISK_FREE_RATE: f64 = 0.03;
+
+const SURFACE_DRAG_SENSITIVITY: f64 = 0.01;
+
+const SURFACE_CACHE_DIR: &str = "surface_cache";
+
+type SurfaceCacheKey = (String, chrono::DateTime<chrono::Utc>);
+
+enum SurfacePayload {
+    Full {
+        key: SurfaceCacheKey,
+        surface: Arc<VolatilitySurface>,
+    },
+    Diff {
+        key: SurfaceCacheKey,
+        diff: SurfaceDiff,
+    },
+}
+
 struct PlotData {
-    call_surface: Option<Arc<VolatilitySurface>>,
-    put_surface: Option<Arc<VolatilitySurface>>,
+    call_surface: Option<SurfacePayload>,
+    put_surface: Option<SurfacePayload>,
     expirations: Vec<chrono::NaiveDate>,
     underlying_price: f64,
     quotes: Vec<OptionQuoteWithIV>,
@@ -64,28 +87,16 @@ fn calculate_volatility_surface_with_iv(
             let underlying_price = q.quote.underlying_price;
             let time_to_expiration = contract.time_to_expiration();
 
-            let (delta_value, vega_value) = if let Some(g) = &q.greeks {
-                (g.delta, g.vega)
-            } else {
-                let delta = utils::delta(
+            let greeks = q.greeks.clone().unwrap_or_else(|| {
+                utils::compute_greeks(
                     underlying_price,
                     contract.strike,
                     time_to_expiration,
                     risk_free_rate,
                     iv_value,
                     contract.is_call(),
-                );
-
-                let vega = utils::vega(
-                    underlying_price,
-                    contract.strike,
-                    time_to_expiration,
-                    risk_free_rate,
-                    iv_value,
-                );
-
-                (delta, vega)
-            };
+                )
+            });
 
             let iv = ImpliedVolatility {
                 contract: contract.clone(),
@@ -93,8 +104,8 @@ fn calculate_volatility_surface_with_iv(
                 underlying_price,
                 option_price,
                 time_to_expiration,
-                delta: delta_value,
-                vega: vega_value,
+                delta: greeks.delta,
+                vega: greeks.vega,
             };
             ivs.push(iv);
         } else {
@@ -112,6 +123,17 @@ fn calculate_volatility_surface_with_iv(
 
     let surface = VolatilitySurface::new(symbol.to_string(), &ivs)?;
 
+    let quality = surface.grid_quality();
+    if quality.fill_ratio < SPARSE_GRID_FILL_RATIO {
+        warn!(
+            "Sparse volatility grid for {}: {:.0}% filled, max strike gap {:.2}, max expiry gap {:.1} days; interpolated values may be unreliable",
+            symbol,
+            quality.fill_ratio * 100.0,
+            quality.max_strike_gap,
+            quality.max_expiry_gap_days
+        );
+    }
+
     Ok(surface)
 }
 
@@ -154,10 +176,105 @@ fn calculate_call_put_surfaces_with_iv(
     Ok((call_surface, put_surface))
 }
 
+fn surface_cache_path(symbol: &str, side: &str) -> std::path::PathBuf {
+    std::path::Path::new(SURFACE_CACHE_DIR).join(format!("{}_{}.parquet", symbol, side))
+}
+
+fn save_surface_to_disk(surface: &VolatilitySurface, side: &str) {
+    let path = surface_cache_path(&surface.symbol, side);
+    let result = std::fs::create_dir_all(SURFACE_CACHE_DIR)
+        .map_err(OptionsError::from)
+        .and_then(|_| surface.save(&path));
+    match result {
+        Ok(()) => debug!("Saved {} surface to {}", side, path.display()),
+        Err(e) => warn!(
+            "Failed to save {} surface to {}: {}",
+            side,
+            path.display(),
+            e
+        ),
+    }
+}
+
+fn publish_surface(key: SurfaceCacheKey, mut surface: VolatilitySurface) -> SurfacePayload {
+    let previous = SURFACE_CACHE.get(&key).map(|e| Arc::clone(e.value()));
+    let diff = previous.and_then(|prev| surface.diff_from(&prev));
+
+    if let Some(ref d) = diff {
+        surface.version = d.version;
+    }
+
+    let surface = Arc::new(surface);
+    SURFACE_CACHE.insert(key.clone(), Arc::clone(&surface));
+
+    match diff {
+        Some(diff) => SurfacePayload::Diff { key, diff },
+        None => SurfacePayload::Full { key, surface },
+    }
+}
+
+// Versions are counted per cache key, so a diff only applies to a held surface
+// published under the same key; stale payloads for another ticker or expiry
+// fall back to the cache.
+fn apply_surface_payload(
+    held: &mut Option<Arc<VolatilitySurface>>,
+    held_key: &mut Option<SurfaceCacheKey>,
+    payload: Option<SurfacePayload>,
+) -> bool {
+    match payload {
+        None => {
+            *held_key = None;
+            held.take().is_some()
+        }
+        Some(SurfacePayload::Full { key, surface }) => {
+            *held = Some(surface);
+            *held_key = Some(key);
+            true
+        }
+        Some(SurfacePayload::Diff { key, diff }) => {
+            if let (Some(current), true) = (held.as_mut(), held_key.as_ref() == Some(&key)) {
+                if current.version == diff.base_version {
+                    if diff.is_empty() {
+                        return false;
+                    }
+                    if let Ok(changed) = Arc::make_mut(current).apply_diff(&diff) {
+                        return changed;
+                    }
+                }
+            }
+
+            match SURFACE_CACHE.get(&key) {
+                Some(entry) => {
+                    *held = Some(Arc::clone(entry.value()));
+                    *held_key = Some(key);
+                    true
+                }
+                None => {
+                    warn!("Received surface diff without a base surface for {}", key.0);
+                    false
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 enum ViewMode {
     VolatilitySkew,
     TermStructure,
+    Surface3D,
+}
+
+impl ViewMode {
+    fn needs_full_chain(self) -> bool {
+        matches!(self, ViewMode::TermStructure | ViewMode::Surface3D)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum SplineMode {
+    FiniteDifference,
+    Monotone,
 }
 
 struct VolatilitySurfaceApp {
@@ -168,15 +285,23 @@ struct VolatilitySurfaceApp {
     expirations_receiver: mpsc::Receiver<ExpirationsData>,
     call_surface: Option<Arc<VolatilitySurface>>,
     put_surface: Option<Arc<VolatilitySurface>>,
+    call_surface_key: Option<SurfaceCacheKey>,
+    put_surface_key: Option<SurfaceCacheKey>,
     expirations: Vec<chrono::NaiveDate>,
     selected_expiration: usize,
     has_expirations: bool,
     expiry_selected: bool,
     underlying_price: Option<f64>,
     view_mode: ViewMode,
+    spline_mode: SplineMode,
     selected_strike: Option<f64>,
     quotes: Vec<OptionQuoteWithIV>,
     selected_contract: Option<OptionQuoteWithIV>,
+    surface_repaints: u64,
+    skipped_repaints: u64,
+    surface_yaw: f64,
+    surface_pitch: f64,
+    monthlies_only: bool,
 }
 
 impl VolatilitySurfaceApp {
@@ -215,6 +340,152 @@ impl VolatilitySurfaceApp {
             })
             .cloned()
     }
+
+    fn load_cached_surfaces(&mut self, ticker: &str) {
+        let load = |side: &str| {
+            VolatilitySurface::load(ticker, surface_cache_path(ticker, side))
+                .ok()
+                .map(Arc::new)
+        };
+        let (call_surface, put_surface) = (load("call"), load("put"));
+        if call_surface.is_none() && put_surface.is_none() {
+            self.status = format!("No cached surface for {}", ticker);
+            return;
+        }
+
+        let mut expirations: Vec<chrono::NaiveDate> = call_surface
+            .iter()
+            .chain(put_surface.iter())
+            .flat_map(|s| s.expirations.iter().map(|e| e.date_naive()))
+            .collect();
+        expirations.sort();
+        expirations.dedup();
+
+        self.call_surface = call_surface;
+        self.put_surface = put_surface;
+        self.call_surface_key = None;
+        self.put_surface_key = None;
+        self.expirations = expirations;
+        self.has_expirations = true;
+        self.selected_expiration = 0;
+        self.expiry_selected = false;
+        self.underlying_price = None;
+        self.quotes.clear();
+        self.selected_contract = None;
+        self.view_mode = ViewMode::Surface3D;
+        self.status = format!("Loaded cached surface for {}", ticker);
+    }
+
+    fn show_surface_3d(&mut self, ui: &mut egui::Ui, surface: &VolatilitySurface) {
+        let (response, painter) =
+            ui.allocate_painter(egui::vec2(900.0, 500.0), egui::Sense::drag());
+        if response.dragged() {
+            let delta = response.drag_delta();
+            self.surface_yaw += delta.x as f64 * SURFACE_DRAG_SENSITIVITY;
+            self.surface_pitch = (self.surface_pitch + delta.y as f64 * SURFACE_DRAG_SENSITIVITY)
+                .clamp(0.0, std::f64::consts::FRAC_PI_2);
+        }
+
+        let rect = response.rect;
+        painter.rect_filled(rect, 0.0, egui::Color32::WHITE);
+
+        let now = chrono::Utc::now();
+        let days: Vec<f64> = surface
+            .expirations
+            .iter()
+            .map(|e| (*e - now).num_seconds() as f64 / 86_400.0)
+            .collect();
+        let finite_vols = surface.volatilities.iter().filter(|v| v.is_finite());
+        let vol_min = finite_vols.clone().copied().fold(f64::INFINITY, f64::min);
+        let vol_max = finite_vols.copied().fold(f64::NEG_INFINITY, f64::max);
+        if days.is_empty() || surface.strikes.is_empty() || !vol_min.is_finite() {
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "No surface data available",
+                egui::FontId::proportional(16.0),
+                egui::Color32::BLACK,
+            );
+            return;
+        }
+
+        let normalize = |v: f64, lo: f64, hi: f64| {
+            if hi > lo {
+                (v - lo) / (hi - lo) - 0.5
+            } else {
+                0.0
+            }
+        };
+        let (strike_lo, strike_hi) = (
+            surface.strikes[0],
+            surface.strikes[surface.strikes.len() - 1],
+        );
+        let (day_lo, day_hi) = (days[0], days[days.len() - 1]);
+
+        let scale = rect.width().min(rect.height()) as f64 * 0.8;
+        let to_screen = |i: usize, j: usize| {
+            let world = [
+                normalize(surface.strikes[j], strike_lo, strike_hi),
+                normalize(days[i], day_lo, day_hi),
+                normalize(surface.volatilities[[i, j]], vol_min, vol_max),
+            ];
+            let ([x, y], depth) = project_point(world, self.surface_yaw, self.surface_pitch);
+            (
+                egui::pos2(
+                    rect.center().x + (x * scale) as f32,
+                    rect.center().y + (y * scale) as f32,
+                ),
+                depth,
+            )
+        };
+
+        let mut quads = Vec::new();
+        for i in 0..days.len().saturating_sub(1) {
+            for j in 0..surface.strikes.len().saturating_sub(1) {
+                let corners = [(i, j), (i, j + 1), (i + 1, j + 1), (i + 1, j)];
+                if corners
+                    .iter()
+                    .any(|&(a, b)| !surface.volatilities[[a, b]].is_finite())
+                {
+                    continue;
+                }
+                let projected = corners.map(|(a, b)| to_screen(a, b));
+                let depth = projected.iter().map(|(_, d)| d).sum::<f64>() / 4.0;
+                let vol = corners
+                    .iter()
+                    .map(|&(a, b)| surface.volatilities[[a, b]])
+                    .sum::<f64>()
+                    / 4.0;
+                quads.push((depth, vol, projected.map(|(p, _)| p)));
+            }
+        }
+        quads.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        for (_, vol, points) in quads {
+            let t = if vol_max > vol_min {
+                (vol - vol_min) / (vol_max - vol_min)
+            } else {
+                0.5
+            };
+            let c = colorous::VIRIDIS.eval_continuous(t);
+            painter.add(egui::Shape::convex_polygon(
+                points.to_vec(),
+                egui::Color32::from_rgb(c.r, c.g, c.b),
+                egui::Stroke::new(0.5, egui::Color32::from_black_alpha(60)),
+            ));
+        }
+
+        painter.text(
+            rect.left_top() + egui::vec2(8.0, 8.0),
+            egui::Align2::LEFT_TOP,
+            format!(
+                "{} IV {:.2}-{:.2}, strikes {:.0}-{:.0}, {:.0}-{:.0} days (drag to rotate)",
+                surface.symbol, vol_min, vol_max, strike_lo, strike_hi, day_lo, day_hi
+            ),
+            egui::FontId::proportional(13.0),
+            egui::Color32::BLACK,
+        );
+    }
 }
 
 impl eframe::App for VolatilitySurfaceApp {
@@ -230,14 +501,32 @@ impl eframe::App for VolatilitySurfaceApp {
         }
 
         while let Ok(plot_data) = self.plot_receiver.try_recv() {
-            self.status = "Received new plot data".to_string();
-            self.call_surface = plot_data.call_surface;
-            self.put_surface = plot_data.put_surface;
+            let call_changed = apply_surface_payload(
+                &mut self.call_surface,
+                &mut self.call_surface_key,
+                plot_data.call_surface,
+            );
+            let put_changed = apply_surface_payload(
+                &mut self.put_surface,
+                &mut self.put_surface_key,
+                plot_data.put_surface,
+            );
             self.underlying_price = Some(plot_data.underlying_price);
             self.quotes = plot_data.quotes;
-            self.selected_contract = None;
 
-            ctx.request_repaint();
+            if call_changed || put_changed {
+                self.status = "Received new plot data".to_string();
+                self.selected_contract = None;
+                self.surface_repaints += 1;
+                ctx.request_repaint();
+            } else {
+                self.skipped_repaints += 1;
+                self.status = "Surface unchanged".to_string();
+            }
+            debug!(
+                "Surface updates: {} repainted, {} skipped",
+                self.surface_repaints, self.skipped_repaints
+            );
         }
 
         egui::CentralPanel::default().show(ctx, |ui| {
@@ -268,10 +557,24 @@ impl eframe::App for VolatilitySurfaceApp {
                         }
                     }
                 }
+                if ui.button("Load cached").clicked() {
+                    if self.ticker_input.trim().is_empty() {
+                        self.status = "Please enter a ticker symbol".to_string();
+                    } else {
+                        let ticker = self.ticker_input.trim().to_uppercase();
+                        self.load_cached_surfaces(&ticker);
+                    }
+                }
             });
 
             ui.separator();
-            ui.label(&self.status);
+            ui.horizontal(|ui| {
+                ui.label(&self.status);
+                ui.weak(format!(
+                    "({} repainted, {} skipped)",
+                    self.surface_repaints, self.skipped_repaints
+                ));
+            });
             ui.separator();
 
             if !self.expirations.is_empty() && self.has_expirations {
@@ -280,9 +583,10 @@ impl eframe::App for VolatilitySurfaceApp {
                     let old_view_mode = self.view_mode;
                     ui.radio_value(&mut self.view_mode, ViewMode::VolatilitySkew, "Volatility Skew");
                     ui.radio_value(&mut self.view_mode, ViewMode::TermStructure, "Term Structure");
+                    ui.radio_value(&mut self.view_mode, ViewMode::Surface3D, "3D Surface");
 
                     if old_view_mode != self.view_mode {
-                        if self.view_mode == ViewMode::TermStructure && !self.ticker_input.trim().is_empty() {
+                        if self.view_mode.needs_full_chain() && !self.ticker_input.trim().is_empty() {
                             let ticker = self.ticker_input.trim().to_uppercase();
                             self.status = format!("Fetching all option data for {}", ticker);
                             self.call_surface = None;
@@ -295,6 +599,12 @@ impl eframe::App for VolatilitySurfaceApp {
                     }
                 });
 
+                ui.horizontal(|ui| {
+                    ui.label("Curve:");
+                    ui.radio_value(&mut self.spline_mode, SplineMode::Monotone, "Monotone");
+                    ui.radio_value(&mut self.spline_mode, SplineMode::FiniteDifference, "Finite Difference");
+                });
+
                 if self.view_mode == ViewMode::VolatilitySkew {
                     ui.horizontal(|ui| {
                         ui.label("Expiration:");
@@ -310,6 +620,9 @@ impl eframe::App for VolatilitySurfaceApp {
                             )
                             .show_ui(ui, |ui| {
                                 for (i, exp) in self.expirations.iter().enumerate() {
+                                    if self.monthlies_only && !ExpirationKind::from_date(*exp).is_monthly() {
+                                        continue;
+                                    }
                                     let response = ui
                                         .selectable_value(
                                             &mut self.selected_expiration,
@@ -330,6 +643,7 @@ impl eframe::App for VolatilitySurfaceApp {
                                     }
                                 }
                             });
+                        ui.checkbox(&mut self.monthlies_only, "Monthlies only");
                     });
                 } else if self.view_mode == ViewMode::TermStructure {
                     ui.horizontal(|ui| {
@@ -414,6 +728,8 @@ impl eframe::App for VolatilitySurfaceApp {
                     ViewMode::VolatilitySkew => !self.expirations.is_empty() && self.expiry_selected,
 
                     ViewMode::TermStructure => self.selected_strike.is_some(),
+
+                    ViewMode::Surface3D => true,
                 };
 
                 if can_show_plot {
@@ -421,11 +737,7 @@ impl eframe::App for VolatilitySurfaceApp {
                     match self.view_mode {
                         ViewMode::VolatilitySkew => {
 
-                            let exp_dt = chrono::Utc.from_utc_datetime(
-                                &self.expirations[self.selected_expiration]
-                                    .and_hms_opt(16, 0, 0)
-                                    .unwrap(),
-                            );
+                            let exp_dt = utils::market_close_utc(self.expirations[self.selected_expiration]);
 
                             let underlying = self.underlying_price.unwrap_or(0.0);
 
@@ -436,7 +748,8 @@ impl eframe::App for VolatilitySurfaceApp {
                             let mut plot = Plot::new(plot_id)
                                 .height(400.0)
                                 .width(900.0)
-                                .label_formatter(|_, _| String::new());
+                                .label_formatter(|_, _| String::new())
+                                .legend(Legend::default());
 
                             if underlying > 0.0 {
                                 let strike_range = 0.2 * underlying;
@@ -455,27 +768,23 @@ impl eframe::App for VolatilitySurfaceApp {
                             }
 
                             plot.show(ui, |plot_ui| {
-                                let surfaces = [
-                                    (self.call_surface.as_ref(), egui::Color32::from_rgb(0, 100, 139)),
-                                    (self.put_surface.as_ref(), egui::Color32::from_rgb(139, 0, 0)),
-                                ];
-                                for (surface_opt, color) in surfaces {
-                                    if let Some(surface) = surface_opt {
-                                        if let Ok((strikes, vols)) = surface.slice_by_expiration(exp_dt) {
-                                            let strike_vec: Vec<f64> = strikes.iter().cloned().collect();
-                                            let vol_vec: Vec<f64> = vols.iter().cloned().collect();
-                                            let spline_points = cubic_hermite_spline(&strike_vec, &vol_vec, 10);
-                                            let line = Line::new(PlotPoints::from(spline_points)).color(color);
-                                            plot_ui.line(line);
-                                            let points: Vec<[f64; 2]> = strike_vec
-                                                .iter()
-                                                .zip(vol_vec.iter())
-                                                .map(|(s, v)| [*s, *v])
-                                                .collect();
-                                            let scatter = Points::new(PlotPoints::from(points)).radius(3.0).color(color);
-                                            plot_ui.points(scatter);
-                                        }
-                                    }
+                                let series = skew_points_by_type(
+                                    self.call_surface.as_deref(),
+                                    self.put_surface.as_deref(),
+                                    exp_dt,
+                                );
+                                for (option_type, points) in series {
+                                    let (name, color) = match option_type {
+                                        OptionType::Call => ("Calls", egui::Color32::from_rgb(0, 100, 139)),
+                                        OptionType::Put => ("Puts", egui::Color32::from_rgb(139, 0, 0)),
+                                    };
+                                    let (strike_vec, vol_vec): (Vec<f64>, Vec<f64>) =
+                                        points.iter().map(|p| (p[0], p[1])).unzip();
+                                    let spline_points = cubic_hermite_spline(&strike_vec, &vol_vec, 10, self.spline_mode);
+                                    let line = Line::new(PlotPoints::from(spline_points)).color(color).name(name);
+                                    plot_ui.line(line);
+                                    let scatter = Points::new(PlotPoints::from(points)).radius(3.0).color(color).name(name);
+                                    plot_ui.points(scatter);
                                 }
 
                                 if plot_ui.response().clicked() {
@@ -520,7 +829,7 @@ impl eframe::App for VolatilitySurfaceApp {
                                                     .map(|d| (d.date_naive().signed_duration_since(today)).num_days() as f64)
                                                     .collect();
 
-                                                let spline_points = cubic_hermite_spline(&x_vals, &y_vals, 10);
+                                                let spline_points = cubic_hermite_spline(&x_vals, &y_vals, 10, self.spline_mode);
                                                 let line = Line::new(PlotPoints::from(spline_points)).color(color);
                                                 plot_ui.line(line);
 
@@ -551,6 +860,12 @@ impl eframe::App for VolatilitySurfaceApp {
                                 ui.label("Please select a strike price to view the term structure.");
                             }
                         }
+                        ViewMode::Surface3D => {
+                            let surface = self.call_surface.clone().or_else(|| self.put_surface.clone());
+                            if let Some(surface) = surface {
+                                self.show_surface_3d(ui, &surface);
+                            }
+                        }
                     }
                 } else {
 
@@ -569,6 +884,7 @@ impl eframe::App for VolatilitySurfaceApp {
                                 ui.label("Failed to render term structure. Please try a different symbol or strike price.");
                             }
                         }
+                        ViewMode::Surface3D => {}
                     }
                 }
             } else if !self.has_expirations {
@@ -591,6 +907,19 @@ impl eframe::App for VolatilitySurfaceApp {
                     "Bid: {:.2}  Ask: {:.2}  Last: {:.2}",
                     c.quote.bid, c.quote.ask, c.quote.last
                 ));
+                ui.label(format!(
+                    "Break-even: {:.2}",
+                    utils::probability::break_even(&c.quote)
+                ));
+                let underlying = c.quote.underlying_price;
+                let intrinsic = c.quote.contract.intrinsic_value(underlying);
+                ui.label(format!(
+                    "{}  Moneyness: {:.3}  Intrinsic: {:.2}  Extrinsic: {:.2}",
+                    if c.quote.contract.is_itm(underlying) { "ITM" } else { "OTM" },
+                    c.quote.contract.moneyness(underlying),
+                    intrinsic,
+                    c.quote.mid_price() - intrinsic
+                ));
                 if let Some(iv) = c.implied_volatility {
                     ui.label(format!("Implied Vol: {:.4}", iv));
                 }
@@ -630,15 +959,16 @@ pub fn parse_options_chain(data: &Value) -> Result<Vec<OptionContract>> {
                     if let Ok(exp_date) = chrono::DateTime::parse_from_rfc3339(expiration) {
                         let exp_utc = exp_date.with_timezone(&chrono::Utc);
                         let option_type = match option_type {
-                            "call" => options_rs::models::OptionType::Call,
-                            "put" => options_rs::models::OptionType::Put,
+                            "call" => OptionType::Call,
+                            "put" => OptionType::Put,
                             _ => continue,
                         };
 
-                        let contract =
-                            OptionContract::new(symbol.to_string(), option_type, strike, exp_utc);
-
-                        options.push(contract);
+                        match OptionContract::new(symbol.to_string(), option_type, strike, exp_utc)
+                        {
+                            Ok(contract) => options.push(contract),
+                            Err(e) => warn!("Skipping contract {}: {}", symbol, e),
+                        }
                     }
                 }
             }
@@ -647,11 +977,8 @@ pub fn parse_options_chain(data: &Value) -> Result<Vec<OptionContract>> {
 
     Ok(options)
 }
-fn cubic_hermite_spline(x: &[f64], y: &[f64], steps: usize) -> Vec<[f64; 2]> {
+fn finite_difference_tangents(x: &[f64], y: &[f64]) -> Vec<f64> {
     let n = x.len();
-    if n < 2 {
-        return x.iter().zip(y.iter()).map(|(&a, &b)| [a, b]).collect();
-    }
     let mut m = vec![0.0; n];
     for i in 0..n {
         if i == 0 {
@@ -662,6 +989,94 @@ fn cubic_hermite_spline(x: &[f64], y: &[f64], steps: usize) -> Vec<[f64; 2]> {
             m[i] = (y[i + 1] - y[i - 1]) / (x[i + 1] - x[i - 1]);
         }
     }
+    m
+}
+
+fn monotone_tangents(x: &[f64], y: &[f64]) -> Vec<f64> {
+    let n = x.len();
+    let delta: Vec<f64> = (0..n - 1)
+        .map(|i| (y[i + 1] - y[i]) / (x[i + 1] - x[i]))
+        .collect();
+
+    let mut m = vec![0.0; n];
+    m[0] = delta[0];
+    m[n - 1] = delta[n - 2];
+    for i in 1..n - 1 {
+        m[i] = if delta[i - 1] * delta[i] <= 0.0 {
+            0.0
+        } else {
+            (delta[i - 1] + delta[i]) / 2.0
+        };
+    }
+
+    for i in 0..n - 1 {
+        if delta[i] == 0.0 {
+            m[i] = 0.0;
+            m[i + 1] = 0.0;
+            continue;
+        }
+        let alpha = m[i] / delta[i];
+        let beta = m[i + 1] / delta[i];
+        let norm = alpha * alpha + beta * beta;
+        if norm > 9.0 {
+            let tau = 3.0 / norm.sqrt();
+            m[i] = tau * alpha * delta[i];
+            m[i + 1] = tau * beta * delta[i];
+        }
+    }
+    m
+}
+
+// Rotates a point in the unit cube (x strike, y time, z IV up) about the
+// vertical axis by `yaw`, tilts it towards the viewer by `pitch` and drops it
+// onto the screen plane. Returns screen coordinates (y pointing down) and depth,
+// where a larger depth is farther from the viewer.
+fn project_point(point: [f64; 3], yaw: f64, pitch: f64) -> ([f64; 2], f64) {
+    let [x, y, z] = point;
+    let (sin_yaw, cos_yaw) = yaw.sin_cos();
+    let (sin_pitch, cos_pitch) = pitch.sin_cos();
+    let x1 = x * cos_yaw - y * sin_yaw;
+    let y1 = x * sin_yaw + y * cos_yaw;
+    let y2 = y1 * cos_pitch - z * sin_pitch;
+    let z2 = y1 * sin_pitch + z * cos_pitch;
+    ([x1, -z2], y2)
+}
+
+// Per-type (strike, IV) points for one expiration of the skew view, sorted by
+// strike with empty cells dropped. Types without data for the expiration are omitted.
+fn skew_points_by_type(
+    call_surface: Option<&VolatilitySurface>,
+    put_surface: Option<&VolatilitySurface>,
+    expiration: chrono::DateTime<chrono::Utc>,
+) -> Vec<(OptionType, Vec<[f64; 2]>)> {
+    [
+        (OptionType::Call, call_surface),
+        (OptionType::Put, put_surface),
+    ]
+    .into_iter()
+    .filter_map(|(option_type, surface)| {
+        let (strikes, vols) = surface?
+            .slice_by_expiration_filtered(expiration, None)
+            .ok()?;
+        let points: Vec<[f64; 2]> = strikes
+            .iter()
+            .zip(vols.iter())
+            .map(|(&k, &v)| [k, v])
+            .collect();
+        (!points.is_empty()).then_some((option_type, points))
+    })
+    .collect()
+}
+
+fn cubic_hermite_spline(x: &[f64], y: &[f64], steps: usize, mode: SplineMode) -> Vec<[f64; 2]> {
+    let n = x.len();
+    if n < 2 {
+        return x.iter().zip(y.iter()).map(|(&a, &b)| [a, b]).collect();
+    }
+    let m = match mode {
+        SplineMode::FiniteDifference => finite_difference_tangents(x, y),
+        SplineMode::Monotone => monotone_tangents(x, y),
+    };
 
     let mut result = Vec::new();
     for i in 0..n - 1 {
@@ -682,45 +1097,11 @@ fn cubic_hermite_spline(x: &[f64], y: &[f64], steps: usize) -> Vec<[f64; 2]> {
 }
 
 async fn fetch_expirations(
+    provider: &dyn OptionDataProvider,
     symbol: &str,
     expirations_sender: mpsc::Sender<ExpirationsData>,
 ) -> Result<()> {
-    let config = Config::from_env()?;
-    let rest_client = RestClient::new(config.alpaca.clone());
-
-    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
-
-    let chain = rest_client
-        .get_options_chain(
-            symbol,
-            None,
-            Some(&today),
-            None,
-            None,
-            None,
-            Some(10000),
-            None,
-        )
-        .await?;
-
-    if chain.results.is_empty() {
-        warn!("No option contracts returned for symbol {}", symbol);
-        return Ok(());
-    }
-
-    let mut contracts = Vec::new();
-    for c in &chain.results {
-        if let Some(contract) = OptionContract::from_occ_symbol(&c.symbol) {
-            contracts.push(contract);
-        }
-    }
-
-    let mut expirations: Vec<chrono::NaiveDate> = contracts
-        .iter()
-        .map(|c| c.expiration.date_naive())
-        .collect();
-    expirations.sort();
-    expirations.dedup();
+    let expirations = provider.expirations(symbol).await?;
     if expirations.is_empty() {
         warn!("No expirations found for {}", symbol);
         return Ok(());
@@ -736,56 +1117,17 @@ async fn fetch_expirations(
 }
 
 async fn run_volatility_surface_plot(
+    provider: &dyn OptionDataProvider,
     symbol: &str,
     plot_sender: mpsc::Sender<PlotData>,
     expiry: Option<chrono::NaiveDate>,
     view_mode: Option<ViewMode>,
 ) -> Result<()> {
     let config = Config::from_env()?;
-    let rest_client = RestClient::new(config.alpaca.clone());
 
     let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
 
-    let chain = rest_client
-        .get_options_chain(
-            symbol,
-            None,
-            Some(&today),
-            None,
-            None,
-            None,
-            Some(10000),
-            None,
-        )
-        .await?;
-
-    if chain.results.is_empty() {
-        warn!("No option contracts returned for symbol {}", symbol);
-        return Ok(());
-    }
-
-    let mut contracts = Vec::new();
-    for c in &chain.results {
-        if let Some(contract) = OptionContract::from_occ_symbol(&c.symbol) {
-            contracts.push(contract);
-        }
-    }
-
-    let mut expirations: Vec<chrono::NaiveDate> = contracts
-        .iter()
-        .map(|c| c.expiration.date_naive())
-        .collect();
-    expirations.sort();
-    expirations.dedup();
-    if expirations.is_empty() {
-        warn!("No expirations found for {}", symbol);
-        return Ok(());
-    }
-
-    let quote_resp = rest_client
-        .get_latest_single_stock_quote(symbol, None, None)
-        .await?;
-    let underlying_price = (quote_resp.quote.bid + quote_resp.quote.ask) / 2.0;
+    let underlying_price = provider.underlying_quote(symbol).await?;
 
     let strike_range_factor = 0.5;
     let strike_min = underlying_price * (1.0 - strike_range_factor);
@@ -796,73 +1138,28 @@ async fn run_volatility_surface_plot(
         strike_min, strike_max, underlying_price
     );
 
-    let snaps = if let Some(ViewMode::TermStructure) = view_mode {
-        info!(
-            "Term structure view: Fetching all option chain snapshots for {}",
-            symbol
-        );
-
-        rest_client
-            .get_option_chain_snapshots(
-                symbol,
-                Some("indicative"),
-                Some(1000),
-                None,
-                None,
-                None,
-                Some(strike_min),
-                Some(strike_max),
-                None,
-                Some(&today),
-                None,
-                None,
-            )
-            .await?
-    } else if let Some(chosen) = expiry {
-        let chosen_str = chosen.format("%Y-%m-%d").to_string();
-        info!(
-            "Volatility skew view: Fetching option chain snapshots for {} exp {}",
-            symbol, chosen_str
-        );
-
-        rest_client
-            .get_option_chain_snapshots(
-                symbol,
-                Some("indicative"),
-                Some(1000),
-                None,
-                None,
-                None,
-                Some(strike_min),
-                Some(strike_max),
-                Some(&chosen_str),
-                None,
-                None,
-                None,
-            )
-            .await?
-    } else {
-        info!("Fetching all option chain snapshots for {}", symbol);
-
-        rest_client
-            .get_option_chain_snapshots(
-                symbol,
-                Some("indicative"),
-                Some(1000),
-                None,
-                None,
-                None,
-                Some(strike_min),
-                Some(strike_max),
-                None,
-                Some(&today),
-                None,
-                None,
-            )
-            .await?
+    let mut filter = ChainSymbolFilter {
+        strike_price_gte: Some(strike_min),
+        strike_price_lte: Some(strike_max),
+        ..Default::default()
     };
+    match expiry {
+        Some(chosen) if !view_mode.is_some_and(ViewMode::needs_full_chain) => {
+            let chosen_str = chosen.format("%Y-%m-%d").to_string();
+            info!(
+                "Volatility skew view: Fetching option chain snapshots for {} exp {}",
+                symbol, chosen_str
+            );
+            filter.expiration_date = Some(chosen_str);
+        }
+        _ => {
+            info!("Fetching all option chain snapshots for {}", symbol);
+            filter.expiration_date_gte = Some(today);
+        }
+    }
+    let snaps = provider.option_chain_snapshots(symbol, &filter).await?;
 
-    if snaps.snapshots.is_empty() {
+    if snaps.is_empty() {
         if let Some(chosen) = expiry {
             warn!("No option snapshots found for {} exp {}", symbol, chosen);
         } else {
@@ -871,89 +1168,157 @@ async fn run_volatility_surface_plot(
         return Ok(());
     }
 
+    let coverage = SnapshotFeedCoverage::from_snapshots(snaps.iter(), FEED_PROBE_SNAPSHOTS);
+    if coverage.lacks_vendor_analytics() {
+        FEED_ANALYTICS_NOTICE.call_once(|| {
+            info!(
+                "greeks/IV unavailable on this feed; computing locally (checked {} snapshots)",
+                coverage.inspected
+            );
+        });
+    }
+
+    let risk_free_rate = DEFAULT_RISK_FREE_RATE;
+    let yield_curve = match utils::treasury_yield_curve().await {
+        Ok(curve) => Some(curve),
+        Err(e) => {
+            warn!(
+                "Falling back to a flat {} risk-free rate: {}",
+                risk_free_rate, e
+            );
+            None
+        }
+    };
+
+    let entries: Vec<_> = snaps
+        .into_iter()
+        .filter_map(|snap| {
+            OptionContract::from_occ_symbol(&snap.symbol)
+                .map(|contract| (contract, (snap.symbol.clone(), snap)))
+        })
+        .collect();
+    let entries =
+        utils::enforce_contract_limit(entries, config.max_contracts, config.contract_limit_mode)?;
+
+    let mut expirations: Vec<chrono::NaiveDate> = entries
+        .iter()
+        .map(|(contract, _)| contract.expiration.date_naive())
+        .collect();
+    expirations.sort();
+    expirations.dedup();
+
     let mut quotes_with_iv = Vec::new();
-    for (occ, snap) in snaps.snapshots {
-        if let Some(contract) = OptionContract::from_occ_symbol(&occ) {
-            if let Some(chosen) = expiry {
-                if let Some(ViewMode::TermStructure) = view_mode {
-                } else if contract.expiration.date_naive() != chosen {
-                    continue;
-                }
+    for (contract, (occ, snap)) in entries {
+        if let Some(chosen) = expiry {
+            if !view_mode.is_some_and(ViewMode::needs_full_chain)
+                && contract.expiration.date_naive() != chosen
+            {
+                continue;
             }
+        }
 
-            let mut bid = snap.last_quote.as_ref().map(|q| q.bid);
-            let mut ask = snap.last_quote.as_ref().map(|q| q.ask);
-            let mut last_price = snap.last_trade.as_ref().map(|t| t.price);
-            let volume = snap.last_trade.as_ref().map(|t| t.size).unwrap_or(0);
-            let mut timestamp = snap
-                .last_quote
-                .as_ref()
-                .map(|q| q.t)
-                .or_else(|| snap.last_trade.as_ref().map(|t| t.t));
-
-            let bar = snap
-                .daily_bar
-                .as_ref()
-                .or(snap.minute_bar.as_ref())
-                .or(snap.prev_daily_bar.as_ref());
-
-            if let Some(bar_data) = bar {
-                if last_price.is_none() {
-                    last_price = Some(bar_data.c);
-                }
-
-                if bid.is_none() || ask.is_none() {
-                    let mid = bar_data.c;
-                    let spread = mid * 0.05;
+        let mut bid = snap.last_quote.as_ref().map(|q| q.bid);
+        let mut ask = snap.last_quote.as_ref().map(|q| q.ask);
+        let mut last_price = snap.last_trade.as_ref().map(|t| t.price);
+        let volume = snap.last_trade.as_ref().map(|t| t.size).unwrap_or(0);
+        let mut timestamp = snap
+            .last_quote
+            .as_ref()
+            .map(|q| q.t)
+            .or_else(|| snap.last_trade.as_ref().map(|t| t.t));
+
+        let bar = snap
+            .daily_bar
+            .as_ref()
+            .or(snap.minute_bar.as_ref())
+            .or(snap.prev_daily_bar.as_ref());
+
+        if let Some(bar_data) = bar {
+            if last_price.is_none() {
+                last_price = Some(bar_data.c);
+            }
 
-                    if bid.is_none() {
-                        bid = Some(mid - spread / 2.0);
-                    }
+            if bid.is_none() || ask.is_none() {
+                let mid = bar_data.c;
+                let spread = mid * 0.05;
 
-                    if ask.is_none() {
-                        ask = Some(mid + spread / 2.0);
-                    }
+                if bid.is_none() {
+                    bid = Some(mid - spread / 2.0);
                 }
 
-                if timestamp.is_none() {
-                    timestamp = Some(bar_data.t);
+                if ask.is_none() {
+                    ask = Some(mid + spread / 2.0);
                 }
             }
 
-            let Some(bid_value) = bid else {
-                debug!("Skipping contract {} - no bid price available", occ);
-                continue;
-            };
-            let Some(ask_value) = ask else {
-                debug!("Skipping contract {} - no ask price available", occ);
-                continue;
-            };
-            let Some(last_price_value) = last_price else {
-                debug!("Skipping contract {} - no last price available", occ);
-                continue;
-            };
-            let timestamp = timestamp.unwrap_or_else(chrono::Utc::now);
-
-            let implied_volatility = snap.implied_volatility;
-            let greeks = snap.greeks;
-
-            let quote = OptionQuote {
-                contract,
-                bid: bid_value,
-                ask: ask_value,
-                last: last_price_value,
-                volume,
-                open_interest: 0,
-                underlying_price,
-                timestamp,
-            };
-
-            quotes_with_iv.push(OptionQuoteWithIV {
-                quote,
-                implied_volatility,
-                greeks,
-            });
+            if timestamp.is_none() {
+                timestamp = Some(bar_data.t);
+            }
         }
+
+        let Some(bid_value) = bid else {
+            debug!("Skipping contract {} - no bid price available", occ);
+            continue;
+        };
+        let Some(ask_value) = ask else {
+            debug!("Skipping contract {} - no ask price available", occ);
+            continue;
+        };
+        let Some(last_price_value) = last_price else {
+            debug!("Skipping contract {} - no last price available", occ);
+            continue;
+        };
+        let timestamp = timestamp.unwrap_or_else(chrono::Utc::now);
+
+        let vendor_iv = snap
+            .implied_volatility
+            .filter(|_| snap.has_implied_volatility());
+        let greeks = snap.greeks;
+
+        let quote = OptionQuote {
+            contract,
+            bid: bid_value,
+            ask: ask_value,
+            last: last_price_value,
+            volume,
+            open_interest: 0,
+            underlying_price,
+            timestamp,
+        };
+
+        let rate = yield_curve.as_ref().map_or(risk_free_rate, |curve| {
+            curve.rate_for(quote.contract.time_to_expiration())
+        });
+
+        let implied_volatility = vendor_iv.or_else(|| match ImpliedVolatility::from_quote_bid_ask(
+            &quote, rate, 0.0,
+        ) {
+            Ok(iv) => Some(iv.value),
+            Err(OptionsError::IvError(e @ IvError::BelowIntrinsic { .. })) => {
+                debug!("Skipping IV for {}: {}", occ, e);
+                None
+            }
+            Err(_) => None,
+        });
+
+        let greeks = greeks.or_else(|| {
+            implied_volatility.map(|iv| {
+                utils::compute_greeks(
+                    underlying_price,
+                    quote.contract.strike,
+                    quote.contract.time_to_expiration(),
+                    rate,
+                    iv,
+                    quote.contract.is_call(),
+                )
+            })
+        });
+
+        quotes_with_iv.push(OptionQuoteWithIV {
+            quote,
+            implied_volatility,
+            greeks,
+        });
     }
 
     if quotes_with_iv.is_empty() {
@@ -965,8 +1330,6 @@ async fn run_volatility_surface_plot(
         return Ok(());
     }
 
-    let risk_free_rate = 0.03;
-
     let (call_surface, put_surface) = {
         let quotes_clone = quotes_with_iv.clone();
         let symbol_clone = symbol.to_string();
@@ -977,9 +1340,24 @@ async fn run_volatility_surface_plot(
         .map_err(|e| OptionsError::Other(format!("Failed to calculate surfaces: {}", e)))??
     };
 
+    if let Some(ref surface) = call_surface {
+        save_surface_to_disk(surface, "call");
+    }
+    if let Some(ref surface) = put_surface {
+        save_surface_to_disk(surface, "put");
+    }
+
+    let cache_expiry = match (view_mode, expiry) {
+        (Some(mode), _) if mode.needs_full_chain() => chrono::DateTime::<chrono::Utc>::MIN_UTC,
+        (_, None) => chrono::DateTime::<chrono::Utc>::MIN_UTC,
+        (_, Some(chosen)) => utils::market_close_utc(chosen),
+    };
+
     let plot_data = PlotData {
-        call_surface: call_surface.map(Arc::new),
-        put_surface: put_surface.map(Arc::new),
+        call_surface: call_surface
+            .map(|s| publish_surface((format!("{}:call", symbol), cache_expiry), s)),
+        put_surface: put_surface
+            .map(|s| publish_surface((format!("{}:put", symbol), cache_expiry), s)),
         expirations,
         underlying_price,
         quotes: quotes_with_iv.clone(),
@@ -995,6 +1373,7 @@ async fn run_volatility_surface_plot(
 async fn main() -> Result<()> {
     let config = Config::from_env()?;
     config.init_logging()?;
+    let provider: Arc<dyn OptionDataProvider> = Arc::from(provider_from_config(&config)?);
 
     let (ticker_sender, mut ticker_receiver) =
         mpsc::channel::<(String, Option<chrono::NaiveDate>, Option<ViewMode>)>(10);
@@ -1006,10 +1385,11 @@ async fn main() -> Result<()> {
         let symbol = args[1].clone();
         info!("Ticker provided as command-line argument: {}", symbol);
 
-        fetch_expirations(&symbol, expirations_sender.clone()).await?;
+        fetch_expirations(provider.as_ref(), &symbol, expirations_sender.clone()).await?;
 
         tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-        run_volatility_surface_plot(&symbol, plot_sender.clone(), None, None).await?;
+        run_volatility_surface_plot(provider.as_ref(), &symbol, plot_sender.clone(), None, None)
+            .await?;
         return Ok(());
     }
 
@@ -1021,26 +1401,38 @@ async fn main() -> Result<()> {
                 ticker, expiry, view_mode
             );
             if expiry.is_none() {
-                if let Err(e) = fetch_expirations(&ticker, expirations_sender.clone()).await {
+                if let Err(e) =
+                    fetch_expirations(provider.as_ref(), &ticker, expirations_sender.clone()).await
+                {
                     warn!("Error fetching expirations for {}: {}", ticker, e);
                 }
 
-                if let Some(ViewMode::TermStructure) = view_mode {
+                if view_mode.is_some_and(ViewMode::needs_full_chain) {
                     info!(
-                        "Term structure view selected, fetching all option data for {}",
-                        ticker
+                        "{:?} view selected, fetching all option data for {}",
+                        view_mode, ticker
                     );
-                    if let Err(e) =
-                        run_volatility_surface_plot(&ticker, plot_sender.clone(), None, view_mode)
-                            .await
+                    if let Err(e) = run_volatility_surface_plot(
+                        provider.as_ref(),
+                        &ticker,
+                        plot_sender.clone(),
+                        None,
+                        view_mode,
+                    )
+                    .await
                     {
                         warn!("Error plotting term structure for {}: {}", ticker, e);
                     }
                 }
             } else {
-                if let Err(e) =
-                    run_volatility_surface_plot(&ticker, plot_sender.clone(), expiry, view_mode)
-                        .await
+                if let Err(e) = run_volatility_surface_plot(
+                    provider.as_ref(),
+                    &ticker,
+                    plot_sender.clone(),
+                    expiry,
+                    view_mode,
+                )
+                .await
                 {
                     warn!("Error plotting volatility surface for {}: {}", ticker, e);
                 }
@@ -1056,15 +1448,23 @@ async fn main() -> Result<()> {
         expirations_receiver,
         call_surface: None,
         put_surface: None,
+        call_surface_key: None,
+        put_surface_key: None,
         expirations: Vec::new(),
         selected_expiration: 0,
         has_expirations: false,
         expiry_selected: false,
         underlying_price: None,
         view_mode: ViewMode::VolatilitySkew,
+        spline_mode: SplineMode::Monotone,
         selected_strike: None,
         quotes: Vec::new(),
         selected_contract: None,
+        surface_repaints: 0,
+        skipped_repaints: 0,
+        surface_yaw: -std::f64::consts::FRAC_PI_4,
+        surface_pitch: std::f64::consts::FRAC_PI_6,
+        monthlies_only: false,
     };
 
     let native_options = eframe::NativeOptions {
@@ -1086,3 +1486,133 @@ async fn main() -> Result<()> {
     info!("shutting down");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn surface(symbol: &str, vol: f64) -> VolatilitySurface {
+        VolatilitySurface {
+            symbol: symbol.to_string(),
+            expirations: vec![chrono::DateTime::<chrono::Utc>::MIN_UTC],
+            strikes: vec![100.0],
+            volatilities: ndarray::Array2::from_elem((1, 1), vol),
+            timestamp: chrono::Utc::now(),
+            version: 1,
+        }
+    }
+
+    #[test]
+    fn diff_for_another_key_is_not_applied() {
+        let held_key: SurfaceCacheKey = (
+            "AAA:call".to_string(),
+            chrono::DateTime::<chrono::Utc>::MIN_UTC,
+        );
+        let other_key: SurfaceCacheKey = (
+            "BBB:call".to_string(),
+            chrono::DateTime::<chrono::Utc>::MIN_UTC,
+        );
+        let mut held = Some(Arc::new(surface("AAA", 0.2)));
+        let mut key = Some(held_key.clone());
+
+        let diff = surface("BBB", 0.5).diff_from(&surface("BBB", 0.4)).unwrap();
+        let payload = SurfacePayload::Diff {
+            key: other_key,
+            diff: diff.clone(),
+        };
+        assert!(!apply_surface_payload(&mut held, &mut key, Some(payload)));
+        assert_eq!(held.as_ref().unwrap().volatilities[[0, 0]], 0.2);
+        assert_eq!(key.as_ref(), Some(&held_key));
+
+        let payload = SurfacePayload::Diff {
+            key: held_key,
+            diff,
+        };
+        assert!(apply_surface_payload(&mut held, &mut key, Some(payload)));
+        assert_eq!(held.as_ref().unwrap().volatilities[[0, 0]], 0.5);
+    }
+
+    #[test]
+    fn monotone_spline_does_not_overshoot_uneven_strikes() {
+        let strikes = [80.0, 90.0, 95.0, 100.0, 140.0, 150.0];
+        let vols = [0.40, 0.30, 0.29, 0.28, 0.28, 0.20];
+
+        let curve = cubic_hermite_spline(&strikes, &vols, 20, SplineMode::Monotone);
+        assert!(curve.windows(2).all(|w| w[1][1] <= w[0][1] + 1e-12));
+        assert_eq!(curve.last(), Some(&[150.0, 0.20]));
+
+        let overshooting = cubic_hermite_spline(&strikes, &vols, 20, SplineMode::FiniteDifference);
+        assert!(overshooting.windows(2).any(|w| w[1][1] > w[0][1] + 1e-12));
+    }
+
+    #[test]
+    fn skew_points_are_split_by_option_type() {
+        let exp = chrono::DateTime::<chrono::Utc>::MIN_UTC;
+        let mut call = surface("AAA", 0.0);
+        call.strikes = vec![90.0, 100.0, 110.0];
+        call.volatilities =
+            ndarray::Array2::from_shape_vec((1, 3), vec![0.3, f64::NAN, 0.2]).unwrap();
+        let mut put = call.clone();
+        put.volatilities = ndarray::Array2::from_shape_vec((1, 3), vec![0.35, 0.3, 0.28]).unwrap();
+
+        let series = skew_points_by_type(Some(&call), Some(&put), exp);
+        assert_eq!(
+            series,
+            vec![
+                (OptionType::Call, vec![[90.0, 0.3], [110.0, 0.2]]),
+                (
+                    OptionType::Put,
+                    vec![[90.0, 0.35], [100.0, 0.3], [110.0, 0.28]]
+                ),
+            ]
+        );
+
+        let puts_only = skew_points_by_type(None, Some(&put), exp);
+        assert_eq!(puts_only.len(), 1);
+        assert_eq!(puts_only[0].0, OptionType::Put);
+
+        let other_expiry = exp + chrono::Duration::days(1);
+        assert!(skew_points_by_type(Some(&call), Some(&put), other_expiry).is_empty());
+    }
+
+    #[test]
+    fn projection_rotates_about_the_vertical_axis_then_tilts() {
+        use std::f64::consts::FRAC_PI_2;
+        let close = |a: ([f64; 2], f64), b: ([f64; 2], f64)| {
+            (a.0[0] - b.0[0]).abs() < 1e-12
+                && (a.0[1] - b.0[1]).abs() < 1e-12
+                && (a.1 - b.1).abs() < 1e-12
+        };
+
+        // Unrotated: x across, z up (screen y points down), y into the screen.
+        assert!(close(
+            project_point([1.0, 2.0, 3.0], 0.0, 0.0),
+            ([1.0, -3.0], 2.0)
+        ));
+
+        // A quarter turn of yaw swings +x into the screen and +y to the left.
+        assert!(close(
+            project_point([1.0, 0.0, 0.0], FRAC_PI_2, 0.0),
+            ([0.0, 0.0], 1.0)
+        ));
+        assert!(close(
+            project_point([0.0, 1.0, 0.0], FRAC_PI_2, 0.0),
+            ([-1.0, 0.0], 0.0)
+        ));
+
+        // A quarter turn of pitch looks straight down: depth becomes -z.
+        assert!(close(
+            project_point([0.0, 1.0, 0.0], 0.0, FRAC_PI_2),
+            ([0.0, -1.0], 0.0)
+        ));
+        assert!(close(
+            project_point([0.0, 0.0, 1.0], 0.0, FRAC_PI_2),
+            ([0.0, 0.0], -1.0)
+        ));
+
+        // Rotations keep distances from the origin.
+        let ([sx, sy], depth) = project_point([0.3, -1.2, 0.7], 0.8, 0.4);
+        let norm = (sx * sx + sy * sy + depth * depth).sqrt();
+        assert!((norm - (0.09f64 + 1.44 + 0.49).sqrt()).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,260 @@
+use eframe::egui;
+use options_rs::error::{OptionsError, Result};
+use options_rs::models::volatility::VolatilitySurface;
+use options_rs::utils::{self, polars_utils};
+use polars::prelude::*;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use tracing::{info, warn};
+
+const BASE_FRAME_INTERVAL: Duration = Duration::from_secs(1);
+
+struct ReplayFrame {
+    path: PathBuf,
+    surface: VolatilitySurface,
+}
+
+fn surface_timestamp(df: &DataFrame, path: &Path) -> chrono::DateTime<chrono::Utc> {
+    let from_column = df
+        .column("timestamp")
+        .ok()
+        .and_then(|s| s.i64().ok().and_then(|ca| ca.get(0)))
+        .and_then(chrono::DateTime::<chrono::Utc>::from_timestamp_millis);
+
+    from_column.unwrap_or_else(|| {
+        std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map(chrono::DateTime::<chrono::Utc>::from)
+            .unwrap_or_else(|_| chrono::Utc::now())
+    })
+}
+
+fn load_frames(dir: &Path, symbol: &str) -> Result<Vec<ReplayFrame>> {
+    let mut paths: Vec<(SystemTime, PathBuf)> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("parquet"))
+        .map(|p| {
+            let modified = std::fs::metadata(&p)
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            (modified, p)
+        })
+        .collect();
+    paths.sort();
+
+    let mut frames = Vec::with_capacity(paths.len());
+    for (_, path) in paths {
+        let path_str = path.to_string_lossy().to_string();
+        let df = match polars_utils::load_dataframe_from_parquet(&path_str) {
+            Ok(df) => df,
+            Err(e) => {
+                warn!("Skipping {}: {}", path_str, e);
+                continue;
+            }
+        };
+        match polars_utils::dataframe_to_volatility_surface(&df, symbol) {
+            Ok(mut surface) => {
+                surface.timestamp = surface_timestamp(&df, &path);
+                frames.push(ReplayFrame { path, surface });
+            }
+            Err(e) => warn!("Skipping {}: {}", path_str, e),
+        }
+    }
+
+    frames.sort_by(|a, b| {
+        a.surface
+            .timestamp
+            .cmp(&b.surface.timestamp)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    Ok(frames)
+}
+
+struct ReplayApp {
+    frames: Vec<ReplayFrame>,
+    current: usize,
+    speed: f64,
+    paused: bool,
+    last_advance: Instant,
+    texture: Option<egui::TextureHandle>,
+    texture_frame: Option<usize>,
+    status: String,
+}
+
+impl ReplayApp {
+    fn frame_interval(&self) -> Duration {
+        BASE_FRAME_INTERVAL.div_f64(self.speed.max(0.01))
+    }
+
+    fn step(&mut self, forward: bool) {
+        if self.frames.is_empty() {
+            return;
+        }
+        self.current = if forward {
+            (self.current + 1).min(self.frames.len() - 1)
+        } else {
+            self.current.saturating_sub(1)
+        };
+        self.last_advance = Instant::now();
+    }
+
+    fn refresh_texture(&mut self, ctx: &egui::Context) {
+        if self.texture_frame == Some(self.current) {
+            return;
+        }
+        let Some(frame) = self.frames.get(self.current) else {
+            return;
+        };
+        match utils::plot_volatility_surface_in_memory(&frame.surface) {
+            Ok(image) => {
+                self.texture = Some(ctx.load_texture(
+                    "replay_surface",
+                    image,
+                    egui::TextureOptions::default(),
+                ));
+                self.status.clear();
+            }
+            Err(e) => {
+                self.status = format!("Failed to render {}: {}", frame.path.display(), e);
+                warn!("{}", self.status);
+            }
+        }
+        self.texture_frame = Some(self.current);
+    }
+}
+
+impl eframe::App for ReplayApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let (toggle_pause, step_forward, step_back) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::Space),
+                i.key_pressed(egui::Key::ArrowRight),
+                i.key_pressed(egui::Key::ArrowLeft),
+            )
+        });
+
+        if toggle_pause {
+            self.paused = !self.paused;
+            self.last_advance = Instant::now();
+        }
+        if step_forward {
+            self.paused = true;
+            self.step(true);
+        }
+        if step_back {
+            self.paused = true;
+            self.step(false);
+        }
+
+        if !self.paused && self.last_advance.elapsed() >= self.frame_interval() {
+            if self.current + 1 < self.frames.len() {
+                self.step(true);
+            } else {
+                self.paused = true;
+            }
+        }
+
+        self.refresh_texture(ctx);
+
+        egui::TopBottomPanel::top("replay_controls").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let label = if self.paused { "Play" } else { "Pause" };
+                if ui.button(label).clicked() {
+                    self.paused = !self.paused;
+                    self.last_advance = Instant::now();
+                }
+                if ui.button("Step").clicked() {
+                    self.paused = true;
+                    self.step(true);
+                }
+                ui.add(egui::Slider::new(&mut self.speed, 0.25..=16.0).text("speed"));
+
+                if let Some(frame) = self.frames.get(self.current) {
+                    ui.label(format!(
+                        "Frame {}/{} | {}",
+                        self.current + 1,
+                        self.frames.len(),
+                        frame.surface.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+                    ));
+                }
+            });
+            ui.label("Space: pause/resume | Right: step forward | Left: step back");
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if self.frames.is_empty() {
+                ui.label("No cached surfaces found");
+                return;
+            }
+            if !self.status.is_empty() {
+                ui.colored_label(egui::Color32::RED, &self.status);
+            }
+            if let Some(texture) = &self.texture {
+                let available = ui.available_size();
+                let size = texture.size_vec2();
+                let scale = (available.x / size.x).min(available.y / size.y).min(1.0);
+                ui.image((texture.id(), size * scale));
+            }
+        });
+
+        if !self.paused {
+            ctx.request_repaint_after(self.frame_interval());
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        return Err(OptionsError::ConfigError(
+            "Usage: replay_surfaces <parquet_dir> [speed] [symbol]".to_string(),
+        ));
+    }
+
+    let dir = PathBuf::from(&args[1]);
+    let speed = args
+        .get(2)
+        .map(|s| {
+            s.parse::<f64>()
+                .map_err(|e| OptionsError::ParseError(format!("Invalid speed '{}': {}", s, e)))
+        })
+        .transpose()?
+        .unwrap_or(1.0);
+    let symbol = args.get(3).cloned().unwrap_or_else(|| "REPLAY".to_string());
+
+    let frames = load_frames(&dir, &symbol)?;
+    info!(
+        "Loaded {} cached surfaces from {}",
+        frames.len(),
+        dir.display()
+    );
+
+    let app = ReplayApp {
+        frames,
+        current: 0,
+        speed,
+        paused: false,
+        last_advance: Instant::now(),
+        texture: None,
+        texture_frame: None,
+        status: String::new(),
+    };
+
+    let native_options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default().with_inner_size([1250.0, 1000.0]),
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "Volatility Surface Replay",
+        native_options,
+        Box::new(|_cc| Ok(Box::new(app))),
+    )
+    .map_err(|e| OptionsError::Other(format!("Failed to start GUI: {}", e)))?;
+
+    Ok(())
+}
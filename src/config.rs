@@ -2,6 +2,7 @@ use crate::error::{OptionsError, Result};
 use dotenv::dotenv;
 use serde::Deserialize;
 use std::env;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct AlpacaConfig {
@@ -10,6 +11,73 @@ pub struct AlpacaConfig {
     pub base_url: String,
     pub data_url: String,
     pub paper_url: String,
+    #[serde(default = "default_user_agent")]
+    pub user_agent: String,
+    #[serde(default)]
+    pub send_request_id: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpOptions {
+    pub proxy: Option<String>,
+    pub danger_accept_invalid_certs: bool,
+    pub timeout: Duration,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            danger_accept_invalid_certs: false,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+pub fn default_user_agent() -> String {
+    format!("volsurf-rs/{}", env!("CARGO_PKG_VERSION"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum ContractLimitMode {
+    Sample,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum DataProviderKind {
+    #[default]
+    Alpaca,
+    Polygon,
+    Tradier,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PolygonConfig {
+    pub api_key: String,
+    #[serde(default = "default_polygon_url")]
+    pub base_url: String,
+}
+
+pub fn default_polygon_url() -> String {
+    "https://api.polygon.io".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TradierConfig {
+    pub token: String,
+    #[serde(default)]
+    pub sandbox: bool,
+}
+
+impl TradierConfig {
+    pub fn base_url(&self) -> &'static str {
+        if self.sandbox {
+            "https://sandbox.tradier.com"
+        } else {
+            "https://api.tradier.com"
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -17,6 +85,20 @@ pub struct Config {
     pub alpaca: AlpacaConfig,
     pub log_level: String,
     pub paper_trading: bool,
+    #[serde(default)]
+    pub max_contracts: Option<usize>,
+    #[serde(default = "default_contract_limit_mode")]
+    pub contract_limit_mode: ContractLimitMode,
+    #[serde(default)]
+    pub data_provider: DataProviderKind,
+    #[serde(default)]
+    pub polygon: Option<PolygonConfig>,
+    #[serde(default)]
+    pub tradier: Option<TradierConfig>,
+}
+
+pub fn default_contract_limit_mode() -> ContractLimitMode {
+    ContractLimitMode::Sample
 }
 
 impl Config {
@@ -33,21 +115,91 @@ impl Config {
         let default_data_url = "https://data.alpaca.markets".to_string();
         let default_paper_url = "https://paper-api.alpaca.markets".to_string();
 
-        let api_key = env::var("ALPACA_API_KEY").map_err(|_| {
-            OptionsError::ConfigError("ALPACA_API_KEY environment variable not set".to_string())
-        })?;
+        let data_provider = match env::var("DATA_PROVIDER") {
+            Ok(v) => match v.to_lowercase().as_str() {
+                "alpaca" => DataProviderKind::Alpaca,
+                "polygon" => DataProviderKind::Polygon,
+                "tradier" => DataProviderKind::Tradier,
+                _ => {
+                    return Err(OptionsError::ConfigError(format!(
+                        "Invalid DATA_PROVIDER '{}': expected 'alpaca', 'polygon' or 'tradier'",
+                        v
+                    )))
+                }
+            },
+            Err(_) => DataProviderKind::default(),
+        };
 
-        let api_secret = env::var("ALPACA_API_SECRET").map_err(|_| {
-            OptionsError::ConfigError("ALPACA_API_SECRET environment variable not set".to_string())
-        })?;
+        // Alpaca credentials are only mandatory when Alpaca is the market data source.
+        let alpaca_var = |name: &str| match env::var(name) {
+            Ok(v) => Ok(v),
+            Err(_) if data_provider != DataProviderKind::Alpaca => Ok(String::new()),
+            Err(_) => Err(OptionsError::ConfigError(format!(
+                "{} environment variable not set",
+                name
+            ))),
+        };
+        let api_key = alpaca_var("ALPACA_API_KEY")?;
+        let api_secret = alpaca_var("ALPACA_API_SECRET")?;
+
+        let polygon = match env::var("POLYGON_API_KEY") {
+            Ok(api_key) => Some(PolygonConfig {
+                api_key,
+                base_url: env::var("POLYGON_BASE_URL").unwrap_or_else(|_| default_polygon_url()),
+            }),
+            Err(_) if data_provider == DataProviderKind::Polygon => {
+                return Err(OptionsError::ConfigError(
+                    "POLYGON_API_KEY environment variable not set".to_string(),
+                ))
+            }
+            Err(_) => None,
+        };
+
+        let tradier = match env::var("TRADIER_TOKEN") {
+            Ok(token) => Some(TradierConfig {
+                token,
+                sandbox: env::var("TRADIER_SANDBOX")
+                    .map(|v| v.to_lowercase() == "true")
+                    .unwrap_or(false),
+            }),
+            Err(_) if data_provider == DataProviderKind::Tradier => {
+                return Err(OptionsError::ConfigError(
+                    "TRADIER_TOKEN environment variable not set".to_string(),
+                ))
+            }
+            Err(_) => None,
+        };
 
         let base_url = env::var("ALPACA_BASE_URL").unwrap_or(default_base_url);
         let data_url = env::var("ALPACA_DATA_URL").unwrap_or(default_data_url);
         let paper_url = env::var("ALPACA_PAPER_URL").unwrap_or(default_paper_url.to_string());
+        let user_agent = env::var("ALPACA_USER_AGENT").unwrap_or_else(|_| default_user_agent());
+        let send_request_id = env::var("ALPACA_SEND_REQUEST_ID")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
         let log_level = env::var("LOG_LEVEL").unwrap_or(default_log_level);
         let paper_trading = env::var("PAPER_TRADING")
             .map(|v| v.to_lowercase() == "true")
             .unwrap_or(default_paper_trading);
+        let max_contracts = match env::var("MAX_CONTRACTS") {
+            Ok(v) => Some(v.parse::<usize>().map_err(|e| {
+                OptionsError::ConfigError(format!("Invalid MAX_CONTRACTS '{}': {}", v, e))
+            })?),
+            Err(_) => None,
+        };
+        let contract_limit_mode = match env::var("MAX_CONTRACTS_MODE") {
+            Ok(v) => match v.to_lowercase().as_str() {
+                "sample" => ContractLimitMode::Sample,
+                "error" => ContractLimitMode::Error,
+                _ => {
+                    return Err(OptionsError::ConfigError(format!(
+                        "Invalid MAX_CONTRACTS_MODE '{}': expected 'sample' or 'error'",
+                        v
+                    )))
+                }
+            },
+            Err(_) => default_contract_limit_mode(),
+        };
 
         Ok(Config {
             alpaca: AlpacaConfig {
@@ -56,9 +208,16 @@ impl Config {
                 base_url,
                 data_url,
                 paper_url,
+                user_agent,
+                send_request_id,
             },
             log_level,
             paper_trading,
+            max_contracts,
+            contract_limit_mode,
+            data_provider,
+            polygon,
+            tradier,
         })
     }
 
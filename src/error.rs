@@ -4,6 +4,15 @@ use plotters_bitmap::BitMapBackendError;
 use polars::prelude::PolarsError;
 use thiserror::Error;
 
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum IvError {
+    #[error("bid {bid} and ask {ask} are both below intrinsic value {intrinsic}")]
+    BelowIntrinsic { bid: f64, ask: f64, intrinsic: f64 },
+
+    #[error("{0}")]
+    Solver(String),
+}
+
 #[derive(Error, Debug)]
 pub enum OptionsError {
     #[error("Parse error: {0}")]
@@ -12,6 +21,9 @@ pub enum OptionsError {
     #[error("Volatility calculation error: {0}")]
     VolatilityError(String),
 
+    #[error("Implied volatility error: {0}")]
+    IvError(#[from] IvError),
+
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
@@ -33,6 +45,23 @@ pub enum OptionsError {
     #[error("Polars error: {0}")]
     PolarsError(#[from] PolarsError),
 
+    #[error("Rate limited (retry after {retry_after:?})")]
+    RateLimited {
+        retry_after: Option<std::time::Duration>,
+    },
+
+    #[error("Unauthorized request to {url}: check API credentials")]
+    Unauthorized { url: String },
+
+    #[error("Forbidden request to {url}: {body}")]
+    Forbidden { url: String, body: String },
+
+    #[error("Not found: {url}")]
+    NotFound { url: String },
+
+    #[error("Network error calling {url}: {message}")]
+    Network { url: String, message: String },
+
     #[error("{0}")]
     Other(String),
 }
@@ -0,0 +1,119 @@
+use crate::error::{OptionsError, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ForwardCurve {
+    Flat {
+        spot: f64,
+        rate: f64,
+        dividend_yield: f64,
+    },
+    Points(Vec<(f64, f64)>),
+}
+
+impl ForwardCurve {
+    pub fn flat(spot: f64, rate: f64, dividend_yield: f64) -> Self {
+        ForwardCurve::Flat {
+            spot,
+            rate,
+            dividend_yield,
+        }
+    }
+
+    pub fn from_points(points: &[(f64, f64)]) -> Result<Self> {
+        let mut points: Vec<(f64, f64)> = points
+            .iter()
+            .copied()
+            .filter(|(t, f)| t.is_finite() && f.is_finite())
+            .collect();
+
+        if points.is_empty() {
+            return Err(OptionsError::VolatilityError(
+                "Forward curve requires at least one point".to_string(),
+            ));
+        }
+        if let Some(&(t, f)) = points.iter().find(|(t, f)| *t < 0.0 || *f <= 0.0) {
+            return Err(OptionsError::VolatilityError(format!(
+                "Invalid forward curve point ({}, {})",
+                t, f
+            )));
+        }
+
+        points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        points.dedup_by(|a, b| a.0 == b.0);
+
+        Ok(ForwardCurve::Points(points))
+    }
+
+    pub fn forward(&self, t: f64) -> f64 {
+        let t = t.max(0.0);
+        match self {
+            ForwardCurve::Flat {
+                spot,
+                rate,
+                dividend_yield,
+            } => spot * ((rate - dividend_yield) * t).exp(),
+            ForwardCurve::Points(points) => {
+                if points.len() == 1 {
+                    return points[0].1;
+                }
+
+                let upper = points
+                    .iter()
+                    .position(|&(pt, _)| pt >= t)
+                    .unwrap_or(points.len() - 1)
+                    .max(1);
+                let (t0, f0) = points[upper - 1];
+                let (t1, f1) = points[upper];
+                if t1 <= t0 {
+                    return f0;
+                }
+
+                let w = (t - t0) / (t1 - t0);
+                (f0.ln() + w * (f1.ln() - f0.ln())).exp()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_curve_grows_at_the_carry_rate() {
+        let (spot, rate): (f64, f64) = (500.0, 0.04);
+        let curve = ForwardCurve::flat(spot, rate, 0.0);
+        for t in [0.0, 0.25, 1.0, 3.0] {
+            let expected = spot * (rate * t).exp();
+            assert!((curve.forward(t) - expected).abs() < 1e-9);
+        }
+
+        let with_yield = ForwardCurve::flat(spot, rate, 0.01);
+        assert!((with_yield.forward(1.0) - spot * 0.03f64.exp()).abs() < 1e-9);
+        assert_eq!(curve.forward(-1.0), spot);
+    }
+
+    #[test]
+    fn points_interpolate_log_linearly() {
+        let curve = ForwardCurve::from_points(&[(1.0, 110.0), (0.0, 100.0), (2.0, 121.0)]).unwrap();
+
+        assert!((curve.forward(0.0) - 100.0).abs() < 1e-12);
+        assert!((curve.forward(1.0) - 110.0).abs() < 1e-12);
+        assert!((curve.forward(0.5) - (100.0f64 * 110.0).sqrt()).abs() < 1e-9);
+        assert!((curve.forward(1.5) - (110.0f64 * 121.0).sqrt()).abs() < 1e-9);
+        // Past the last point the final segment's growth rate carries on.
+        assert!((curve.forward(3.0) - 133.1).abs() < 1e-9);
+
+        let single = ForwardCurve::from_points(&[(0.5, 105.0)]).unwrap();
+        assert_eq!(single.forward(2.0), 105.0);
+    }
+
+    #[test]
+    fn from_points_rejects_empty_and_invalid_curves() {
+        assert!(ForwardCurve::from_points(&[]).is_err());
+        assert!(ForwardCurve::from_points(&[(f64::NAN, 100.0)]).is_err());
+        assert!(ForwardCurve::from_points(&[(0.5, 0.0)]).is_err());
+        assert!(ForwardCurve::from_points(&[(-0.5, 100.0)]).is_err());
+    }
+}
@@ -1,7 +1,16 @@
+pub mod forward;
 mod option;
+pub mod smoothing;
+pub mod surface_diff;
+mod surface_io;
+pub mod surface_store;
 pub mod surface_update;
 pub mod volatility;
 
+pub use forward::ForwardCurve;
 pub use option::*;
+pub use smoothing::MidPriceSmoother;
+pub use surface_diff::{CellChange, SurfaceDiff};
+pub use surface_store::{DenseStore, SparseStore, SurfaceStore};
 pub use surface_update::SurfaceUpdate;
 pub use volatility::*;
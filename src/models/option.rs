@@ -1,7 +1,12 @@
-use chrono::{DateTime, Utc};
+use crate::error::{OptionsError, Result};
+use crate::utils::{business_days_between, market_close_utc};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use tracing::{trace, warn};
 
+const OCC_SUFFIX_LEN: usize = 15;
+const OCC_MAX_STRIKE_FIELD: u64 = 99_999_999;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OptionType {
     Call,
@@ -17,6 +22,40 @@ impl std::fmt::Display for OptionType {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExpirationKind {
+    Weekly,
+    Monthly,
+    Quarterly,
+    Other,
+}
+
+impl ExpirationKind {
+    pub fn from_date(date: NaiveDate) -> Self {
+        let third_friday = NaiveDate::from_weekday_of_month_opt(
+            date.year(),
+            date.month(),
+            chrono::Weekday::Fri,
+            3,
+        );
+        if third_friday == Some(date) {
+            if matches!(date.month(), 3 | 6 | 9 | 12) {
+                ExpirationKind::Quarterly
+            } else {
+                ExpirationKind::Monthly
+            }
+        } else if date.weekday() == chrono::Weekday::Fri {
+            ExpirationKind::Weekly
+        } else {
+            ExpirationKind::Other
+        }
+    }
+
+    pub fn is_monthly(&self) -> bool {
+        matches!(self, ExpirationKind::Monthly | ExpirationKind::Quarterly)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptionContract {
     pub symbol: String,
@@ -27,21 +66,26 @@ pub struct OptionContract {
 }
 
 impl OptionContract {
+    /// Fails when the strike does not fit the 8-digit OCC strike field.
+    ///
+    /// The OCC symbol only encodes the expiration date, and `from_occ_symbol`
+    /// restores it as `market_close_utc(date)`, so a contract round-trips through
+    /// its symbol exactly only when `expiration` is already the market close.
     pub fn new(
         symbol: String,
         option_type: OptionType,
         strike: f64,
         expiration: DateTime<Utc>,
-    ) -> Self {
-        let option_symbol = Self::generate_occ_symbol(&symbol, option_type, strike, expiration);
+    ) -> Result<Self> {
+        let option_symbol = Self::generate_occ_symbol(&symbol, option_type, strike, expiration)?;
 
-        Self {
+        Ok(Self {
             symbol,
             option_type,
             strike,
             expiration,
             option_symbol,
-        }
+        })
     }
 
     fn generate_occ_symbol(
@@ -49,70 +93,50 @@ impl OptionContract {
         option_type: OptionType,
         strike: f64,
         expiration: DateTime<Utc>,
-    ) -> String {
+    ) -> Result<String> {
+        let strike_field = (strike * 1000.0).round();
+        if !(0.0..=OCC_MAX_STRIKE_FIELD as f64).contains(&strike_field) {
+            return Err(OptionsError::ParseError(format!(
+                "Strike {} does not fit the 8-digit OCC strike field",
+                strike
+            )));
+        }
         let type_char = match option_type {
             OptionType::Call => 'C',
             OptionType::Put => 'P',
         };
-        let strike_str = format!("{:08}", (strike * 1000.0) as u32);
         let date_str = expiration.format("%y%m%d").to_string();
-        format!("{}{}{}{}", symbol, date_str, type_char, strike_str)
+        Ok(format!(
+            "{}{}{}{:08}",
+            symbol, date_str, type_char, strike_field as u64
+        ))
     }
 
     pub fn from_occ_symbol(occ_symbol: &str) -> Option<Self> {
         trace!("Parsing OCC symbol: {}", occ_symbol);
-        let mut type_positions = Vec::new();
-        for (i, c) in occ_symbol.char_indices() {
-            if c == 'C' || c == 'P' {
-                type_positions.push(i);
-            }
-        }
-
-        if type_positions.is_empty() {
-            warn!("Failed to find 'C' or 'P' in OCC symbol: {}", occ_symbol);
-            return None;
-        }
-
-        let mut valid_type_pos = None;
-        for &pos in &type_positions {
-            if pos >= 6 {
-                let date_part = &occ_symbol[(pos - 6)..pos];
-                if date_part.chars().all(|c| c.is_digit(10)) {
-                    valid_type_pos = Some(pos);
-                    break;
-                }
-            }
-        }
-
-        let type_pos = match valid_type_pos {
-            Some(pos) => pos,
-            None => {
-                let pos = *type_positions.last().unwrap();
-                warn!("No valid option type position found with date check, using last position {} as fallback for: {}", pos, occ_symbol);
-                pos
-            }
-        };
 
-        if type_pos < 6 {
-            warn!(
-                "OCC symbol too short for date part: {} (type_pos={})",
-                occ_symbol, type_pos
-            );
+        // OCC layout: root (up to 6 chars, space padded), YYMMDD, C/P, strike * 1000
+        // as 8 digits. Anchoring from the right keeps roots containing 'C'/'P' intact.
+        if !occ_symbol.is_ascii() || occ_symbol.len() < OCC_SUFFIX_LEN + 1 {
+            warn!("OCC symbol too short or not ASCII: {}", occ_symbol);
             return None;
         }
 
-        if type_pos + 1 >= occ_symbol.len() {
-            warn!("OCC symbol too short for strike price: {}", occ_symbol);
+        let split = occ_symbol.len() - OCC_SUFFIX_LEN;
+        let (root, suffix) = occ_symbol.split_at(split);
+        let symbol = root.trim_end().to_string();
+        if symbol.is_empty() {
+            warn!("Missing root symbol in OCC symbol: {}", occ_symbol);
             return None;
         }
 
-        let symbol = occ_symbol[0..(type_pos - 6)].to_string();
-        let date_str = &occ_symbol[(type_pos - 6)..type_pos];
-
+        let date_str = &suffix[0..6];
+        let strike_str = &suffix[7..];
         trace!("Extracted symbol: {}, date_str: {}", symbol, date_str);
-        let option_type = match occ_symbol.chars().nth(type_pos) {
-            Some('C') => OptionType::Call,
-            Some('P') => OptionType::Put,
+
+        let option_type = match suffix.as_bytes()[6] {
+            b'C' => OptionType::Call,
+            b'P' => OptionType::Put,
             _ => {
                 warn!(
                     "Invalid option type character in OCC symbol: {}",
@@ -121,12 +145,17 @@ impl OptionContract {
                 return None;
             }
         };
-        let strike_str = &occ_symbol[(type_pos + 1)..];
-        if strike_str.is_empty() {
-            warn!("Empty strike string in OCC symbol: {}", occ_symbol);
+
+        if !date_str.bytes().all(|b| b.is_ascii_digit())
+            || !strike_str.bytes().all(|b| b.is_ascii_digit())
+        {
+            warn!(
+                "Expected 6-digit date and 8-digit strike in OCC symbol: {}",
+                occ_symbol
+            );
             return None;
         }
-        let strike = match strike_str.parse::<u32>() {
+        let strike = match strike_str.parse::<u64>() {
             Ok(s) => s as f64 / 1000.0,
             Err(e) => {
                 warn!(
@@ -136,13 +165,6 @@ impl OptionContract {
                 return None;
             }
         };
-        if date_str.len() != 6 {
-            warn!(
-                "Date string '{}' is not exactly 6 characters long in OCC symbol: {}",
-                date_str, occ_symbol
-            );
-            return None;
-        }
 
         let year_str = &date_str[0..2];
         let month_str = &date_str[2..4];
@@ -167,7 +189,7 @@ impl OptionContract {
         };
 
         let month = match month_str.parse::<u32>() {
-            Ok(m) if m >= 1 && m <= 12 => m,
+            Ok(m) if (1..=12).contains(&m) => m,
             Ok(m) => {
                 warn!(
                     "Invalid month value {} (must be 1-12) in OCC symbol: {}",
@@ -185,7 +207,7 @@ impl OptionContract {
         };
 
         let day = match day_str.parse::<u32>() {
-            Ok(d) if d >= 1 && d <= 31 => d,
+            Ok(d) if (1..=31).contains(&d) => d,
             Ok(d) => {
                 warn!(
                     "Invalid day value {} (must be 1-31) in OCC symbol: {}",
@@ -213,27 +235,7 @@ impl OptionContract {
             }
         };
 
-        let naive_datetime = match naive_date.and_hms_opt(16, 0, 0) {
-            Some(dt) => dt,
-            None => {
-                warn!(
-                    "Failed to create datetime for {}-{}-{} 16:00:00 in OCC symbol: {}",
-                    year, month, day, occ_symbol
-                );
-                return None;
-            }
-        };
-
-        let expiration = match naive_datetime.and_local_timezone(chrono::Utc).single() {
-            Some(e) => e,
-            None => {
-                warn!(
-                    "Failed to convert to UTC for {}-{}-{} 16:00:00 in OCC symbol: {}",
-                    year, month, day, occ_symbol
-                );
-                return None;
-            }
-        };
+        let expiration = market_close_utc(naive_date);
 
         trace!(
             "Successfully parsed OCC symbol: {} -> symbol={}, type={:?}, strike={}, expiration={}",
@@ -263,12 +265,51 @@ impl OptionContract {
         duration.num_seconds() as f64 / (365.0 * 24.0 * 60.0 * 60.0)
     }
 
+    pub fn calendar_days_to_expiration(&self) -> i64 {
+        let today = Utc::now().date_naive();
+        (self.expiration.date_naive() - today).num_days().max(0)
+    }
+
+    pub fn business_days_to_expiration(&self) -> i64 {
+        self.business_days_to_expiration_with_holidays(&[])
+    }
+
+    pub fn business_days_to_expiration_with_holidays(&self, holidays: &[NaiveDate]) -> i64 {
+        business_days_between(
+            Utc::now().date_naive(),
+            self.expiration.date_naive(),
+            holidays,
+        )
+    }
+
+    pub fn expiration_kind(&self) -> ExpirationKind {
+        ExpirationKind::from_date(self.expiration.date_naive())
+    }
+
     pub fn is_call(&self) -> bool {
         self.option_type == OptionType::Call
     }
     pub fn is_put(&self) -> bool {
         self.option_type == OptionType::Put
     }
+
+    pub fn moneyness(&self, underlying: f64) -> f64 {
+        match self.option_type {
+            OptionType::Call => underlying / self.strike,
+            OptionType::Put => self.strike / underlying,
+        }
+    }
+
+    pub fn is_itm(&self, underlying: f64) -> bool {
+        self.intrinsic_value(underlying) > 0.0
+    }
+
+    pub fn intrinsic_value(&self, underlying: f64) -> f64 {
+        match self.option_type {
+            OptionType::Call => (underlying - self.strike).max(0.0),
+            OptionType::Put => (self.strike - underlying).max(0.0),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -308,3 +349,186 @@ impl OptionQuote {
         (self.bid + self.ask) / 2.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(occ: &str) -> OptionContract {
+        OptionContract::from_occ_symbol(occ).unwrap()
+    }
+
+    #[test]
+    fn parses_spxw_weeklies() {
+        let c = parse("SPXW240614C05300000");
+        assert_eq!(c.symbol, "SPXW");
+        assert_eq!(c.option_type, OptionType::Call);
+        assert_eq!(c.strike, 5300.0);
+        assert_eq!(
+            c.expiration.date_naive(),
+            NaiveDate::from_ymd_opt(2024, 6, 14).unwrap()
+        );
+    }
+
+    #[test]
+    fn parses_roots_with_dots_and_type_letters() {
+        let c = parse("BRK.B240621P00410000");
+        assert_eq!(c.symbol, "BRK.B");
+        assert_eq!(c.option_type, OptionType::Put);
+        assert_eq!(c.strike, 410.0);
+
+        let c = parse("PCG240621C00017500");
+        assert_eq!(c.symbol, "PCG");
+        assert_eq!(c.option_type, OptionType::Call);
+        assert_eq!(c.strike, 17.5);
+    }
+
+    #[test]
+    fn parses_space_padded_roots() {
+        let c = parse("SPY   240621C00500000");
+        assert_eq!(c.symbol, "SPY");
+        assert_eq!(c.option_symbol, "SPY   240621C00500000");
+    }
+
+    #[test]
+    fn keeps_fractional_strikes() {
+        assert_eq!(parse("XYZ240621P00000500").strike, 0.5);
+        assert_eq!(parse("XYZ240621C00002125").strike, 2.125);
+    }
+
+    #[test]
+    fn generated_symbols_round_trip() {
+        let roots = ["SPY", "SPXW", "BRK.B", "PCG"];
+        let strikes = [0.5, 2.125, 17.5, 410.0, 4295.0, 5300.0, 99_999.999];
+        let dates = [(2024, 1, 19), (2024, 6, 21), (2025, 12, 31), (2030, 2, 28)];
+
+        for root in roots {
+            for &strike in &strikes {
+                for &(y, m, d) in &dates {
+                    for option_type in [OptionType::Call, OptionType::Put] {
+                        let expiration =
+                            market_close_utc(NaiveDate::from_ymd_opt(y, m, d).unwrap());
+                        let contract =
+                            OptionContract::new(root.to_string(), option_type, strike, expiration)
+                                .unwrap();
+                        let parsed = parse(&contract.option_symbol);
+                        assert_eq!(parsed.symbol, contract.symbol);
+                        assert_eq!(parsed.option_type, option_type);
+                        assert_eq!(parsed.strike, strike);
+                        assert_eq!(parsed.expiration, expiration);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_strikes_outside_the_occ_field() {
+        let expiration = market_close_utc(NaiveDate::from_ymd_opt(2024, 6, 21).unwrap());
+        for strike in [100_000.0, -1.0, f64::NAN] {
+            assert!(
+                OptionContract::new("SPX".to_string(), OptionType::Call, strike, expiration)
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_symbols() {
+        for occ in [
+            "",
+            "240621C00500000",
+            "SPY240621X00500000",
+            "SPY241321C00500000",
+            "SPY240632C00500000",
+            "SPY240621C0050000A",
+            "SPY240231C00500000",
+        ] {
+            assert!(OptionContract::from_occ_symbol(occ).is_none(), "{}", occ);
+        }
+    }
+
+    fn contract(option_type: OptionType, strike: f64) -> OptionContract {
+        let expiration = market_close_utc(NaiveDate::from_ymd_opt(2024, 6, 21).unwrap());
+        OptionContract::new("SPY".to_string(), option_type, strike, expiration).unwrap()
+    }
+
+    #[test]
+    fn moneyness_and_intrinsic_value() {
+        let call = contract(OptionType::Call, 500.0);
+        let put = contract(OptionType::Put, 500.0);
+
+        assert_eq!(call.moneyness(550.0), 1.1);
+        assert_eq!(put.moneyness(400.0), 1.25);
+
+        assert!(call.is_itm(510.0));
+        assert_eq!(call.intrinsic_value(510.0), 10.0);
+        assert!(!call.is_itm(490.0));
+        assert_eq!(call.intrinsic_value(490.0), 0.0);
+
+        assert!(put.is_itm(490.0));
+        assert_eq!(put.intrinsic_value(490.0), 10.0);
+        assert!(!put.is_itm(510.0));
+        assert_eq!(put.intrinsic_value(510.0), 0.0);
+
+        for c in [&call, &put] {
+            assert_eq!(c.moneyness(500.0), 1.0);
+            assert!(!c.is_itm(500.0));
+            assert_eq!(c.intrinsic_value(500.0), 0.0);
+        }
+    }
+
+    #[test]
+    fn business_days_skip_weekends_and_holidays() {
+        let friday = NaiveDate::from_ymd_opt(2024, 6, 14).unwrap();
+        let monday_after_next = NaiveDate::from_ymd_opt(2024, 6, 24).unwrap();
+        let juneteenth = NaiveDate::from_ymd_opt(2024, 6, 19).unwrap();
+
+        assert_eq!(business_days_between(friday, monday_after_next, &[]), 6);
+        assert_eq!(
+            business_days_between(friday, monday_after_next, &[juneteenth]),
+            5
+        );
+        assert_eq!(business_days_between(monday_after_next, friday, &[]), 0);
+    }
+
+    #[test]
+    fn days_to_expiration_counts_from_today_and_floors_at_zero() {
+        let expired = contract(OptionType::Call, 500.0);
+        assert_eq!(expired.calendar_days_to_expiration(), 0);
+        assert_eq!(expired.business_days_to_expiration(), 0);
+
+        let date = Utc::now().date_naive() + chrono::Duration::days(14);
+        let live = OptionContract::new(
+            "SPY".to_string(),
+            OptionType::Put,
+            500.0,
+            market_close_utc(date),
+        )
+        .unwrap();
+        assert_eq!(live.calendar_days_to_expiration(), 14);
+        assert_eq!(live.business_days_to_expiration(), 10);
+    }
+
+    #[test]
+    fn classifies_2024_expirations() {
+        let kind = |y, m, d| ExpirationKind::from_date(NaiveDate::from_ymd_opt(y, m, d).unwrap());
+
+        assert_eq!(kind(2024, 1, 19), ExpirationKind::Monthly);
+        assert_eq!(kind(2024, 5, 17), ExpirationKind::Monthly);
+        assert_eq!(kind(2024, 3, 15), ExpirationKind::Quarterly);
+        assert_eq!(kind(2024, 6, 21), ExpirationKind::Quarterly);
+        assert_eq!(kind(2024, 9, 20), ExpirationKind::Quarterly);
+        assert_eq!(kind(2024, 12, 20), ExpirationKind::Quarterly);
+        assert_eq!(kind(2024, 6, 14), ExpirationKind::Weekly);
+        assert_eq!(kind(2024, 6, 28), ExpirationKind::Weekly);
+        assert_eq!(kind(2024, 6, 17), ExpirationKind::Other);
+
+        assert!(kind(2024, 6, 21).is_monthly());
+        assert!(!kind(2024, 6, 14).is_monthly());
+        assert_eq!(
+            contract(OptionType::Call, 500.0).expiration_kind(),
+            ExpirationKind::Quarterly
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use crate::models::option::OptionQuote;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default)]
+pub struct MidPriceSmoother {
+    alpha: Option<f64>,
+    smoothed: HashMap<String, f64>,
+}
+
+impl MidPriceSmoother {
+    pub fn new(alpha: f64) -> Self {
+        Self {
+            alpha: Some(alpha.clamp(f64::EPSILON, 1.0)),
+            smoothed: HashMap::new(),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    pub fn alpha(&self) -> Option<f64> {
+        self.alpha
+    }
+
+    pub fn set_alpha(&mut self, alpha: Option<f64>) {
+        self.alpha = alpha.map(|a| a.clamp(f64::EPSILON, 1.0));
+        if self.alpha.is_none() {
+            self.smoothed.clear();
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.alpha.is_some()
+    }
+
+    pub fn update(&mut self, option_symbol: &str, mid: f64) -> f64 {
+        let Some(alpha) = self.alpha else {
+            return mid;
+        };
+
+        if !mid.is_finite() {
+            return self.smoothed.get(option_symbol).copied().unwrap_or(mid);
+        }
+
+        let value = match self.smoothed.get(option_symbol) {
+            Some(&prev) => alpha * mid + (1.0 - alpha) * prev,
+            None => mid,
+        };
+        self.smoothed.insert(option_symbol.to_string(), value);
+        value
+    }
+
+    pub fn smoothed_mid(&self, option_symbol: &str) -> Option<f64> {
+        self.smoothed.get(option_symbol).copied()
+    }
+
+    pub fn smooth_quote(&mut self, quote: &OptionQuote) -> OptionQuote {
+        let mid = quote.mid_price();
+        let smoothed = self.update(&quote.contract.option_symbol, mid);
+        let shift = smoothed - mid;
+
+        let mut out = quote.clone();
+        out.bid += shift;
+        out.ask += shift;
+        out
+    }
+
+    pub fn reset(&mut self) {
+        self.smoothed.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ImpliedVolatility, OptionContract, OptionType};
+    use crate::utils::price;
+
+    fn quote_at_vol(sigma: f64) -> OptionQuote {
+        let expiration = chrono::Utc::now() + chrono::Duration::days(30);
+        let contract =
+            OptionContract::new("SPY".to_string(), OptionType::Call, 500.0, expiration).unwrap();
+        let mid = price(
+            500.0,
+            500.0,
+            contract.time_to_expiration(),
+            0.05,
+            sigma,
+            true,
+        );
+        OptionQuote::new(contract, mid - 0.05, mid + 0.05, mid, 0, 0, 500.0)
+    }
+
+    #[test]
+    fn smoothed_iv_stays_between_alternating_extremes() {
+        let (high, low) = (quote_at_vol(0.30), quote_at_vol(0.20));
+        let mut smoother = MidPriceSmoother::new(0.2);
+
+        for i in 0..20 {
+            let raw = if i % 2 == 0 { &high } else { &low };
+            let smoothed = smoother.smooth_quote(raw);
+            if i == 0 {
+                continue;
+            }
+            let iv = ImpliedVolatility::from_quote(&smoothed, 0.05, 0.0)
+                .unwrap()
+                .value;
+            assert!(iv > 0.2 + 1e-3 && iv < 0.3 - 1e-3, "step {}: {}", i, iv);
+            assert!((smoothed.ask - smoothed.bid - 0.1).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn disabled_smoother_passes_quotes_through() {
+        let mut smoother = MidPriceSmoother::disabled();
+        assert!(!smoother.is_enabled());
+        assert_eq!(smoother.update("SPY", 3.0), 3.0);
+        assert_eq!(smoother.update("SPY", 1.0), 1.0);
+
+        let mut smoother = MidPriceSmoother::new(0.5);
+        smoother.update("SPY", 3.0);
+        assert_eq!(smoother.update("SPY", 1.0), 2.0);
+        smoother.set_alpha(None);
+        assert_eq!(smoother.smoothed_mid("SPY"), None);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::error::{OptionsError, Result};
+use crate::models::volatility::VolatilitySurface;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+const CHANGE_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CellChange {
+    pub row: usize,
+    pub col: usize,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SurfaceDiff {
+    pub base_version: u64,
+    pub version: u64,
+    pub timestamp: DateTime<Utc>,
+    pub changes: Vec<CellChange>,
+}
+
+impl SurfaceDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+}
+
+fn cell_changed(old: f64, new: f64) -> bool {
+    match (old.is_nan(), new.is_nan()) {
+        (true, true) => false,
+        (false, false) => (old - new).abs() > CHANGE_TOLERANCE,
+        _ => true,
+    }
+}
+
+impl VolatilitySurface {
+    pub fn same_grid(&self, other: &VolatilitySurface) -> bool {
+        self.expirations == other.expirations
+            && self.strikes == other.strikes
+            && self.volatilities.shape() == other.volatilities.shape()
+    }
+
+    pub fn diff_from(&self, previous: &VolatilitySurface) -> Option<SurfaceDiff> {
+        if !self.same_grid(previous) {
+            return None;
+        }
+
+        let mut changes = Vec::new();
+        for ((row, col), &value) in self.volatilities.indexed_iter() {
+            if cell_changed(previous.volatilities[[row, col]], value) {
+                changes.push(CellChange { row, col, value });
+            }
+        }
+
+        let version = if changes.is_empty() {
+            previous.version
+        } else {
+            previous.version + 1
+        };
+
+        Some(SurfaceDiff {
+            base_version: previous.version,
+            version,
+            timestamp: self.timestamp,
+            changes,
+        })
+    }
+
+    pub fn apply_diff(&mut self, diff: &SurfaceDiff) -> Result<bool> {
+        if diff.base_version != self.version {
+            return Err(OptionsError::VolatilityError(format!(
+                "Surface diff expects version {} but surface is at version {}",
+                diff.base_version, self.version
+            )));
+        }
+
+        let (rows, cols) = self.volatilities.dim();
+        if let Some(c) = diff.changes.iter().find(|c| c.row >= rows || c.col >= cols) {
+            return Err(OptionsError::VolatilityError(format!(
+                "Surface diff cell ({}, {}) is outside the {}x{} grid",
+                c.row, c.col, rows, cols
+            )));
+        }
+
+        for c in &diff.changes {
+            self.volatilities[[c.row, c.col]] = c.value;
+        }
+
+        self.version = diff.version;
+        if !diff.is_empty() {
+            self.timestamp = diff.timestamp;
+        }
+
+        Ok(!diff.is_empty())
+    }
+}
@@ -0,0 +1,294 @@
+use crate::error::{OptionsError, Result};
+use crate::models::volatility::VolatilitySurface;
+use crate::utils::polars_utils;
+use chrono::{DateTime, Utc};
+use polars::prelude::{NamedFrom, Series};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+
+const CSV_HEADER: &str = "expiration,strike,volatility";
+const PARQUET_TIMESTAMP_COLUMN: &str = "surface_timestamp";
+const PARQUET_VERSION_COLUMN: &str = "surface_version";
+
+#[derive(Serialize, Deserialize)]
+struct SurfaceSnapshot {
+    version: u64,
+    timestamp: DateTime<Utc>,
+    symbol: String,
+    expirations: Vec<DateTime<Utc>>,
+    strikes: Vec<f64>,
+    volatilities: Vec<Vec<Option<f64>>>,
+}
+
+impl VolatilitySurface {
+    pub fn to_csv<W: Write>(&self, w: W) -> Result<()> {
+        let mut w = std::io::BufWriter::new(w);
+        writeln!(w, "{}", CSV_HEADER)?;
+        for (i, expiration) in self.expirations.iter().enumerate() {
+            for (j, strike) in self.strikes.iter().enumerate() {
+                writeln!(
+                    w,
+                    "{},{},{}",
+                    expiration.to_rfc3339(),
+                    strike,
+                    self.volatilities[[i, j]]
+                )?;
+            }
+        }
+        w.flush()?;
+        Ok(())
+    }
+
+    pub fn from_csv<R: Read>(symbol: &str, r: R) -> Result<VolatilitySurface> {
+        let mut lines = BufReader::new(r).lines();
+        let header = lines.next().transpose()?.unwrap_or_default();
+        if header.trim() != CSV_HEADER {
+            return Err(OptionsError::ParseError(format!(
+                "Surface CSV must start with header '{}'",
+                CSV_HEADER
+            )));
+        }
+
+        let mut rows: Vec<(DateTime<Utc>, f64, f64)> = Vec::new();
+        for (n, line) in lines.enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let line_no = n + 2;
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [expiration, strike, volatility] = fields[..] else {
+                return Err(OptionsError::ParseError(format!(
+                    "Line {}: expected 3 fields, found {}",
+                    line_no,
+                    fields.len()
+                )));
+            };
+
+            let expiration = DateTime::parse_from_rfc3339(expiration)
+                .map_err(|e| {
+                    OptionsError::ParseError(format!(
+                        "Line {}: invalid expiration '{}': {}",
+                        line_no, expiration, e
+                    ))
+                })?
+                .with_timezone(&Utc);
+            let strike = strike.parse::<f64>().map_err(|e| {
+                OptionsError::ParseError(format!(
+                    "Line {}: invalid strike '{}': {}",
+                    line_no, strike, e
+                ))
+            })?;
+            let volatility = if volatility.is_empty() {
+                f64::NAN
+            } else {
+                volatility.parse::<f64>().map_err(|e| {
+                    OptionsError::ParseError(format!(
+                        "Line {}: invalid volatility '{}': {}",
+                        line_no, volatility, e
+                    ))
+                })?
+            };
+            rows.push((expiration, strike, volatility));
+        }
+
+        if rows.is_empty() {
+            return Err(OptionsError::VolatilityError(
+                "Cannot create volatility surface from empty CSV".to_string(),
+            ));
+        }
+
+        let mut expirations: Vec<DateTime<Utc>> = rows.iter().map(|r| r.0).collect();
+        expirations.sort();
+        expirations.dedup();
+        let mut strikes: Vec<f64> = rows.iter().map(|r| r.1).collect();
+        strikes.sort_by(|a, b| a.total_cmp(b));
+        strikes.dedup();
+
+        let mut volatilities =
+            ndarray::Array2::from_elem((expirations.len(), strikes.len()), f64::NAN);
+        for (expiration, strike, volatility) in rows {
+            let i = expirations.binary_search(&expiration).unwrap_or_default();
+            let j = strikes
+                .binary_search_by(|k| k.total_cmp(&strike))
+                .unwrap_or_default();
+            volatilities[[i, j]] = volatility;
+        }
+
+        Ok(VolatilitySurface {
+            symbol: symbol.to_string(),
+            expirations,
+            strikes,
+            volatilities,
+            timestamp: Utc::now(),
+            version: 1,
+        })
+    }
+
+    pub fn to_json_pretty(&self) -> Result<String> {
+        let snapshot = SurfaceSnapshot {
+            version: self.version,
+            timestamp: self.timestamp,
+            symbol: self.symbol.clone(),
+            expirations: self.expirations.clone(),
+            strikes: self.strikes.clone(),
+            volatilities: self
+                .volatilities
+                .rows()
+                .into_iter()
+                .map(|row| row.iter().map(|v| v.is_finite().then_some(*v)).collect())
+                .collect(),
+        };
+        Ok(serde_json::to_string_pretty(&snapshot)?)
+    }
+
+    pub fn from_json(s: &str) -> Result<VolatilitySurface> {
+        let snapshot: SurfaceSnapshot = serde_json::from_str(s)?;
+        let (rows, cols) = (snapshot.expirations.len(), snapshot.strikes.len());
+        if snapshot.volatilities.len() != rows
+            || snapshot.volatilities.iter().any(|row| row.len() != cols)
+        {
+            return Err(OptionsError::ParseError(format!(
+                "Surface JSON volatilities do not match the {}x{} grid",
+                rows, cols
+            )));
+        }
+
+        let values: Vec<f64> = snapshot
+            .volatilities
+            .into_iter()
+            .flatten()
+            .map(|v| v.unwrap_or(f64::NAN))
+            .collect();
+        let volatilities = ndarray::Array2::from_shape_vec((rows, cols), values)
+            .map_err(|e| OptionsError::ParseError(format!("Invalid surface grid: {}", e)))?;
+
+        Ok(VolatilitySurface {
+            symbol: snapshot.symbol,
+            expirations: snapshot.expirations,
+            strikes: snapshot.strikes,
+            volatilities,
+            timestamp: snapshot.timestamp,
+            version: snapshot.version,
+        })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut df = polars_utils::volatility_surface_to_dataframe(self)?;
+        // Parquet rows have no header, so the timestamp and version repeat per row.
+        let rows = df.height();
+        let nanos = self.timestamp.timestamp_nanos_opt().unwrap_or_default();
+        df.with_column(Series::new(PARQUET_TIMESTAMP_COLUMN, vec![nanos; rows]))?;
+        df.with_column(Series::new(
+            PARQUET_VERSION_COLUMN,
+            vec![self.version; rows],
+        ))?;
+        polars_utils::cache_dataframe_to_parquet(&df, &path.as_ref().to_string_lossy())
+    }
+
+    /// Files written before the timestamp and version columns existed load with
+    /// the current time and version 1.
+    pub fn load<P: AsRef<Path>>(symbol: &str, path: P) -> Result<VolatilitySurface> {
+        let df = polars_utils::load_dataframe_from_parquet(&path.as_ref().to_string_lossy())?;
+        let mut surface = polars_utils::dataframe_to_volatility_surface(&df, symbol)?;
+        if let Ok(column) = df.column(PARQUET_TIMESTAMP_COLUMN) {
+            if let Some(nanos) = column.i64()?.get(0) {
+                surface.timestamp = DateTime::from_timestamp_nanos(nanos);
+            }
+        }
+        if let Ok(column) = df.column(PARQUET_VERSION_COLUMN) {
+            if let Some(version) = column.u64()?.get(0) {
+                surface.version = version;
+            }
+        }
+        Ok(surface)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    fn sample_surface() -> VolatilitySurface {
+        let now = Utc::now();
+        VolatilitySurface {
+            symbol: "SPY".to_string(),
+            expirations: vec![
+                now + chrono::Duration::days(7),
+                now + chrono::Duration::days(35),
+            ],
+            strikes: vec![480.0, 500.5, 520.0],
+            volatilities: array![[0.31, f64::NAN, 0.27], [f64::NAN, 0.22, 0.235]],
+            timestamp: now,
+            version: 7,
+        }
+    }
+
+    fn assert_same_grid(a: &VolatilitySurface, b: &VolatilitySurface) {
+        assert_eq!(a.symbol, b.symbol);
+        assert_eq!(a.expirations, b.expirations);
+        assert_eq!(a.strikes, b.strikes);
+        assert_eq!(a.volatilities.dim(), b.volatilities.dim());
+        for (x, y) in a.volatilities.iter().zip(b.volatilities.iter()) {
+            assert!(x == y || (x.is_nan() && y.is_nan()), "{} != {}", x, y);
+        }
+    }
+
+    #[test]
+    fn csv_round_trip_keeps_nan_cells() {
+        let surface = sample_surface();
+        let mut buf = Vec::new();
+        surface.to_csv(&mut buf).unwrap();
+
+        let reloaded = VolatilitySurface::from_csv("SPY", buf.as_slice()).unwrap();
+        assert_same_grid(&surface, &reloaded);
+    }
+
+    #[test]
+    fn csv_rejects_bad_headers_and_rows() {
+        assert!(VolatilitySurface::from_csv("SPY", "strike,volatility\n".as_bytes()).is_err());
+        assert!(VolatilitySurface::from_csv("SPY", CSV_HEADER.as_bytes()).is_err());
+        let bad_row = format!("{}\n2024-06-21T20:00:00+00:00,500\n", CSV_HEADER);
+        assert!(VolatilitySurface::from_csv("SPY", bad_row.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn json_round_trip_keeps_nan_cells_and_header() {
+        let surface = sample_surface();
+        let json = surface.to_json_pretty().unwrap();
+        assert!(json.contains("null"));
+
+        let reloaded = VolatilitySurface::from_json(&json).unwrap();
+        assert_same_grid(&surface, &reloaded);
+        assert_eq!(reloaded.version, surface.version);
+        assert_eq!(reloaded.timestamp, surface.timestamp);
+    }
+
+    #[test]
+    fn json_rejects_mismatched_grids() {
+        let mut value: serde_json::Value =
+            serde_json::from_str(&sample_surface().to_json_pretty().unwrap()).unwrap();
+        value["volatilities"][1] = serde_json::json!([0.2]);
+        assert!(VolatilitySurface::from_json(&value.to_string()).is_err());
+    }
+
+    #[test]
+    fn parquet_round_trip_keeps_nan_cells_and_header() {
+        // Parquet stores expirations in milliseconds.
+        let mut surface = sample_surface();
+        for expiration in surface.expirations.iter_mut() {
+            *expiration = DateTime::from_timestamp_millis(expiration.timestamp_millis()).unwrap();
+        }
+        let path = std::env::temp_dir().join(format!("surface-io-{}.parquet", std::process::id()));
+
+        surface.save(&path).unwrap();
+        let reloaded = VolatilitySurface::load("SPY", &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_same_grid(&surface, &reloaded);
+        assert_eq!(reloaded.version, surface.version);
+        assert_eq!(reloaded.timestamp, surface.timestamp);
+    }
+}
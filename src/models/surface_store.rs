@@ -0,0 +1,247 @@
+use crate::models::volatility::VolatilitySurface;
+use ndarray::Array2;
+use std::collections::HashMap;
+
+pub const SPARSE_FILL_THRESHOLD: f64 = 0.25;
+
+pub trait SurfaceStore: Send + Sync {
+    fn dim(&self) -> (usize, usize);
+    fn get(&self, row: usize, col: usize) -> f64;
+    fn set(&mut self, row: usize, col: usize, value: f64);
+    fn iter_filled(&self) -> Box<dyn Iterator<Item = ((usize, usize), f64)> + '_>;
+    fn heap_bytes(&self) -> usize;
+
+    fn filled_len(&self) -> usize {
+        self.iter_filled().count()
+    }
+
+    fn to_dense(&self) -> Array2<f64> {
+        let mut values = Array2::from_elem(self.dim(), f64::NAN);
+        for ((row, col), value) in self.iter_filled() {
+            values[[row, col]] = value;
+        }
+        values
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DenseStore {
+    values: Array2<f64>,
+}
+
+impl DenseStore {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            values: Array2::from_elem((rows, cols), f64::NAN),
+        }
+    }
+
+    pub fn from_array(values: Array2<f64>) -> Self {
+        Self { values }
+    }
+}
+
+impl SurfaceStore for DenseStore {
+    fn dim(&self) -> (usize, usize) {
+        self.values.dim()
+    }
+
+    fn get(&self, row: usize, col: usize) -> f64 {
+        self.values.get((row, col)).copied().unwrap_or(f64::NAN)
+    }
+
+    fn set(&mut self, row: usize, col: usize, value: f64) {
+        if let Some(cell) = self.values.get_mut((row, col)) {
+            *cell = value;
+        }
+    }
+
+    fn iter_filled(&self) -> Box<dyn Iterator<Item = ((usize, usize), f64)> + '_> {
+        Box::new(
+            self.values
+                .indexed_iter()
+                .filter(|(_, v)| !v.is_nan())
+                .map(|(idx, &v)| (idx, v)),
+        )
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.values.len() * std::mem::size_of::<f64>()
+    }
+
+    fn to_dense(&self) -> Array2<f64> {
+        self.values.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SparseStore {
+    rows: usize,
+    cols: usize,
+    values: HashMap<(usize, usize), f64>,
+}
+
+impl SparseStore {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            values: HashMap::new(),
+        }
+    }
+
+    pub fn from_array(values: &Array2<f64>) -> Self {
+        let (rows, cols) = values.dim();
+        let mut store = Self::new(rows, cols);
+        for ((row, col), &v) in values.indexed_iter() {
+            store.set(row, col, v);
+        }
+        store
+    }
+}
+
+impl SurfaceStore for SparseStore {
+    fn dim(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    fn get(&self, row: usize, col: usize) -> f64 {
+        self.values.get(&(row, col)).copied().unwrap_or(f64::NAN)
+    }
+
+    fn set(&mut self, row: usize, col: usize, value: f64) {
+        if row >= self.rows || col >= self.cols {
+            return;
+        }
+        if value.is_nan() {
+            self.values.remove(&(row, col));
+        } else {
+            self.values.insert((row, col), value);
+        }
+    }
+
+    fn iter_filled(&self) -> Box<dyn Iterator<Item = ((usize, usize), f64)> + '_> {
+        Box::new(self.values.iter().map(|(&idx, &v)| (idx, v)))
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.values.capacity()
+            * (std::mem::size_of::<(usize, usize)>() + std::mem::size_of::<f64>())
+    }
+
+    fn filled_len(&self) -> usize {
+        self.values.len()
+    }
+}
+
+pub fn select_store(values: &Array2<f64>) -> Box<dyn SurfaceStore> {
+    let total = values.len();
+    let filled = values.iter().filter(|v| !v.is_nan()).count();
+    let fill_ratio = if total == 0 {
+        0.0
+    } else {
+        filled as f64 / total as f64
+    };
+
+    if fill_ratio < SPARSE_FILL_THRESHOLD {
+        Box::new(SparseStore::from_array(values))
+    } else {
+        Box::new(DenseStore::from_array(values.clone()))
+    }
+}
+
+impl VolatilitySurface {
+    pub fn store(&self) -> Box<dyn SurfaceStore> {
+        select_store(&self.volatilities)
+    }
+
+    pub fn load_store(&mut self, store: &dyn SurfaceStore) {
+        self.volatilities = store.to_dense();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An SPX-like grid: 50 expirations by 500 strikes with roughly 4% of cells quoted.
+    fn sparse_grid() -> Array2<f64> {
+        Array2::from_shape_fn((50, 500), |(i, j)| {
+            if (i * 500 + j) % 25 == 0 {
+                0.2 + 0.001 * i as f64
+            } else {
+                f64::NAN
+            }
+        })
+    }
+
+    #[test]
+    fn selects_backing_by_fill_ratio() {
+        let sparse = select_store(&sparse_grid());
+        assert_eq!(sparse.filled_len(), 1000);
+        assert!(sparse.heap_bytes() < 50 * 500 * std::mem::size_of::<f64>());
+
+        let dense = select_store(&Array2::from_elem((4, 4), 0.2));
+        assert_eq!(dense.heap_bytes(), 16 * std::mem::size_of::<f64>());
+    }
+
+    #[test]
+    fn sparse_store_uses_less_memory_on_a_500_by_50_grid() {
+        let grid = sparse_grid();
+        let dense = DenseStore::from_array(grid.clone());
+        let sparse = SparseStore::from_array(&grid);
+
+        assert_eq!(dense.heap_bytes(), 200_000);
+        assert!(
+            sparse.heap_bytes() * 4 < dense.heap_bytes(),
+            "sparse {} bytes vs dense {} bytes",
+            sparse.heap_bytes(),
+            dense.heap_bytes()
+        );
+    }
+
+    #[test]
+    fn dense_and_sparse_stores_agree() {
+        let grid = sparse_grid();
+        let mut dense = DenseStore::from_array(grid.clone());
+        let mut sparse = SparseStore::from_array(&grid);
+
+        for store in [&mut dense as &mut dyn SurfaceStore, &mut sparse] {
+            store.set(3, 7, 0.5);
+            store.set(0, 0, f64::NAN);
+            store.set(99, 0, 0.1);
+            assert_eq!(store.dim(), (50, 500));
+            assert_eq!(store.get(3, 7), 0.5);
+            assert!(store.get(0, 0).is_nan());
+            assert!(store.get(99, 0).is_nan());
+        }
+
+        let (a, b) = (dense.to_dense(), sparse.to_dense());
+        assert!(a
+            .iter()
+            .zip(b.iter())
+            .all(|(x, y)| x == y || (x.is_nan() && y.is_nan())));
+        assert_eq!(dense.filled_len(), sparse.filled_len());
+    }
+
+    #[test]
+    fn surface_round_trips_through_its_store() {
+        let mut surface = VolatilitySurface {
+            symbol: "SPX".to_string(),
+            expirations: (0..50)
+                .map(|d| chrono::Utc::now() + chrono::Duration::days(d + 1))
+                .collect(),
+            strikes: (0..500).map(|k| 4000.0 + 5.0 * k as f64).collect(),
+            volatilities: sparse_grid(),
+            timestamp: chrono::Utc::now(),
+            version: 1,
+        };
+
+        let mut store = surface.store();
+        store.set(10, 20, 0.33);
+        surface.load_store(store.as_ref());
+        assert_eq!(surface.volatilities[[10, 20]], 0.33);
+        assert_eq!(surface.volatilities[[0, 0]], 0.2);
+        assert!(surface.volatilities[[0, 1]].is_nan());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::models::volatility::VolatilitySurface;
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
@@ -7,3 +8,13 @@ pub struct SurfaceUpdate {
     pub expiries: Vec<NaiveDate>,
     pub sigma: Vec<f64>,
 }
+
+impl From<&VolatilitySurface> for SurfaceUpdate {
+    fn from(surface: &VolatilitySurface) -> Self {
+        Self {
+            strikes: surface.strikes.clone(),
+            expiries: surface.expirations.iter().map(|e| e.date_naive()).collect(),
+            sigma: surface.volatilities.iter().copied().collect(),
+        }
+    }
+}
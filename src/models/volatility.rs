@@ -1,10 +1,44 @@
 use crate::error::{OptionsError, Result};
+use crate::models::forward::ForwardCurve;
 use crate::models::option::{OptionContract, OptionQuote};
-use crate::utils::{delta, implied_volatility, vega};
+use crate::utils::svi::{fit_svi, SviParams};
+use crate::utils::{
+    annualized_to_period, delta, delta_with_yield, implied_volatility_bid_ask_with_yield,
+    vega_with_yield, BlackScholes, OptionModel, CALENDAR_DAYS_PER_YEAR,
+};
 use ndarray::{Array1, Array2};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
 
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+const BUTTERFLY_TOLERANCE: f64 = 1e-8;
+const CALENDAR_TOLERANCE: f64 = 1e-10;
+const ATM_STRIKE_TOLERANCE: f64 = 1e-9;
+
+fn years_between(from: chrono::DateTime<chrono::Utc>, to: chrono::DateTime<chrono::Utc>) -> f64 {
+    if to <= from {
+        0.0
+    } else {
+        (to - from).num_seconds() as f64 / SECONDS_PER_YEAR
+    }
+}
+
+fn forward_at(
+    forward_curve: &ForwardCurve,
+    now: chrono::DateTime<chrono::Utc>,
+    expiration: chrono::DateTime<chrono::Utc>,
+) -> Result<f64> {
+    let forward = forward_curve.forward(years_between(now, expiration));
+    if forward > 0.0 && forward.is_finite() {
+        Ok(forward)
+    } else {
+        Err(OptionsError::VolatilityError(format!(
+            "Invalid forward {} for expiration {}",
+            forward, expiration
+        )))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImpliedVolatility {
     pub contract: OptionContract,
@@ -21,6 +55,69 @@ impl ImpliedVolatility {
         quote: &OptionQuote,
         risk_free_rate: f64,
         dividend_yield: f64,
+    ) -> Result<Self> {
+        Self::from_quote_with_model(quote, risk_free_rate, dividend_yield, &BlackScholes)
+    }
+
+    pub fn from_quote_bid_ask(
+        quote: &OptionQuote,
+        risk_free_rate: f64,
+        dividend_yield: f64,
+    ) -> Result<Self> {
+        let contract = &quote.contract;
+        let underlying_price = quote.underlying_price;
+        let strike = contract.strike;
+        let time_to_expiration = contract.time_to_expiration();
+
+        if time_to_expiration <= 0.0 {
+            return Err(OptionsError::VolatilityError(
+                "Option is expired, cannot calculate implied volatility".to_string(),
+            ));
+        }
+
+        let is_call = contract.is_call();
+        let iv = implied_volatility_bid_ask_with_yield(
+            quote.bid,
+            quote.ask,
+            underlying_price,
+            strike,
+            time_to_expiration,
+            risk_free_rate,
+            dividend_yield,
+            is_call,
+        )?;
+
+        Ok(Self {
+            contract: contract.clone(),
+            value: iv,
+            underlying_price,
+            option_price: quote.mid_price(),
+            time_to_expiration,
+            delta: delta_with_yield(
+                underlying_price,
+                strike,
+                time_to_expiration,
+                risk_free_rate,
+                dividend_yield,
+                iv,
+                is_call,
+            ),
+            vega: vega_with_yield(
+                underlying_price,
+                strike,
+                time_to_expiration,
+                risk_free_rate,
+                dividend_yield,
+                iv,
+            ),
+        })
+    }
+
+    pub fn from_quote_with_model<M: OptionModel + ?Sized>(
+        quote: &OptionQuote,
+        risk_free_rate: f64,
+        dividend_yield: f64,
+        model: &M,
     ) -> Result<Self> {
         let contract = &quote.contract;
         let option_price = quote.mid_price();
@@ -42,32 +139,39 @@ impl ImpliedVolatility {
 
         let is_call = contract.is_call();
 
-        let iv = implied_volatility(
-            option_price,
-            underlying_price,
-            strike,
-            time_to_expiration,
-            risk_free_rate - dividend_yield,
-            is_call,
-        )
-        .map_err(|e| {
-            OptionsError::VolatilityError(format!("Failed to calculate implied volatility: {}", e))
-        })?;
+        let iv = model
+            .implied_vol_with_yield(
+                option_price,
+                underlying_price,
+                strike,
+                time_to_expiration,
+                risk_free_rate,
+                dividend_yield,
+                is_call,
+            )
+            .map_err(|e| {
+                OptionsError::VolatilityError(format!(
+                    "Failed to calculate implied volatility: {}",
+                    e
+                ))
+            })?;
 
-        let delta_value = delta(
+        let delta_value = model.delta_with_yield(
             underlying_price,
             strike,
             time_to_expiration,
-            risk_free_rate - dividend_yield,
+            risk_free_rate,
+            dividend_yield,
             iv,
             is_call,
         );
 
-        let vega_value = vega(
+        let vega_value = model.vega_with_yield(
             underlying_price,
             strike,
             time_to_expiration,
-            risk_free_rate - dividend_yield,
+            risk_free_rate,
+            dividend_yield,
             iv,
         );
 
@@ -83,6 +187,227 @@ impl ImpliedVolatility {
     }
 }
 
+pub fn batch_implied_volatility_quotes(
+    quotes: &[OptionQuote],
+    risk_free_rate: f64,
+    dividend_yield: f64,
+) -> Vec<Result<ImpliedVolatility>> {
+    batch_implied_volatility_quotes_with_model(
+        quotes,
+        risk_free_rate,
+        dividend_yield,
+        &BlackScholes,
+    )
+}
+
+pub fn batch_implied_volatility_quotes_with_model<M: OptionModel + ?Sized>(
+    quotes: &[OptionQuote],
+    risk_free_rate: f64,
+    dividend_yield: f64,
+    model: &M,
+) -> Vec<Result<ImpliedVolatility>> {
+    let rates = vec![risk_free_rate; quotes.len()];
+    batch_with_rates(quotes, &rates, dividend_yield, model)
+}
+
+fn batch_with_rates<M: OptionModel + ?Sized>(
+    quotes: &[OptionQuote],
+    rates: &[f64],
+    dividend_yield: f64,
+    model: &M,
+) -> Vec<Result<ImpliedVolatility>> {
+    use rayon::prelude::*;
+
+    quotes
+        .par_iter()
+        .zip(rates)
+        .map(|(quote, &rate)| {
+            ImpliedVolatility::from_quote_with_model(quote, rate, dividend_yield, model)
+        })
+        .collect()
+}
+
+fn natural_cubic_spline(x: &[f64], y: &[f64], at: f64) -> f64 {
+    let n = x.len();
+    if n == 2 {
+        return y[0] + (y[1] - y[0]) * (at - x[0]) / (x[1] - x[0]);
+    }
+
+    let h: Vec<f64> = (0..n - 1).map(|i| x[i + 1] - x[i]).collect();
+    let mut m = vec![0.0; n];
+    let mut c_prime = vec![0.0; n];
+    let mut d_prime = vec![0.0; n];
+    for i in 1..n - 1 {
+        let a = h[i - 1];
+        let b = 2.0 * (h[i - 1] + h[i]);
+        let c = h[i];
+        let d = 6.0 * ((y[i + 1] - y[i]) / h[i] - (y[i] - y[i - 1]) / h[i - 1]);
+        let denom = b - a * c_prime[i - 1];
+        c_prime[i] = c / denom;
+        d_prime[i] = (d - a * d_prime[i - 1]) / denom;
+    }
+    for i in (1..n - 1).rev() {
+        m[i] = d_prime[i] - c_prime[i] * m[i + 1];
+    }
+
+    if at <= x[0] {
+        let slope = (y[1] - y[0]) / h[0] - h[0] * (2.0 * m[0] + m[1]) / 6.0;
+        return y[0] + slope * (at - x[0]);
+    }
+    if at >= x[n - 1] {
+        let slope = (y[n - 1] - y[n - 2]) / h[n - 2] + h[n - 2] * (m[n - 2] + 2.0 * m[n - 1]) / 6.0;
+        return y[n - 1] + slope * (at - x[n - 1]);
+    }
+
+    let i = x.partition_point(|&xi| xi <= at).clamp(1, n - 1) - 1;
+    let (a, b) = (x[i + 1] - at, at - x[i]);
+    m[i] * a.powi(3) / (6.0 * h[i])
+        + m[i + 1] * b.powi(3) / (6.0 * h[i])
+        + (y[i] / h[i] - m[i] * h[i] / 6.0) * a
+        + (y[i + 1] / h[i] - m[i + 1] * h[i] / 6.0) * b
+}
+
+fn grid_bracket(axis: &[f64], x: f64) -> (usize, usize, f64) {
+    if axis.len() == 1 {
+        return (0, 0, 0.0);
+    }
+    let hi = axis.partition_point(|&a| a < x).clamp(1, axis.len() - 1);
+    let lo = hi - 1;
+    let width = axis[hi] - axis[lo];
+    let w = if width > 0.0 {
+        ((x - axis[lo]) / width).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (lo, hi, w)
+}
+
+fn flat_smile_value(strikes: &[f64], vols: ndarray::ArrayView1<f64>, strike: f64) -> Option<f64> {
+    let points: Vec<(f64, f64)> = strikes
+        .iter()
+        .zip(vols.iter())
+        .filter(|(_, v)| v.is_finite())
+        .map(|(&k, &v)| (k, v))
+        .collect();
+    let (first, last) = (points.first()?, points.last()?);
+    if strike <= first.0 {
+        return Some(first.1);
+    }
+    if strike >= last.0 {
+        return Some(last.1);
+    }
+    let idx = points.partition_point(|&(k, _)| k <= strike);
+    let ((k0, v0), (k1, v1)) = (points[idx - 1], points[idx]);
+    Some(v0 + (v1 - v0) * (strike - k0) / (k1 - k0))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExtrapMode {
+    Error,
+    ClampEdge,
+    FlatExtend,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GridQuality {
+    pub max_strike_gap: f64,
+    pub max_expiry_gap_days: f64,
+    pub fill_ratio: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SurfaceSummary {
+    pub n_expiries: usize,
+    pub n_strikes: usize,
+    pub fill_ratio: f64,
+    pub min_iv: Option<f64>,
+    pub max_iv: Option<f64>,
+    pub mean_iv: Option<f64>,
+    pub atm_iv_estimate: Option<f64>,
+    pub strike_range: Option<(f64, f64)>,
+    pub expiry_range: Option<(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)>,
+}
+
+impl std::fmt::Display for SurfaceSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pct = |v: Option<f64>| match v {
+            Some(v) => format!("{:.2}%", v * 100.0),
+            None => "n/a".to_string(),
+        };
+
+        writeln!(
+            f,
+            "{} expiries x {} strikes ({:.1}% filled)",
+            self.n_expiries,
+            self.n_strikes,
+            self.fill_ratio * 100.0
+        )?;
+        writeln!(
+            f,
+            "IV min {} / mean {} / max {} / ATM~ {}",
+            pct(self.min_iv),
+            pct(self.mean_iv),
+            pct(self.max_iv),
+            pct(self.atm_iv_estimate)
+        )?;
+        match self.strike_range {
+            Some((lo, hi)) => writeln!(f, "Strikes {:.2} - {:.2}", lo, hi)?,
+            None => writeln!(f, "Strikes n/a")?,
+        }
+        match self.expiry_range {
+            Some((first, last)) => write!(
+                f,
+                "Expiries {} - {}",
+                first.format("%Y-%m-%d"),
+                last.format("%Y-%m-%d")
+            ),
+            None => write!(f, "Expiries n/a"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MoneynessWindow {
+    pub spot: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl MoneynessWindow {
+    pub fn contains(&self, strike: f64) -> bool {
+        let m = strike / self.spot;
+        m >= self.min && m <= self.max
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TermShape {
+    pub front_vol: f64,
+    pub back_vol: f64,
+    pub slope: f64,
+    pub curvature: f64,
+    pub is_backwardated: bool,
+}
+
+const TERM_SHAPE_TENORS: [i64; 6] = [7, 30, 60, 90, 180, 365];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SkewMetric {
+    pub expiration: chrono::DateTime<chrono::Utc>,
+    pub rr_25: f64,
+    pub bf_25: f64,
+}
+
+const SKEW_DELTA: f64 = 0.25;
+const DELTA_STRIKE_MAX_ITER: usize = 100;
+const DELTA_STRIKE_TOL: f64 = 1e-8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnchorShift {
+    Vol,
+    Variance,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VolatilitySurface {
     pub symbol: String,
@@ -141,10 +466,154 @@ impl VolatilitySurface {
         })
     }
 
+    pub fn from_quotes_filtered(
+        symbol: String,
+        quotes: &[OptionQuote],
+        risk_free_rate: f64,
+        max_spread_pct: f64,
+        max_staleness: chrono::Duration,
+    ) -> Result<Self> {
+        let cutoff = chrono::Utc::now() - max_staleness;
+        let kept: Vec<OptionQuote> = quotes
+            .iter()
+            .filter(|q| q.bid > 0.0 && q.ask >= q.bid)
+            .filter(|q| (q.ask - q.bid) / q.mid_price() <= max_spread_pct)
+            .filter(|q| q.timestamp >= cutoff)
+            .cloned()
+            .collect();
+        let ivs: Vec<ImpliedVolatility> =
+            batch_implied_volatility_quotes(&kept, risk_free_rate, 0.0)
+                .into_iter()
+                .filter_map(|iv| iv.ok())
+                .collect();
+
+        if ivs.is_empty() {
+            return Err(OptionsError::VolatilityError(format!(
+                "No implied volatilities left after filtering {} quotes",
+                quotes.len()
+            )));
+        }
+
+        Self::new(symbol, &ivs)
+    }
+
+    pub fn from_quotes_with_curve(
+        symbol: String,
+        quotes: &[OptionQuote],
+        rate_fn: impl Fn(f64) -> f64,
+        dividend_yield: f64,
+    ) -> Result<Self> {
+        let rates: Vec<f64> = quotes
+            .iter()
+            .map(|q| rate_fn(q.contract.time_to_expiration()))
+            .collect();
+        let ivs: Vec<ImpliedVolatility> =
+            batch_with_rates(quotes, &rates, dividend_yield, &BlackScholes)
+                .into_iter()
+                .filter_map(|iv| iv.ok())
+                .collect();
+
+        if ivs.is_empty() {
+            return Err(OptionsError::VolatilityError(format!(
+                "No implied volatilities could be computed from {} quotes",
+                quotes.len()
+            )));
+        }
+
+        Self::new(symbol, &ivs)
+    }
+
     pub fn interpolate(
         &self,
         expiration: chrono::DateTime<chrono::Utc>,
         strike: f64,
+    ) -> Result<f64> {
+        self.interpolate_ext(expiration, strike, ExtrapMode::Error)
+    }
+
+    pub fn interpolate_ext(
+        &self,
+        expiration: chrono::DateTime<chrono::Utc>,
+        strike: f64,
+        mode: ExtrapMode,
+    ) -> Result<f64> {
+        match mode {
+            ExtrapMode::Error => self.interpolate_strict(expiration, strike),
+            ExtrapMode::ClampEdge => self.interpolate_clamped(expiration, strike),
+            ExtrapMode::FlatExtend => self.interpolate_flat(expiration, strike),
+        }
+    }
+
+    fn expiration_bracket(
+        &self,
+        expiration: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(usize, usize, f64)> {
+        let Some(&first_exp) = self.expirations.first() else {
+            return Err(OptionsError::VolatilityError(
+                "Cannot interpolate an empty surface".to_string(),
+            ));
+        };
+        let offsets: Vec<f64> = self
+            .expirations
+            .iter()
+            .map(|e| (*e - first_exp).num_seconds() as f64)
+            .collect();
+        Ok(grid_bracket(
+            &offsets,
+            (expiration - first_exp).num_seconds() as f64,
+        ))
+    }
+
+    fn interpolate_clamped(
+        &self,
+        expiration: chrono::DateTime<chrono::Utc>,
+        strike: f64,
+    ) -> Result<f64> {
+        let (e1, e2, t) = self.expiration_bracket(expiration)?;
+        if self.strikes.is_empty() {
+            return Err(OptionsError::VolatilityError(
+                "Cannot interpolate an empty surface".to_string(),
+            ));
+        }
+        let (s1, s2, u) = grid_bracket(&self.strikes, strike);
+
+        let v11 = self.volatilities[[e1, s1]];
+        let v12 = self.volatilities[[e1, s2]];
+        let v21 = self.volatilities[[e2, s1]];
+        let v22 = self.volatilities[[e2, s2]];
+
+        if v11.is_nan() || v12.is_nan() || v21.is_nan() || v22.is_nan() {
+            return Err(OptionsError::VolatilityError(
+                "Cannot interpolate with NaN values".to_string(),
+            ));
+        }
+
+        Ok((1.0 - t) * (1.0 - u) * v11 + (1.0 - t) * u * v12 + t * (1.0 - u) * v21 + t * u * v22)
+    }
+
+    fn interpolate_flat(
+        &self,
+        expiration: chrono::DateTime<chrono::Utc>,
+        strike: f64,
+    ) -> Result<f64> {
+        let (e1, e2, t) = self.expiration_bracket(expiration)?;
+        let smile = |row: usize| {
+            flat_smile_value(&self.strikes, self.volatilities.row(row), strike).ok_or_else(|| {
+                OptionsError::VolatilityError(format!(
+                    "No volatilities available for expiration {}",
+                    self.expirations[row]
+                ))
+            })
+        };
+        let v1 = smile(e1)?;
+        let v2 = smile(e2)?;
+        Ok((1.0 - t) * v1 + t * v2)
+    }
+
+    fn interpolate_strict(
+        &self,
+        expiration: chrono::DateTime<chrono::Utc>,
+        strike: f64,
     ) -> Result<f64> {
         let mut exp_idx_before = None;
         let mut exp_idx_after = None;
@@ -206,6 +675,38 @@ impl VolatilitySurface {
         }
     }
 
+    pub fn interpolate_smile(
+        &self,
+        expiration: chrono::DateTime<chrono::Utc>,
+        strike: f64,
+    ) -> Result<f64> {
+        let row = self
+            .expirations
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, &e)| (e - expiration).num_seconds().abs())
+            .map(|(i, _)| i)
+            .ok_or_else(|| {
+                OptionsError::VolatilityError("Volatility surface has no expirations".to_string())
+            })?;
+
+        let (strikes, vols): (Vec<f64>, Vec<f64>) = self
+            .strikes
+            .iter()
+            .zip(self.volatilities.row(row).iter())
+            .filter(|(_, v)| !v.is_nan())
+            .map(|(&k, &v)| (k, v))
+            .unzip();
+
+        if strikes.len() < 2 {
+            return Err(OptionsError::VolatilityError(
+                "At least two strikes are required for smile interpolation".to_string(),
+            ));
+        }
+
+        Ok(natural_cubic_spline(&strikes, &vols, strike))
+    }
+
     pub fn slice_by_expiration(
         &self,
         expiration: chrono::DateTime<chrono::Utc>,
@@ -226,6 +727,25 @@ impl VolatilitySurface {
         Ok((strikes, volatilities))
     }
 
+    pub fn slice_by_expiration_filtered(
+        &self,
+        expiration: chrono::DateTime<chrono::Utc>,
+        window: Option<MoneynessWindow>,
+    ) -> Result<(Array1<f64>, Array1<f64>)> {
+        let (strikes, vols) = self.slice_by_expiration(expiration)?;
+
+        let mut points: Vec<(f64, f64)> = strikes
+            .iter()
+            .zip(vols.iter())
+            .filter(|(&k, &v)| !v.is_nan() && window.is_none_or(|w| w.contains(k)))
+            .map(|(&k, &v)| (k, v))
+            .collect();
+        points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let (strikes, vols): (Vec<f64>, Vec<f64>) = points.into_iter().unzip();
+        Ok((Array1::from_vec(strikes), Array1::from_vec(vols)))
+    }
+
     pub fn slice_by_strike(&self, strike: f64) -> Result<(Array1<f64>, Array1<f64>)> {
         let strike_idx = self
             .strikes
@@ -342,4 +862,1430 @@ impl VolatilitySurface {
     pub fn get_version(&self) -> u64 {
         self.version
     }
+
+    pub fn implied_daily_move(
+        &self,
+        expiration: chrono::DateTime<chrono::Utc>,
+        strike: f64,
+        spot: f64,
+    ) -> Result<f64> {
+        let exact = self
+            .expirations
+            .iter()
+            .position(|&e| e == expiration)
+            .zip(self.strikes.iter().position(|&s| s == strike))
+            .map(|(i, j)| self.volatilities[[i, j]])
+            .filter(|v| !v.is_nan());
+
+        let iv = match exact {
+            Some(v) => v,
+            None => self.interpolate(expiration, strike)?,
+        };
+
+        Ok(spot * annualized_to_period(iv, 1.0, CALENDAR_DAYS_PER_YEAR))
+    }
+
+    pub fn times_to_expiration(&self) -> Vec<f64> {
+        let now = chrono::Utc::now();
+        self.expirations
+            .iter()
+            .map(|&exp| years_between(now, exp))
+            .collect()
+    }
+
+    pub fn to_total_variance(&self) -> Array2<f64> {
+        let times = self.times_to_expiration();
+        let mut variance = self.volatilities.clone();
+        for (mut row, t) in variance.rows_mut().into_iter().zip(times) {
+            row.mapv_inplace(|sigma| sigma * sigma * t);
+        }
+        variance
+    }
+
+    pub fn interpolate_variance(
+        &self,
+        expiration: chrono::DateTime<chrono::Utc>,
+        strike: f64,
+    ) -> Result<f64> {
+        let out_of_range = || {
+            OptionsError::VolatilityError(
+                "Cannot interpolate variance: expiration or strike out of range".to_string(),
+            )
+        };
+        let (Some(&first_exp), Some(&last_exp)) =
+            (self.expirations.first(), self.expirations.last())
+        else {
+            return Err(out_of_range());
+        };
+        let (Some(&k_min), Some(&k_max)) = (self.strikes.first(), self.strikes.last()) else {
+            return Err(out_of_range());
+        };
+        if expiration < first_exp || expiration > last_exp || strike < k_min || strike > k_max {
+            return Err(out_of_range());
+        }
+
+        let target_t = years_between(chrono::Utc::now(), expiration);
+        if target_t <= 0.0 {
+            return Err(OptionsError::VolatilityError(
+                "Cannot interpolate variance at or past expiration".to_string(),
+            ));
+        }
+
+        let times = self.times_to_expiration();
+        let variance = self.to_total_variance();
+        let (e1, e2, t) = self.expiration_bracket(expiration)?;
+        let (s1, s2, u) = grid_bracket(&self.strikes, strike);
+
+        let w1 = (1.0 - u) * variance[[e1, s1]] + u * variance[[e1, s2]];
+        let w2 = (1.0 - u) * variance[[e2, s1]] + u * variance[[e2, s2]];
+        if w1.is_nan() || w2.is_nan() {
+            return Err(OptionsError::VolatilityError(
+                "Cannot interpolate with NaN values".to_string(),
+            ));
+        }
+
+        let w = if e1 == e2 || times[e2] <= times[e1] {
+            (1.0 - t) * w1 + t * w2
+        } else {
+            let weight = ((target_t - times[e1]) / (times[e2] - times[e1])).clamp(0.0, 1.0);
+            w1 + (w2 - w1) * weight
+        };
+
+        Ok((w.max(0.0) / target_t).sqrt())
+    }
+
+    pub fn butterfly_violations(
+        &self,
+        spot: f64,
+        r: f64,
+    ) -> Vec<(chrono::DateTime<chrono::Utc>, f64)> {
+        let now = chrono::Utc::now();
+        let mut violations = Vec::new();
+
+        for (i, &expiration) in self.expirations.iter().enumerate() {
+            let t = years_between(now, expiration);
+            if t <= 0.0 {
+                continue;
+            }
+
+            let calls: Vec<(f64, f64)> = self
+                .strikes
+                .iter()
+                .zip(self.volatilities.row(i).iter())
+                .filter(|(_, sigma)| sigma.is_finite() && **sigma > 0.0)
+                .map(|(&k, &sigma)| (k, BlackScholes.price(spot, k, t, r, sigma, true)))
+                .collect();
+
+            for w in calls.windows(3) {
+                let (k0, c0) = w[0];
+                let (k1, c1) = w[1];
+                let (k2, c2) = w[2];
+                let second = (c2 - c1) / (k2 - k1) - (c1 - c0) / (k1 - k0);
+                if second < -BUTTERFLY_TOLERANCE {
+                    violations.push((expiration, k1));
+                }
+            }
+        }
+
+        violations
+    }
+
+    pub fn calendar_violations(&self) -> Vec<(f64, chrono::DateTime<chrono::Utc>)> {
+        let variance = self.to_total_variance();
+        let mut violations = Vec::new();
+
+        for (j, &strike) in self.strikes.iter().enumerate() {
+            let mut previous: Option<f64> = None;
+            for (i, &expiration) in self.expirations.iter().enumerate() {
+                let w = variance[[i, j]];
+                if w.is_nan() {
+                    continue;
+                }
+                if previous.is_some_and(|p| w < p - CALENDAR_TOLERANCE) {
+                    violations.push((strike, expiration));
+                }
+                previous = Some(previous.map_or(w, |p| p.max(w)));
+            }
+        }
+
+        violations
+    }
+
+    pub fn calibrate_svi(&self, forward_curve: &ForwardCurve) -> Result<Vec<SviParams>> {
+        let now = chrono::Utc::now();
+        let variance = self.to_total_variance();
+        self.expirations
+            .iter()
+            .enumerate()
+            .map(|(i, &expiration)| {
+                let forward = forward_at(forward_curve, now, expiration)?;
+                let (k, w): (Vec<f64>, Vec<f64>) = self
+                    .strikes
+                    .iter()
+                    .zip(variance.row(i).iter())
+                    .filter(|(_, w)| w.is_finite() && **w > 0.0)
+                    .map(|(&strike, &w)| ((strike / forward).ln(), w))
+                    .unzip();
+                fit_svi(&k, &w)
+            })
+            .collect()
+    }
+
+    pub fn constant_maturity(&self, tenors_days: &[i64]) -> Result<VolatilitySurface> {
+        let now = chrono::Utc::now();
+        let times = self.times_to_expiration();
+
+        let mut tenors: Vec<i64> = tenors_days.iter().copied().filter(|&d| d > 0).collect();
+        tenors.sort_unstable();
+        tenors.dedup();
+
+        let mut expirations = Vec::new();
+        let mut rows: Vec<Vec<f64>> = Vec::new();
+
+        for tenor in tenors {
+            let target_exp = now + chrono::Duration::days(tenor);
+            let target_t = years_between(now, target_exp);
+
+            let Some(upper) = times.iter().position(|&t| t >= target_t) else {
+                continue;
+            };
+            if upper == 0 && times[0] > target_t {
+                continue;
+            }
+
+            let row: Vec<f64> = if times[upper] == target_t || upper == 0 {
+                (0..self.strikes.len())
+                    .map(|j| self.volatilities[[upper, j]])
+                    .collect()
+            } else {
+                let lower = upper - 1;
+                let (t1, t2) = (times[lower], times[upper]);
+                (0..self.strikes.len())
+                    .map(|j| {
+                        let v1 = self.volatilities[[lower, j]];
+                        let v2 = self.volatilities[[upper, j]];
+                        if v1.is_nan() || v2.is_nan() {
+                            return f64::NAN;
+                        }
+                        let w1 = v1 * v1 * t1;
+                        let w2 = v2 * v2 * t2;
+                        let w = w1 + (w2 - w1) * (target_t - t1) / (t2 - t1);
+                        if w > 0.0 {
+                            (w / target_t).sqrt()
+                        } else {
+                            f64::NAN
+                        }
+                    })
+                    .collect()
+            };
+
+            expirations.push(target_exp);
+            rows.push(row);
+        }
+
+        if expirations.is_empty() {
+            return Err(OptionsError::VolatilityError(
+                "No requested tenor falls within the listed expirations".to_string(),
+            ));
+        }
+
+        let mut volatilities = Array2::from_elem((expirations.len(), self.strikes.len()), f64::NAN);
+        for (i, row) in rows.iter().enumerate() {
+            for (j, &v) in row.iter().enumerate() {
+                volatilities[[i, j]] = v;
+            }
+        }
+
+        Ok(Self {
+            symbol: self.symbol.clone(),
+            expirations,
+            strikes: self.strikes.clone(),
+            volatilities,
+            timestamp: now,
+            version: 1,
+        })
+    }
+
+    pub fn skew_slope(
+        &self,
+        expiration: chrono::DateTime<chrono::Utc>,
+        forward_curve: &ForwardCurve,
+    ) -> Result<f64> {
+        let forward = forward_curve.forward(years_between(chrono::Utc::now(), expiration));
+        if forward <= 0.0 || !forward.is_finite() {
+            return Err(OptionsError::VolatilityError(format!(
+                "Invalid forward {} for skew slope",
+                forward
+            )));
+        }
+
+        let (strikes, vols) = self.slice_by_expiration(expiration)?;
+        let points: Vec<(f64, f64)> = strikes
+            .iter()
+            .zip(vols.iter())
+            .filter(|(&k, &v)| k > 0.0 && v.is_finite())
+            .map(|(&k, &v)| ((k / forward).ln(), v))
+            .collect();
+
+        if points.len() < 3 {
+            return Err(OptionsError::VolatilityError(format!(
+                "At least three strikes with volatilities are required for skew slope, found {}",
+                points.len()
+            )));
+        }
+
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+        let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+
+        if sxx <= f64::EPSILON {
+            return Err(OptionsError::VolatilityError(
+                "Strikes are degenerate in log-moneyness".to_string(),
+            ));
+        }
+
+        Ok(sxy / sxx)
+    }
+
+    pub fn forward_skew(
+        &self,
+        exp_near: chrono::DateTime<chrono::Utc>,
+        exp_far: chrono::DateTime<chrono::Utc>,
+        forward_curve: &ForwardCurve,
+    ) -> Result<f64> {
+        let near = self.skew_slope(exp_near, forward_curve)?;
+        let far = self.skew_slope(exp_far, forward_curve)?;
+        Ok(far - near)
+    }
+
+    pub fn grid_quality(&self) -> GridQuality {
+        let max_strike_gap = self
+            .strikes
+            .windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0, f64::max);
+
+        let max_expiry_gap_days = self
+            .expirations
+            .windows(2)
+            .map(|w| (w[1] - w[0]).num_seconds().abs() as f64 / 86_400.0)
+            .fold(0.0, f64::max);
+
+        let total = self.volatilities.len();
+        let fill_ratio = if total == 0 {
+            0.0
+        } else {
+            self.volatilities.iter().filter(|v| !v.is_nan()).count() as f64 / total as f64
+        };
+
+        GridQuality {
+            max_strike_gap,
+            max_expiry_gap_days,
+            fill_ratio,
+        }
+    }
+
+    pub fn fill_missing(&mut self) -> usize {
+        let original = self.volatilities.clone();
+        let (rows, cols) = original.dim();
+
+        let average = |values: [Option<f64>; 2]| {
+            let found: Vec<f64> = values.into_iter().flatten().collect();
+            (!found.is_empty()).then(|| found.iter().sum::<f64>() / found.len() as f64)
+        };
+
+        let mut filled = 0;
+        for i in 0..rows {
+            for j in 0..cols {
+                if !original[[i, j]].is_nan() {
+                    continue;
+                }
+                let row = original.row(i);
+                let column = original.column(j);
+                let along_strike = average([
+                    row.iter().take(j).rev().find(|v| !v.is_nan()).copied(),
+                    row.iter().skip(j + 1).find(|v| !v.is_nan()).copied(),
+                ]);
+                let value = along_strike.or_else(|| {
+                    average([
+                        column.iter().take(i).rev().find(|v| !v.is_nan()).copied(),
+                        column.iter().skip(i + 1).find(|v| !v.is_nan()).copied(),
+                    ])
+                });
+                if let Some(v) = value {
+                    self.volatilities[[i, j]] = v;
+                    filled += 1;
+                }
+            }
+        }
+
+        filled
+    }
+
+    fn value_at(&self, expiration: chrono::DateTime<chrono::Utc>, strike: f64) -> f64 {
+        let i = self.expirations.iter().position(|&e| e == expiration);
+        let j = self.strikes.iter().position(|&k| k == strike);
+        match (i, j) {
+            (Some(i), Some(j)) => self.volatilities[[i, j]],
+            _ => f64::NAN,
+        }
+    }
+
+    pub fn merge_otm(
+        call: &VolatilitySurface,
+        put: &VolatilitySurface,
+        forward_curve: &ForwardCurve,
+    ) -> Result<VolatilitySurface> {
+        let now = chrono::Utc::now();
+        let expirations: Vec<chrono::DateTime<chrono::Utc>> = call
+            .expirations
+            .iter()
+            .chain(&put.expirations)
+            .copied()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let mut strikes: Vec<f64> = call.strikes.iter().chain(&put.strikes).copied().collect();
+        strikes.sort_by(|a, b| a.total_cmp(b));
+        strikes.dedup();
+
+        let mut volatilities = Array2::from_elem((expirations.len(), strikes.len()), f64::NAN);
+        for (i, &expiration) in expirations.iter().enumerate() {
+            let forward = forward_at(forward_curve, now, expiration)?;
+
+            for (j, &strike) in strikes.iter().enumerate() {
+                let call_vol = call.value_at(expiration, strike);
+                let put_vol = put.value_at(expiration, strike);
+                let at_forward = (strike - forward).abs() <= ATM_STRIKE_TOLERANCE * forward;
+                volatilities[[i, j]] = if at_forward {
+                    match (call_vol.is_nan(), put_vol.is_nan()) {
+                        (false, false) => (call_vol + put_vol) / 2.0,
+                        (false, true) => call_vol,
+                        _ => put_vol,
+                    }
+                } else {
+                    let (otm, itm) = if strike < forward {
+                        (put_vol, call_vol)
+                    } else {
+                        (call_vol, put_vol)
+                    };
+                    if otm.is_nan() {
+                        itm
+                    } else {
+                        otm
+                    }
+                };
+            }
+        }
+
+        Ok(VolatilitySurface {
+            symbol: call.symbol.clone(),
+            expirations,
+            strikes,
+            volatilities,
+            timestamp: call.timestamp.max(put.timestamp),
+            version: 1,
+        })
+    }
+
+    pub fn to_moneyness(&self, forward_curve: &ForwardCurve) -> Result<(Array2<f64>, Array2<f64>)> {
+        let now = chrono::Utc::now();
+        let mut log_moneyness =
+            Array2::from_elem((self.expirations.len(), self.strikes.len()), f64::NAN);
+        for (i, &expiration) in self.expirations.iter().enumerate() {
+            let forward = forward_at(forward_curve, now, expiration)?;
+            for (j, &strike) in self.strikes.iter().enumerate() {
+                if strike > 0.0 {
+                    log_moneyness[[i, j]] = (strike / forward).ln();
+                }
+            }
+        }
+        Ok((log_moneyness, self.volatilities.clone()))
+    }
+
+    pub fn mark_event_expirations(
+        &self,
+        event_dates: &[chrono::NaiveDate],
+    ) -> Vec<(chrono::DateTime<chrono::Utc>, bool)> {
+        let today = chrono::Utc::now().date_naive();
+        self.expirations
+            .iter()
+            .map(|&expiration| {
+                let expiry = expiration.date_naive();
+                let spans = event_dates.iter().any(|&d| d > today && d <= expiry);
+                (expiration, spans)
+            })
+            .collect()
+    }
+
+    pub fn atm_term_structure(&self, forward_curve: &ForwardCurve) -> Vec<(f64, f64)> {
+        let now = chrono::Utc::now();
+        self.expirations
+            .iter()
+            .filter_map(|&expiration| {
+                let forward = forward_at(forward_curve, now, expiration).ok()?;
+                self.interpolate_smile(expiration, forward)
+                    .ok()
+                    .map(|vol| (years_between(now, expiration), vol))
+            })
+            .collect()
+    }
+
+    fn strike_for_delta(
+        &self,
+        expiration: chrono::DateTime<chrono::Utc>,
+        spot: f64,
+        t: f64,
+        r: f64,
+        target: f64,
+        is_call: bool,
+    ) -> Option<(f64, f64)> {
+        let delta_at = |k: f64| {
+            self.interpolate_smile(expiration, k)
+                .ok()
+                .map(|vol| (delta(spot, k, t, r, vol, is_call) - target, vol))
+        };
+
+        let (mut lo, mut hi) = (*self.strikes.first()?, *self.strikes.last()?);
+        let (f_lo, _) = delta_at(lo)?;
+        let (f_hi, _) = delta_at(hi)?;
+        if f_lo.signum() == f_hi.signum() {
+            return None;
+        }
+
+        let mut mid = (lo + hi) / 2.0;
+        for _ in 0..DELTA_STRIKE_MAX_ITER {
+            mid = (lo + hi) / 2.0;
+            let (f_mid, _) = delta_at(mid)?;
+            if f_mid.abs() < DELTA_STRIKE_TOL || hi - lo < DELTA_STRIKE_TOL {
+                break;
+            }
+            if f_mid.signum() == f_lo.signum() {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        delta_at(mid).map(|(_, vol)| (mid, vol))
+    }
+
+    pub fn skew_metrics(&self, spot: f64, r: f64, forward_curve: &ForwardCurve) -> Vec<SkewMetric> {
+        let now = chrono::Utc::now();
+        self.expirations
+            .iter()
+            .zip(self.times_to_expiration())
+            .filter(|(_, t)| *t > 0.0)
+            .filter_map(|(&expiration, t)| {
+                let (_, vol_25c) =
+                    self.strike_for_delta(expiration, spot, t, r, SKEW_DELTA, true)?;
+                let (_, vol_25p) =
+                    self.strike_for_delta(expiration, spot, t, r, -SKEW_DELTA, false)?;
+                let forward = forward_at(forward_curve, now, expiration).ok()?;
+                let vol_atm = self.interpolate_smile(expiration, forward).ok()?;
+                Some(SkewMetric {
+                    expiration,
+                    rr_25: vol_25c - vol_25p,
+                    bf_25: (vol_25c + vol_25p) / 2.0 - vol_atm,
+                })
+            })
+            .collect()
+    }
+
+    pub fn atm_vol_at_tenor(&self, tenor_days: i64, forward_curve: &ForwardCurve) -> Result<f64> {
+        let cm = self.constant_maturity(&[tenor_days])?;
+        let forward = forward_curve.forward(tenor_days as f64 / CALENDAR_DAYS_PER_YEAR);
+        let points: Vec<(f64, f64)> = cm
+            .strikes
+            .iter()
+            .zip(cm.volatilities.row(0).iter())
+            .filter(|(_, v)| v.is_finite())
+            .map(|(&k, &v)| (k, v))
+            .collect();
+
+        let upper = points.iter().position(|&(k, _)| k >= forward);
+        let atm = match upper {
+            Some(0) if points[0].0 == forward => Some(points[0].1),
+            Some(0) | None => None,
+            Some(idx) => {
+                let (k0, v0) = points[idx - 1];
+                let (k1, v1) = points[idx];
+                Some(v0 + (v1 - v0) * (forward - k0) / (k1 - k0))
+            }
+        };
+
+        atm.ok_or_else(|| {
+            OptionsError::VolatilityError(format!(
+                "Forward {} is outside the strikes with {}-day volatilities",
+                forward, tenor_days
+            ))
+        })
+    }
+
+    pub fn reanchor_atm(
+        &self,
+        target_30d_atm: f64,
+        forward_curve: &ForwardCurve,
+        shift: AnchorShift,
+    ) -> Result<VolatilitySurface> {
+        if target_30d_atm <= 0.0 || !target_30d_atm.is_finite() {
+            return Err(OptionsError::VolatilityError(format!(
+                "Invalid target ATM volatility {}",
+                target_30d_atm
+            )));
+        }
+
+        let current = self.atm_vol_at_tenor(30, forward_curve)?;
+        if current <= 0.0 {
+            return Err(OptionsError::VolatilityError(
+                "Current 30-day ATM volatility is not positive".to_string(),
+            ));
+        }
+
+        let volatilities = match shift {
+            AnchorShift::Vol => {
+                let delta = target_30d_atm - current;
+                self.volatilities
+                    .mapv(|v| if v.is_nan() { v } else { (v + delta).max(0.0) })
+            }
+            AnchorShift::Variance => {
+                let scale = target_30d_atm / current;
+                self.volatilities.mapv(|v| v * scale)
+            }
+        };
+
+        Ok(Self {
+            symbol: self.symbol.clone(),
+            expirations: self.expirations.clone(),
+            strikes: self.strikes.clone(),
+            volatilities,
+            timestamp: chrono::Utc::now(),
+            version: 1,
+        })
+    }
+
+    pub fn summary(&self) -> SurfaceSummary {
+        let filled: Vec<f64> = self
+            .volatilities
+            .iter()
+            .copied()
+            .filter(|v| v.is_finite())
+            .collect();
+
+        let (min_iv, max_iv, mean_iv) = if filled.is_empty() {
+            (None, None, None)
+        } else {
+            let min = filled.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = filled.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let mean = filled.iter().sum::<f64>() / filled.len() as f64;
+            (Some(min), Some(max), Some(mean))
+        };
+
+        let strike_range = match (self.strikes.first(), self.strikes.last()) {
+            (Some(&lo), Some(&hi)) => Some((lo, hi)),
+            _ => None,
+        };
+        let expiry_range = match (self.expirations.first(), self.expirations.last()) {
+            (Some(&first), Some(&last)) => Some((first, last)),
+            _ => None,
+        };
+
+        let atm_iv_estimate = strike_range.and_then(|(lo, hi)| {
+            let center = 0.5 * (lo + hi);
+            self.volatilities.rows().into_iter().find_map(|row| {
+                self.strikes
+                    .iter()
+                    .zip(row.iter())
+                    .filter(|(_, v)| v.is_finite())
+                    .min_by(|(a, _), (b, _)| {
+                        (*a - center)
+                            .abs()
+                            .partial_cmp(&(*b - center).abs())
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    })
+                    .map(|(_, &v)| v)
+            })
+        });
+
+        SurfaceSummary {
+            n_expiries: self.expirations.len(),
+            n_strikes: self.strikes.len(),
+            fill_ratio: self.grid_quality().fill_ratio,
+            min_iv,
+            max_iv,
+            mean_iv,
+            atm_iv_estimate,
+            strike_range,
+            expiry_range,
+        }
+    }
+
+    pub fn resample(&self, n_expiries: usize, n_strikes: usize) -> Result<VolatilitySurface> {
+        if self.expirations.is_empty() || self.strikes.is_empty() {
+            return Err(OptionsError::VolatilityError(
+                "Cannot resample an empty volatility surface".to_string(),
+            ));
+        }
+        if n_expiries < 2 || n_strikes < 2 {
+            return Err(OptionsError::VolatilityError(format!(
+                "Resample grid must be at least 2x2, got {}x{}",
+                n_expiries, n_strikes
+            )));
+        }
+
+        let first_exp = self.expirations[0];
+        let offsets: Vec<f64> = self
+            .expirations
+            .iter()
+            .map(|&e| (e - first_exp).num_seconds() as f64)
+            .collect();
+        let span = offsets[offsets.len() - 1];
+        let (k_min, k_max) = (self.strikes[0], self.strikes[self.strikes.len() - 1]);
+
+        let expirations: Vec<chrono::DateTime<chrono::Utc>> = (0..n_expiries)
+            .map(|i| {
+                let secs = span * i as f64 / (n_expiries - 1) as f64;
+                first_exp + chrono::Duration::seconds(secs.round() as i64)
+            })
+            .collect();
+        let strikes: Vec<f64> = (0..n_strikes)
+            .map(|j| k_min + (k_max - k_min) * j as f64 / (n_strikes - 1) as f64)
+            .collect();
+
+        let mut volatilities = Array2::from_elem((n_expiries, n_strikes), f64::NAN);
+        for (i, exp) in expirations.iter().enumerate() {
+            let (e1, e2, t) = grid_bracket(&offsets, (*exp - first_exp).num_seconds() as f64);
+            for (j, &k) in strikes.iter().enumerate() {
+                let (s1, s2, u) = grid_bracket(&self.strikes, k);
+                let corners = [
+                    ((1.0 - t) * (1.0 - u), self.volatilities[[e1, s1]]),
+                    ((1.0 - t) * u, self.volatilities[[e1, s2]]),
+                    (t * (1.0 - u), self.volatilities[[e2, s1]]),
+                    (t * u, self.volatilities[[e2, s2]]),
+                ];
+                let (weight, sum) = corners
+                    .iter()
+                    .filter(|(_, v)| !v.is_nan())
+                    .fold((0.0, 0.0), |(w, acc), (cw, v)| (w + cw, acc + cw * v));
+                if weight > 0.0 {
+                    volatilities[[i, j]] = sum / weight;
+                }
+            }
+        }
+
+        Ok(Self {
+            symbol: self.symbol.clone(),
+            expirations,
+            strikes,
+            volatilities,
+            timestamp: self.timestamp,
+            version: self.version,
+        })
+    }
+
+    pub fn term_structure_shape(&self, forward_curve: &ForwardCurve) -> Result<TermShape> {
+        let points: Vec<(f64, f64)> = TERM_SHAPE_TENORS
+            .iter()
+            .filter_map(|&days| {
+                self.atm_vol_at_tenor(days, forward_curve)
+                    .ok()
+                    .map(|v| (days as f64 / CALENDAR_DAYS_PER_YEAR, v))
+            })
+            .collect();
+
+        if points.len() < 2 {
+            return Err(OptionsError::VolatilityError(format!(
+                "At least two ATM tenors are required for term structure shape, found {}",
+                points.len()
+            )));
+        }
+
+        let n = points.len() as f64;
+        let mean_t = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_v = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let stt: f64 = points.iter().map(|p| (p.0 - mean_t).powi(2)).sum();
+        let stv: f64 = points.iter().map(|p| (p.0 - mean_t) * (p.1 - mean_v)).sum();
+        let slope = if stt > 0.0 { stv / stt } else { 0.0 };
+
+        let curvature = if points.len() >= 3 {
+            let (mut s0, mut s1, mut s2, mut s3, mut s4) = (0.0, 0.0, 0.0, 0.0, 0.0);
+            let (mut y0, mut y1, mut y2) = (0.0, 0.0, 0.0);
+            for &(t, v) in &points {
+                s0 += 1.0;
+                s1 += t;
+                s2 += t * t;
+                s3 += t * t * t;
+                s4 += t * t * t * t;
+                y0 += v;
+                y1 += t * v;
+                y2 += t * t * v;
+            }
+            let det = |m: [[f64; 3]; 3]| {
+                m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+                    - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+                    + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+            };
+            let d = det([[s0, s1, s2], [s1, s2, s3], [s2, s3, s4]]);
+            if d.abs() > f64::EPSILON {
+                2.0 * det([[s0, s1, y0], [s1, s2, y1], [s2, s3, y2]]) / d
+            } else {
+                0.0
+            }
+        } else {
+            0.0
+        };
+
+        let front_vol = points[0].1;
+        let back_vol = points[points.len() - 1].1;
+
+        Ok(TermShape {
+            front_vol,
+            back_vol,
+            slope,
+            curvature,
+            is_backwardated: front_vol > back_vol,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OptionType;
+    use crate::utils::price_with_yield;
+
+    fn quote_priced_with_yield(is_call: bool, r: f64, q: f64, sigma: f64) -> (OptionQuote, f64) {
+        let expiration = chrono::Utc::now() + chrono::Duration::days(90);
+        let option_type = if is_call {
+            OptionType::Call
+        } else {
+            OptionType::Put
+        };
+        let contract =
+            OptionContract::new("SPY".to_string(), option_type, 500.0, expiration).unwrap();
+        let t = contract.time_to_expiration();
+        let price = price_with_yield(510.0, 500.0, t, r, q, sigma, is_call);
+        let quote = OptionQuote::new(contract, price, price, price, 0, 0, 510.0);
+        (quote, t)
+    }
+
+    #[test]
+    fn implied_volatility_uses_separate_dividend_yield() {
+        let (r, q, sigma) = (0.05, 0.02, 0.25);
+        for is_call in [true, false] {
+            let (quote, t) = quote_priced_with_yield(is_call, r, q, sigma);
+            let expected_delta = delta_with_yield(510.0, 500.0, t, r, q, sigma, is_call);
+
+            let iv = ImpliedVolatility::from_quote(&quote, r, q).unwrap();
+            assert!((iv.value - sigma).abs() < 1e-4);
+            assert!((iv.delta - expected_delta).abs() < 1e-4);
+
+            let iv = ImpliedVolatility::from_quote_bid_ask(&quote, r, q).unwrap();
+            assert!((iv.value - sigma).abs() < 1e-4);
+            assert!((iv.delta - expected_delta).abs() < 1e-4);
+
+            let iv = batch_implied_volatility_quotes(std::slice::from_ref(&quote), r, q)
+                .pop()
+                .unwrap()
+                .unwrap();
+            assert!((iv.value - sigma).abs() < 1e-4);
+            assert!((iv.delta - expected_delta).abs() < 1e-4);
+        }
+    }
+
+    fn surface(days: &[i64], strikes: &[f64], vols: Vec<f64>) -> VolatilitySurface {
+        let now = chrono::Utc::now();
+        VolatilitySurface {
+            symbol: "SPY".to_string(),
+            expirations: days
+                .iter()
+                .map(|&d| now + chrono::Duration::days(d))
+                .collect(),
+            strikes: strikes.to_vec(),
+            volatilities: Array2::from_shape_vec((days.len(), strikes.len()), vols).unwrap(),
+            timestamp: now,
+            version: 0,
+        }
+    }
+
+    fn priced_quote(is_call: bool, strike: f64, days: i64, r: f64, sigma: f64) -> OptionQuote {
+        let option_type = if is_call {
+            OptionType::Call
+        } else {
+            OptionType::Put
+        };
+        let expiration = chrono::Utc::now() + chrono::Duration::days(days);
+        let contract =
+            OptionContract::new("SPY".to_string(), option_type, strike, expiration).unwrap();
+        let price = crate::utils::price(
+            510.0,
+            strike,
+            contract.time_to_expiration(),
+            r,
+            sigma,
+            is_call,
+        );
+        OptionQuote::new(contract, price, price, price, 100, 100, 510.0)
+    }
+
+    fn chain(r: f64) -> Vec<OptionQuote> {
+        let mut quotes = Vec::new();
+        for (i, days) in [14, 45, 120].into_iter().enumerate() {
+            for (j, strike) in [460.0, 490.0, 510.0, 530.0, 560.0].into_iter().enumerate() {
+                let sigma = 0.18 + 0.01 * i as f64 + 0.02 * (j as f64 - 2.0).abs();
+                quotes.push(priced_quote(strike >= 510.0, strike, days, r, sigma));
+            }
+        }
+        quotes
+    }
+
+    #[test]
+    fn calibrate_svi_recovers_each_slice() {
+        let known = [
+            SviParams {
+                a: 0.004,
+                b: 0.05,
+                rho: -0.5,
+                m: 0.02,
+                sigma: 0.1,
+            },
+            SviParams {
+                a: 0.02,
+                b: 0.08,
+                rho: -0.3,
+                m: 0.0,
+                sigma: 0.2,
+            },
+        ];
+        let curve = ForwardCurve::flat(500.0, 0.05, 0.01);
+        let strikes: Vec<f64> = (0..21).map(|i| 350.0 + 15.0 * i as f64).collect();
+
+        let mut vol_surface = surface(&[30, 180], &strikes, vec![0.0; 2 * strikes.len()]);
+        let times = vol_surface.times_to_expiration();
+        let forwards: Vec<f64> = times.iter().map(|&t| curve.forward(t)).collect();
+        for (i, params) in known.iter().enumerate() {
+            for (j, &strike) in strikes.iter().enumerate() {
+                let k = (strike / forwards[i]).ln();
+                vol_surface.volatilities[[i, j]] = params.implied_volatility(k, times[i]).unwrap();
+            }
+        }
+
+        let fitted = vol_surface.calibrate_svi(&curve).unwrap();
+        for (fit, params) in fitted.iter().zip(&known) {
+            assert!((fit.a - params.a).abs() < 1e-4, "{:?}", fit);
+            assert!((fit.b - params.b).abs() < 1e-4, "{:?}", fit);
+            assert!((fit.rho - params.rho).abs() < 1e-3, "{:?}", fit);
+            assert!((fit.m - params.m).abs() < 1e-3, "{:?}", fit);
+            assert!((fit.sigma - params.sigma).abs() < 1e-3, "{:?}", fit);
+        }
+
+        assert!(vol_surface
+            .calibrate_svi(&ForwardCurve::flat(0.0, 0.05, 0.01))
+            .is_err());
+    }
+
+    #[test]
+    fn resample_keeps_the_original_nodes() {
+        let vol_surface = surface(
+            &[30, 60, 90],
+            &[90.0, 100.0, 110.0],
+            vec![0.30, 0.26, 0.25, 0.28, 0.24, 0.23, 0.27, 0.23, 0.22],
+        );
+
+        let resampled = vol_surface.resample(5, 5).unwrap();
+        assert_eq!(resampled.volatilities.dim(), (5, 5));
+        assert_eq!(resampled.strikes, vec![90.0, 95.0, 100.0, 105.0, 110.0]);
+        assert_eq!(resampled.expirations.len(), 5);
+        for (i, original_i) in [(0, 0), (2, 1), (4, 2)] {
+            assert_eq!(
+                resampled.expirations[i],
+                vol_surface.expirations[original_i]
+            );
+            for (j, original_j) in [(0, 0), (2, 1), (4, 2)] {
+                let got = resampled.volatilities[[i, j]];
+                let want = vol_surface.volatilities[[original_i, original_j]];
+                assert!((got - want).abs() < 1e-12, "{} != {}", got, want);
+            }
+        }
+        let between = resampled.volatilities[[1, 1]];
+        assert!((between - (0.30 + 0.26 + 0.28 + 0.24) / 4.0).abs() < 1e-12);
+
+        assert!(vol_surface.resample(1, 5).is_err());
+    }
+
+    #[test]
+    fn term_structure_shape_classifies_contango_backwardation_and_humps() {
+        let days = [6, 30, 60, 90, 180, 366];
+        let curve = ForwardCurve::flat(100.0, 0.0, 0.0);
+        let shape = |atm: [f64; 6]| {
+            let vols = atm.iter().flat_map(|&v| [v; 3]).collect();
+            surface(&days, &[90.0, 100.0, 110.0], vols)
+                .term_structure_shape(&curve)
+                .unwrap()
+        };
+
+        let contango = shape([0.15, 0.17, 0.19, 0.20, 0.22, 0.24]);
+        assert!(contango.slope > 0.0, "{:?}", contango);
+        assert!(!contango.is_backwardated);
+        assert!(contango.back_vol > contango.front_vol);
+
+        let backwardation = shape([0.45, 0.38, 0.33, 0.30, 0.27, 0.25]);
+        assert!(backwardation.slope < 0.0, "{:?}", backwardation);
+        assert!(backwardation.is_backwardated);
+
+        let humped = shape([0.18, 0.22, 0.25, 0.26, 0.23, 0.18]);
+        assert!(humped.curvature < 0.0, "{:?}", humped);
+        assert!(humped.curvature < contango.curvature);
+        assert!(humped.curvature < backwardation.curvature);
+
+        assert!(surface(&[30], &[100.0], vec![0.2])
+            .term_structure_shape(&curve)
+            .is_err());
+    }
+
+    #[test]
+    fn filtered_slice_drops_nan_and_applies_the_window() {
+        let nan = f64::NAN;
+        let vol_surface = surface(
+            &[30, 60],
+            &[80.0, 90.0, 100.0, 110.0, 120.0],
+            vec![0.30, nan, 0.25, 0.24, 0.26, 0.29, 0.27, 0.24, nan, 0.25],
+        );
+        let expiration = vol_surface.expirations[0];
+
+        let (strikes, vols) = vol_surface
+            .slice_by_expiration_filtered(expiration, None)
+            .unwrap();
+        assert_eq!(strikes.to_vec(), vec![80.0, 100.0, 110.0, 120.0]);
+        assert_eq!(vols.to_vec(), vec![0.30, 0.25, 0.24, 0.26]);
+
+        let window = MoneynessWindow {
+            spot: 100.0,
+            min: 0.85,
+            max: 1.1,
+        };
+        let (strikes, vols) = vol_surface
+            .slice_by_expiration_filtered(expiration, Some(window))
+            .unwrap();
+        assert_eq!(strikes.to_vec(), vec![100.0, 110.0]);
+        assert_eq!(vols.to_vec(), vec![0.25, 0.24]);
+
+        let (strikes, _) = vol_surface
+            .slice_by_expiration_filtered(vol_surface.expirations[1], Some(window))
+            .unwrap();
+        assert_eq!(strikes.to_vec(), vec![90.0, 100.0]);
+    }
+
+    #[test]
+    fn interpolate_smile_hits_nodes_and_keeps_monotone_smiles_monotone() {
+        let strikes: [f64; 7] = [400.0, 450.0, 480.0, 500.0, 520.0, 560.0, 600.0];
+        let vols: Vec<f64> = strikes.iter().map(|&k| 0.6 * (-k / 400.0).exp()).collect();
+        let vol_surface = surface(&[30], &strikes, vols.clone());
+        let expiration = vol_surface.expirations[0];
+
+        for (&k, &v) in strikes.iter().zip(&vols) {
+            assert!((vol_surface.interpolate_smile(expiration, k).unwrap() - v).abs() < 1e-12);
+        }
+
+        let mut previous = f64::INFINITY;
+        for i in 0..=400 {
+            let v = vol_surface
+                .interpolate_smile(expiration, 400.0 + 0.5 * i as f64)
+                .unwrap();
+            assert!(v < previous);
+            previous = v;
+        }
+    }
+
+    #[test]
+    fn interpolate_smile_skips_nan_strikes() {
+        let vol_surface = surface(&[30], &[90.0, 100.0, 110.0], vec![0.3, f64::NAN, 0.2]);
+        let expiration = vol_surface.expirations[0];
+        assert!((vol_surface.interpolate_smile(expiration, 100.0).unwrap() - 0.25).abs() < 1e-12);
+
+        let sparse = surface(&[30], &[90.0, 100.0], vec![0.3, f64::NAN]);
+        assert!(sparse.interpolate_smile(expiration, 95.0).is_err());
+    }
+
+    fn extrapolation_surface() -> VolatilitySurface {
+        surface(
+            &[30, 90],
+            &[90.0, 100.0, 110.0],
+            vec![0.30, 0.25, 0.22, 0.28, 0.24, 0.21],
+        )
+    }
+
+    #[test]
+    fn error_mode_rejects_points_outside_the_grid() {
+        let vol_surface = extrapolation_surface();
+        let (near, far) = (vol_surface.expirations[0], vol_surface.expirations[1]);
+        let mid = near + chrono::Duration::days(30);
+        let interp = |e, k| vol_surface.interpolate_ext(e, k, ExtrapMode::Error);
+
+        assert!((interp(mid, 95.0).unwrap() - 0.2675).abs() < 1e-3);
+        assert!(interp(mid, 80.0).is_err());
+        assert!(interp(mid, 130.0).is_err());
+        assert!(interp(near - chrono::Duration::days(20), 95.0).is_err());
+        assert!(interp(far + chrono::Duration::days(60), 95.0).is_err());
+    }
+
+    #[test]
+    fn clamp_edge_mode_holds_the_boundary_values() {
+        let mut vol_surface = extrapolation_surface();
+        let (near, far) = (vol_surface.expirations[0], vol_surface.expirations[1]);
+        let before = near - chrono::Duration::days(20);
+        let after = far + chrono::Duration::days(60);
+        let interp =
+            |s: &VolatilitySurface, e, k| s.interpolate_ext(e, k, ExtrapMode::ClampEdge).unwrap();
+
+        assert!((interp(&vol_surface, near, 80.0) - 0.30).abs() < 1e-12);
+        assert!((interp(&vol_surface, near, 130.0) - 0.22).abs() < 1e-12);
+        assert!((interp(&vol_surface, before, 100.0) - 0.25).abs() < 1e-12);
+        assert!((interp(&vol_surface, after, 100.0) - 0.24).abs() < 1e-12);
+        assert!((interp(&vol_surface, before, 80.0) - 0.30).abs() < 1e-12);
+        assert!((interp(&vol_surface, after, 130.0) - 0.21).abs() < 1e-12);
+
+        vol_surface.volatilities[[0, 0]] = f64::NAN;
+        assert!(vol_surface
+            .interpolate_ext(near, 80.0, ExtrapMode::ClampEdge)
+            .is_err());
+    }
+
+    #[test]
+    fn flat_extend_mode_extends_the_nearest_finite_values() {
+        let mut vol_surface = extrapolation_surface();
+        let (near, far) = (vol_surface.expirations[0], vol_surface.expirations[1]);
+        let before = near - chrono::Duration::days(20);
+        let after = far + chrono::Duration::days(60);
+        let interp =
+            |s: &VolatilitySurface, e, k| s.interpolate_ext(e, k, ExtrapMode::FlatExtend).unwrap();
+
+        assert!((interp(&vol_surface, near, 80.0) - 0.30).abs() < 1e-12);
+        assert!((interp(&vol_surface, far, 130.0) - 0.21).abs() < 1e-12);
+        assert!((interp(&vol_surface, before, 105.0) - 0.235).abs() < 1e-12);
+        assert!((interp(&vol_surface, after, 95.0) - 0.26).abs() < 1e-12);
+
+        // Missing wing quotes fall back to the nearest finite strike.
+        vol_surface.volatilities[[0, 0]] = f64::NAN;
+        assert!((interp(&vol_surface, near, 80.0) - 0.25).abs() < 1e-12);
+        assert!((interp(&vol_surface, before, 80.0) - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn variance_interpolation_is_monotone_in_time() {
+        let vol_surface = surface(
+            &[30, 120],
+            &[450.0, 500.0, 550.0],
+            vec![0.28, 0.25, 0.27, 0.24, 0.22, 0.23],
+        );
+        let variance = vol_surface.to_total_variance();
+        let times = vol_surface.times_to_expiration();
+        assert!((variance[[1, 1]] - 0.22 * 0.22 * times[1]).abs() < 1e-12);
+
+        let first = vol_surface.expirations[0];
+        for strike in [450.0, 480.0, 500.0, 550.0] {
+            let mut previous = 0.0;
+            for day in 0..=90 {
+                let expiration = first + chrono::Duration::days(day);
+                let sigma = vol_surface
+                    .interpolate_variance(expiration, strike)
+                    .unwrap();
+                let w = sigma * sigma * years_between(chrono::Utc::now(), expiration);
+                assert!(w > previous, "strike {} day {}", strike, day);
+                previous = w;
+            }
+        }
+
+        let last = vol_surface.expirations[1];
+        assert!((vol_surface.interpolate_variance(last, 500.0).unwrap() - 0.22).abs() < 1e-6);
+        assert!(vol_surface
+            .interpolate_variance(last + chrono::Duration::days(1), 500.0)
+            .is_err());
+    }
+
+    #[test]
+    fn butterfly_check_flags_a_dented_smile() {
+        let strikes = [440.0, 470.0, 500.0, 530.0, 560.0];
+        let clean = surface(&[60], &strikes, vec![0.30, 0.27, 0.25, 0.24, 0.245]);
+        assert!(clean.butterfly_violations(500.0, 0.04).is_empty());
+
+        let dented = surface(&[60], &strikes, vec![0.30, 0.27, 0.80, 0.24, 0.245]);
+        let violations = dented.butterfly_violations(500.0, 0.04);
+        assert_eq!(violations, vec![(dented.expirations[0], 500.0)]);
+    }
+
+    #[test]
+    fn calendar_check_flags_falling_total_variance() {
+        let vol_surface = surface(
+            &[30, 90],
+            &[450.0, 500.0, 550.0],
+            vec![0.30, 0.40, f64::NAN, 0.25, 0.20, 0.22],
+        );
+        assert_eq!(
+            vol_surface.calendar_violations(),
+            vec![(500.0, vol_surface.expirations[1])]
+        );
+    }
+
+    #[test]
+    fn atm_term_structure_reads_the_forward_column() {
+        let vol_surface = surface(
+            &[30, 90, 180],
+            &[90.0, 100.0, 110.0],
+            vec![0.32, 0.28, 0.27, 0.29, 0.25, 0.24, 0.27, 0.23, 0.22],
+        );
+        let times = vol_surface.times_to_expiration();
+
+        let term = vol_surface.atm_term_structure(&ForwardCurve::flat(100.0, 0.0, 0.0));
+        assert_eq!(term.len(), 3);
+        for ((t, vol), (expected_t, expected_vol)) in
+            term.iter().zip(times.iter().zip([0.28, 0.25, 0.23]))
+        {
+            assert!((t - expected_t).abs() < 1e-6);
+            assert!(
+                (vol - expected_vol).abs() < 1e-12,
+                "{} != {}",
+                vol,
+                expected_vol
+            );
+        }
+
+        // A forward that drifts up the strikes moves the ATM point with it.
+        let drifting = ForwardCurve::from_points(&[(times[0], 90.0), (times[2], 110.0)]).unwrap();
+        let term = vol_surface.atm_term_structure(&drifting);
+        assert!((term[0].1 - 0.32).abs() < 1e-6);
+        assert!((term[2].1 - 0.22).abs() < 1e-6);
+    }
+
+    #[test]
+    fn skew_metrics_on_flat_and_symmetric_smiles() {
+        let (spot, r) = (500.0, 0.03);
+        let curve = ForwardCurve::flat(spot, r, 0.0);
+        let strikes: Vec<f64> = (0..41).map(|i| 400.0 + 5.0 * i as f64).collect();
+
+        let flat = surface(&[30], &strikes, vec![0.2; strikes.len()]);
+        let metric = flat.skew_metrics(spot, r, &curve)[0];
+        assert!(metric.rr_25.abs() < 1e-6);
+        assert!(metric.bf_25.abs() < 1e-6);
+
+        let mut smile = surface(&[30], &strikes, vec![0.0; strikes.len()]);
+        let forward = curve.forward(smile.times_to_expiration()[0]);
+        for (j, &k) in strikes.iter().enumerate() {
+            smile.volatilities[[0, j]] = 0.2 + 2.0 * (k / forward).ln().powi(2);
+        }
+        let metric = smile.skew_metrics(spot, r, &curve)[0];
+        assert_eq!(metric.expiration, smile.expirations[0]);
+        assert!(metric.rr_25.abs() < 2e-3, "{:?}", metric);
+        assert!(metric.bf_25 > 0.0, "{:?}", metric);
+    }
+
+    #[test]
+    fn fill_missing_patches_holes_and_empty_rows() {
+        let nan = f64::NAN;
+        let mut vol_surface = surface(
+            &[7, 30, 60],
+            &[480.0, 500.0, 520.0],
+            vec![0.30, 0.28, 0.27, nan, nan, nan, 0.25, nan, 0.23],
+        );
+
+        assert_eq!(vol_surface.fill_missing(), 4);
+        let expected = [0.30, 0.28, 0.27, 0.275, 0.28, 0.25, 0.25, 0.24, 0.23];
+        for (v, e) in vol_surface.volatilities.iter().zip(expected) {
+            assert!((v - e).abs() < 1e-12, "{} != {}", v, e);
+        }
+        assert_eq!(vol_surface.fill_missing(), 0);
+
+        let mut empty = surface(&[7, 30], &[480.0, 500.0], vec![nan; 4]);
+        assert_eq!(empty.fill_missing(), 0);
+        assert!(empty.volatilities.iter().all(|v| v.is_nan()));
+    }
+
+    #[test]
+    fn merge_otm_of_agreeing_surfaces_is_unchanged() {
+        let call = surface(
+            &[30, 90],
+            &[480.0, 500.0, 520.0],
+            vec![0.26, 0.24, 0.23, 0.25, 0.235, 0.225],
+        );
+        let put = call.clone();
+        let curve = ForwardCurve::flat(501.0, 0.0, 0.0);
+
+        let merged = VolatilitySurface::merge_otm(&call, &put, &curve).unwrap();
+        assert_eq!(merged.expirations, call.expirations);
+        assert_eq!(merged.strikes, call.strikes);
+        assert_eq!(merged.volatilities, call.volatilities);
+    }
+
+    #[test]
+    fn merge_otm_picks_the_out_of_the_money_side() {
+        let call = surface(&[30], &[480.0, 500.0, 520.0], vec![0.20; 3]);
+        let mut put = call.clone();
+        put.strikes = vec![460.0, 480.0, 500.0];
+        put.volatilities.fill(0.30);
+        let curve = ForwardCurve::flat(500.0, 0.0, 0.0);
+
+        let merged = VolatilitySurface::merge_otm(&call, &put, &curve).unwrap();
+        assert_eq!(merged.strikes, vec![460.0, 480.0, 500.0, 520.0]);
+        let row: Vec<f64> = merged.volatilities.row(0).to_vec();
+        assert_eq!(row, vec![0.30, 0.30, 0.25, 0.20]);
+
+        let bad_curve = ForwardCurve::flat(f64::NAN, 0.0, 0.0);
+        assert!(VolatilitySurface::merge_otm(&call, &put, &bad_curve).is_err());
+    }
+
+    #[test]
+    fn to_moneyness_maps_the_forward_to_zero() {
+        let vol_surface = surface(&[30, 90], &[450.0, 500.0, 550.0], vec![0.25; 6]);
+        let times = vol_surface.times_to_expiration();
+        let curve = ForwardCurve::from_points(&[(times[0], 500.0), (times[1], 550.0)]).unwrap();
+
+        let (log_moneyness, vols) = vol_surface.to_moneyness(&curve).unwrap();
+        assert_eq!(vols, vol_surface.volatilities);
+        assert!(log_moneyness[[0, 1]].abs() < 1e-9);
+        assert!(log_moneyness[[1, 2]].abs() < 1e-9);
+        assert!((log_moneyness[[0, 0]] - (0.9f64).ln()).abs() < 1e-9);
+
+        let bad_curve = ForwardCurve::flat(-1.0, 0.0, 0.0);
+        assert!(vol_surface.to_moneyness(&bad_curve).is_err());
+    }
+
+    #[test]
+    fn batch_quotes_match_serial_solves() {
+        let r = 0.04;
+        let mut quotes = chain(r);
+        quotes.push(priced_quote(true, 500.0, -1, r, 0.2));
+        quotes[3].bid = 0.0;
+        quotes[3].ask = 0.0;
+
+        let batch = batch_implied_volatility_quotes(&quotes, r, 0.0);
+        assert_eq!(batch.len(), quotes.len());
+        for (quote, iv) in quotes.iter().zip(&batch) {
+            match (ImpliedVolatility::from_quote(quote, r, 0.0), iv) {
+                (Ok(serial), Ok(iv)) => {
+                    assert_eq!(iv.contract.option_symbol, serial.contract.option_symbol);
+                    assert_eq!(iv.value, serial.value);
+                    assert_eq!(iv.delta, serial.delta);
+                }
+                (Err(_), Err(_)) => {}
+                (serial, iv) => panic!("serial {:?} vs batch {:?}", serial, iv),
+            }
+        }
+        assert!(batch[3].is_err());
+        assert!(batch.last().unwrap().is_err());
+
+        let serial: Vec<ImpliedVolatility> = quotes
+            .iter()
+            .filter_map(|q| ImpliedVolatility::from_quote(q, r, 0.0).ok())
+            .collect();
+        let expected = VolatilitySurface::new("SPY".to_string(), &serial).unwrap();
+        let built =
+            VolatilitySurface::from_quotes_with_curve("SPY".to_string(), &quotes, |_| r, 0.0)
+                .unwrap();
+        assert_eq!(built.expirations, expected.expirations);
+        assert_eq!(built.strikes, expected.strikes);
+        for (a, b) in built.volatilities.iter().zip(expected.volatilities.iter()) {
+            assert!(a == b || (a.is_nan() && b.is_nan()), "{} != {}", a, b);
+        }
+    }
+
+    #[test]
+    fn marks_only_expirations_spanning_the_event() {
+        let vol_surface = surface(&[7, 30, 60], &[100.0], vec![0.2; 3]);
+        let today = chrono::Utc::now().date_naive();
+        let flags = |events: &[chrono::NaiveDate]| -> Vec<bool> {
+            vol_surface
+                .mark_event_expirations(events)
+                .into_iter()
+                .map(|(_, spans)| spans)
+                .collect()
+        };
+
+        let earnings = today + chrono::Duration::days(20);
+        assert_eq!(flags(&[earnings]), [false, true, true]);
+        let marked = vol_surface.mark_event_expirations(&[earnings]);
+        assert_eq!(marked[1].0, vol_surface.expirations[1]);
+
+        // An event on the expiry date itself still lands inside that expiry.
+        let on_expiry = vol_surface.expirations[0].date_naive();
+        assert_eq!(flags(&[on_expiry]), [true, true, true]);
+
+        let past = today - chrono::Duration::days(3);
+        let beyond = today + chrono::Duration::days(90);
+        assert_eq!(flags(&[past, today, beyond]), [false, false, false]);
+        assert_eq!(flags(&[]), [false, false, false]);
+    }
+
+    #[test]
+    fn from_quotes_filtered_drops_wide_and_stale_quotes() {
+        let r = 0.04;
+        let mut quotes = chain(r);
+        let wide = 0;
+        quotes[wide].bid *= 0.5;
+        quotes[wide].ask *= 1.5;
+        let stale = 7;
+        quotes[stale].timestamp = chrono::Utc::now() - chrono::Duration::minutes(30);
+        let good = 12;
+        let spread = 0.01 * quotes[good].bid;
+        quotes[good].bid -= spread;
+        quotes[good].ask += spread;
+
+        let vol_surface = VolatilitySurface::from_quotes_filtered(
+            "SPY".to_string(),
+            &quotes,
+            r,
+            0.1,
+            chrono::Duration::minutes(5),
+        )
+        .unwrap();
+
+        let at = |quote: &OptionQuote| {
+            vol_surface.value_at(quote.contract.expiration, quote.contract.strike)
+        };
+        assert!(at(&quotes[wide]).is_nan());
+        assert!(at(&quotes[stale]).is_nan());
+        assert!(at(&quotes[good]).is_finite());
+        let kept = vol_surface
+            .volatilities
+            .iter()
+            .filter(|v| v.is_finite())
+            .count();
+        assert_eq!(kept, quotes.len() - 2);
+
+        assert!(VolatilitySurface::from_quotes_filtered(
+            "SPY".to_string(),
+            &quotes[..1],
+            r,
+            0.1,
+            chrono::Duration::minutes(5),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn from_quotes_with_curve_uses_each_expirys_rate() {
+        let rate_fn = |t: f64| if t < 0.1 { 0.01 } else { 0.06 };
+        let near = priced_quote(true, 510.0, 14, 0.01, 0.2);
+        let far = priced_quote(true, 510.0, 120, 0.06, 0.2);
+        let quotes = [near.clone(), far.clone()];
+
+        let vol_surface =
+            VolatilitySurface::from_quotes_with_curve("SPY".to_string(), &quotes, rate_fn, 0.0)
+                .unwrap();
+
+        for (quote, rate) in [(&near, 0.01), (&far, 0.06)] {
+            let expected = ImpliedVolatility::from_quote(quote, rate, 0.0)
+                .unwrap()
+                .value;
+            let got = vol_surface.value_at(quote.contract.expiration, 510.0);
+            assert!((got - expected).abs() < 1e-12, "{} != {}", got, expected);
+            assert!((got - 0.2).abs() < 1e-4);
+        }
+
+        // A single flat rate misprices the far expiry.
+        let flat = ImpliedVolatility::from_quote(&far, 0.01, 0.0)
+            .unwrap()
+            .value;
+        assert!((flat - 0.2).abs() > 1e-3);
+    }
 }
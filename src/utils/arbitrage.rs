@@ -0,0 +1,167 @@
+use crate::models::OptionQuote;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+struct ParityPair<'a> {
+    call: &'a OptionQuote,
+    put: &'a OptionQuote,
+}
+
+fn call_put_pairs(quotes: &[OptionQuote]) -> BTreeMap<(DateTime<Utc>, u64), ParityPair<'_>> {
+    let mut calls = BTreeMap::new();
+    let mut puts = BTreeMap::new();
+    for q in quotes.iter().filter(|q| q.mid_price() > 0.0) {
+        let key = (q.contract.expiration, q.contract.strike.to_bits());
+        if q.contract.is_call() {
+            calls.insert(key, q);
+        } else {
+            puts.insert(key, q);
+        }
+    }
+
+    calls
+        .into_iter()
+        .filter_map(|(key, call)| puts.get(&key).map(|&put| (key, ParityPair { call, put })))
+        .collect()
+}
+
+pub fn parity_violations(
+    quotes: &[OptionQuote],
+    underlying: f64,
+    r: f64,
+) -> Vec<(f64, DateTime<Utc>, f64)> {
+    call_put_pairs(quotes)
+        .into_values()
+        .filter_map(|pair| {
+            let strike = pair.call.contract.strike;
+            let t = pair.call.contract.time_to_expiration();
+            let theoretical = underlying - strike * (-r * t).exp();
+            let violation = (pair.call.mid_price() - pair.put.mid_price()) - theoretical;
+
+            let half_spreads = 0.5
+                * ((pair.call.ask - pair.call.bid).max(0.0)
+                    + (pair.put.ask - pair.put.bid).max(0.0));
+
+            (violation.abs() > half_spreads).then_some((
+                strike,
+                pair.call.contract.expiration,
+                violation,
+            ))
+        })
+        .collect()
+}
+
+pub fn implied_forward(quotes: &[OptionQuote], r: f64) -> Vec<(DateTime<Utc>, f64)> {
+    let mut best: BTreeMap<DateTime<Utc>, (f64, f64)> = BTreeMap::new();
+
+    for ((expiration, _), pair) in call_put_pairs(quotes) {
+        let strike = pair.call.contract.strike;
+        let t = pair.call.contract.time_to_expiration();
+        let diff = pair.call.mid_price() - pair.put.mid_price();
+        let forward = strike + (r * t).exp() * diff;
+
+        let entry = best.entry(expiration).or_insert((f64::INFINITY, forward));
+        if diff.abs() < entry.0 {
+            *entry = (diff.abs(), forward);
+        }
+    }
+
+    best.into_iter()
+        .map(|(expiration, (_, forward))| (expiration, forward))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{OptionContract, OptionType};
+    use crate::utils::{market_close_utc, price};
+
+    const SPOT: f64 = 500.0;
+    const R: f64 = 0.05;
+    const SIGMA: f64 = 0.2;
+
+    fn expiration(days: i64) -> DateTime<Utc> {
+        market_close_utc((Utc::now() + chrono::Duration::days(days)).date_naive())
+    }
+
+    fn quote(
+        option_type: OptionType,
+        strike: f64,
+        expiration: DateTime<Utc>,
+        mid: f64,
+    ) -> OptionQuote {
+        let contract =
+            OptionContract::new("SPY".to_string(), option_type, strike, expiration).unwrap();
+        OptionQuote::new(contract, mid - 0.05, mid + 0.05, mid, 0, 0, SPOT)
+    }
+
+    fn parity_chain(expiration: DateTime<Utc>, strikes: &[f64]) -> Vec<OptionQuote> {
+        strikes
+            .iter()
+            .flat_map(|&strike| {
+                let t = quote(OptionType::Call, strike, expiration, 1.0)
+                    .contract
+                    .time_to_expiration();
+                [
+                    quote(
+                        OptionType::Call,
+                        strike,
+                        expiration,
+                        price(SPOT, strike, t, R, SIGMA, true),
+                    ),
+                    quote(
+                        OptionType::Put,
+                        strike,
+                        expiration,
+                        price(SPOT, strike, t, R, SIGMA, false),
+                    ),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn consistent_chain_has_no_parity_violations() {
+        let quotes = parity_chain(expiration(30), &[450.0, 480.0, 500.0, 520.0, 550.0]);
+        assert!(parity_violations(&quotes, SPOT, R).is_empty());
+    }
+
+    #[test]
+    fn flags_a_skewed_pair_with_its_sign() {
+        let exp = expiration(30);
+        let mut quotes = parity_chain(exp, &[480.0, 500.0, 520.0]);
+        let call = quotes
+            .iter_mut()
+            .find(|q| q.contract.is_call() && q.contract.strike == 500.0)
+            .unwrap();
+        let mid = call.mid_price() + 2.0;
+        *call = quote(OptionType::Call, 500.0, exp, mid);
+
+        let violations = parity_violations(&quotes, SPOT, R);
+        assert_eq!(violations.len(), 1);
+        let (strike, expiration, violation) = violations[0];
+        assert_eq!(strike, 500.0);
+        assert_eq!(expiration, exp);
+        assert!((violation - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn implied_forward_recovers_the_chain_forward() {
+        let near = expiration(30);
+        let far = expiration(180);
+        let mut quotes = parity_chain(near, &[480.0, 495.0, 505.0, 520.0]);
+        quotes.extend(parity_chain(far, &[450.0, 500.0, 550.0]));
+        quotes.push(quote(OptionType::Call, 500.0, expiration(60), 12.0));
+
+        let forwards = implied_forward(&quotes, R);
+        assert_eq!(forwards.len(), 2);
+        for ((exp, forward), expected_exp) in forwards.into_iter().zip([near, far]) {
+            assert_eq!(exp, expected_exp);
+            let t = quote(OptionType::Call, 500.0, exp, 1.0)
+                .contract
+                .time_to_expiration();
+            assert!((forward - SPOT * (R * t).exp()).abs() < 1e-6);
+        }
+    }
+}
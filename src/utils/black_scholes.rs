@@ -1,15 +1,21 @@
+use crate::api::OptionGreeks;
+use crate::error::IvError;
 use statrs::distribution::ContinuousCDF;
 use statrs::distribution::{Continuous, Normal};
 use std::sync::OnceLock;
 
 static NORMAL_DIST: OnceLock<Normal> = OnceLock::new();
 
-fn get_normal() -> &'static Normal {
+pub(crate) fn get_normal() -> &'static Normal {
     NORMAL_DIST.get_or_init(|| Normal::new(0.0, 1.0).unwrap())
 }
 
 fn calculate_d1(s: f64, k: f64, t: f64, r: f64, sigma: f64) -> f64 {
-    ((s / k).ln() + (r + 0.5 * sigma * sigma) * t) / (sigma * t.sqrt())
+    calculate_d1_with_yield(s, k, t, r, 0.0, sigma)
+}
+
+fn calculate_d1_with_yield(s: f64, k: f64, t: f64, r: f64, q: f64, sigma: f64) -> f64 {
+    ((s / k).ln() + (r - q + 0.5 * sigma * sigma) * t) / (sigma * t.sqrt())
 }
 
 fn calculate_d2(d1: f64, sigma: f64, t: f64) -> f64 {
@@ -17,33 +23,110 @@ fn calculate_d2(d1: f64, sigma: f64, t: f64) -> f64 {
 }
 
 pub fn price(s: f64, k: f64, t: f64, r: f64, sigma: f64, is_call: bool) -> f64 {
+    price_with_yield(s, k, t, r, 0.0, sigma, is_call)
+}
+
+pub fn price_with_yield(s: f64, k: f64, t: f64, r: f64, q: f64, sigma: f64, is_call: bool) -> f64 {
     let n = get_normal();
-    let d1 = calculate_d1(s, k, t, r, sigma);
+    let d1 = calculate_d1_with_yield(s, k, t, r, q, sigma);
     let d2 = calculate_d2(d1, sigma, t);
+    let discounted_spot = s * (-q * t).exp();
     if is_call {
-        s * n.cdf(d1) - k * (-r * t).exp() * n.cdf(d2)
+        discounted_spot * n.cdf(d1) - k * (-r * t).exp() * n.cdf(d2)
     } else {
-        k * (-r * t).exp() * n.cdf(-d2) - s * n.cdf(-d1)
+        k * (-r * t).exp() * n.cdf(-d2) - discounted_spot * n.cdf(-d1)
     }
 }
 
 pub fn delta(s: f64, k: f64, t: f64, r: f64, sigma: f64, is_call: bool) -> f64 {
+    delta_with_yield(s, k, t, r, 0.0, sigma, is_call)
+}
+
+pub fn delta_with_yield(s: f64, k: f64, t: f64, r: f64, q: f64, sigma: f64, is_call: bool) -> f64 {
     let n = get_normal();
-    let d1 = calculate_d1(s, k, t, r, sigma);
+    let d1 = calculate_d1_with_yield(s, k, t, r, q, sigma);
+    let carry = (-q * t).exp();
     if is_call {
-        n.cdf(d1)
+        carry * n.cdf(d1)
     } else {
-        n.cdf(d1) - 1.0
+        carry * (n.cdf(d1) - 1.0)
     }
 }
 
 pub fn vega(s: f64, k: f64, t: f64, r: f64, sigma: f64) -> f64 {
+    vega_with_yield(s, k, t, r, 0.0, sigma)
+}
+
+pub fn vega_with_yield(s: f64, k: f64, t: f64, r: f64, q: f64, sigma: f64) -> f64 {
+    let n = get_normal();
+    let d1 = calculate_d1_with_yield(s, k, t, r, q, sigma);
+    s * (-q * t).exp() * n.pdf(d1) * t.sqrt()
+}
+
+pub fn gamma(s: f64, k: f64, t: f64, r: f64, sigma: f64) -> f64 {
+    let n = get_normal();
+    let d1 = calculate_d1(s, k, t, r, sigma);
+    n.pdf(d1) / (s * sigma * t.sqrt())
+}
+
+pub fn theta(s: f64, k: f64, t: f64, r: f64, sigma: f64, is_call: bool) -> f64 {
+    let n = get_normal();
+    let d1 = calculate_d1(s, k, t, r, sigma);
+    let d2 = calculate_d2(d1, sigma, t);
+    let decay = -s * n.pdf(d1) * sigma / (2.0 * t.sqrt());
+    let discounted_strike = k * (-r * t).exp();
+    if is_call {
+        decay - r * discounted_strike * n.cdf(d2)
+    } else {
+        decay + r * discounted_strike * n.cdf(-d2)
+    }
+}
+
+pub fn rho(s: f64, k: f64, t: f64, r: f64, sigma: f64, is_call: bool) -> f64 {
     let n = get_normal();
     let d1 = calculate_d1(s, k, t, r, sigma);
-    s * n.pdf(d1) * t.sqrt()
+    let d2 = calculate_d2(d1, sigma, t);
+    let discounted_strike = k * (-r * t).exp();
+    if is_call {
+        discounted_strike * t * n.cdf(d2)
+    } else {
+        -discounted_strike * t * n.cdf(-d2)
+    }
+}
+
+pub fn compute_greeks(s: f64, k: f64, t: f64, r: f64, sigma: f64, is_call: bool) -> OptionGreeks {
+    let n = get_normal();
+    let d1 = calculate_d1(s, k, t, r, sigma);
+    let d2 = calculate_d2(d1, sigma, t);
+    let pdf_d1 = n.pdf(d1);
+    let sqrt_t = t.sqrt();
+    let discounted_strike = k * (-r * t).exp();
+    let decay = -s * pdf_d1 * sigma / (2.0 * sqrt_t);
+
+    let (delta, theta, rho) = if is_call {
+        (
+            n.cdf(d1),
+            decay - r * discounted_strike * n.cdf(d2),
+            discounted_strike * t * n.cdf(d2),
+        )
+    } else {
+        (
+            n.cdf(d1) - 1.0,
+            decay + r * discounted_strike * n.cdf(-d2),
+            -discounted_strike * t * n.cdf(-d2),
+        )
+    };
+
+    OptionGreeks {
+        delta,
+        gamma: pdf_d1 / (s * sigma * sqrt_t),
+        theta,
+        vega: s * pdf_d1 * sqrt_t,
+        rho,
+    }
 }
 
-fn calculate_intrinsic(s: f64, k: f64, is_call: bool) -> f64 {
+pub(crate) fn calculate_intrinsic(s: f64, k: f64, is_call: bool) -> f64 {
     if is_call {
         (s - k).max(0.0)
     } else {
@@ -51,6 +134,35 @@ fn calculate_intrinsic(s: f64, k: f64, is_call: bool) -> f64 {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IvSolution {
+    pub sigma: f64,
+    pub iterations: usize,
+    pub converged: bool,
+    pub final_residual: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IvSolverConfig {
+    pub tol: f64,
+    pub max_iter: usize,
+    pub sigma_low: f64,
+    pub sigma_high: f64,
+    pub initial_guess: f64,
+}
+
+impl Default for IvSolverConfig {
+    fn default() -> Self {
+        Self {
+            tol: 1e-6,
+            max_iter: 100,
+            sigma_low: 1e-4,
+            sigma_high: 5.0,
+            initial_guess: 0.2,
+        }
+    }
+}
+
 pub fn implied_volatility(
     price_target: f64,
     s: f64,
@@ -59,24 +171,146 @@ pub fn implied_volatility(
     r: f64,
     is_call: bool,
 ) -> Result<f64, String> {
+    implied_volatility_with(
+        &IvSolverConfig::default(),
+        price_target,
+        s,
+        k,
+        t,
+        r,
+        is_call,
+    )
+}
+
+pub fn implied_volatility_with(
+    cfg: &IvSolverConfig,
+    price_target: f64,
+    s: f64,
+    k: f64,
+    t: f64,
+    r: f64,
+    is_call: bool,
+) -> Result<f64, String> {
+    let solution = implied_volatility_detailed_with(cfg, price_target, s, k, t, r, is_call)?;
+    if solution.converged {
+        Ok(solution.sigma)
+    } else {
+        Err("Implied volatility did not converge".to_string())
+    }
+}
+
+pub fn implied_volatility_detailed(
+    price_target: f64,
+    s: f64,
+    k: f64,
+    t: f64,
+    r: f64,
+    is_call: bool,
+) -> Result<IvSolution, String> {
+    implied_volatility_detailed_with(
+        &IvSolverConfig::default(),
+        price_target,
+        s,
+        k,
+        t,
+        r,
+        is_call,
+    )
+}
+
+pub fn implied_volatility_detailed_with(
+    cfg: &IvSolverConfig,
+    price_target: f64,
+    s: f64,
+    k: f64,
+    t: f64,
+    r: f64,
+    is_call: bool,
+) -> Result<IvSolution, String> {
+    implied_volatility_detailed_with_yield(cfg, price_target, s, k, t, r, 0.0, is_call)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn implied_volatility_detailed_with_yield(
+    cfg: &IvSolverConfig,
+    price_target: f64,
+    s: f64,
+    k: f64,
+    t: f64,
+    r: f64,
+    q: f64,
+    is_call: bool,
+) -> Result<IvSolution, String> {
     if price_target <= 0.0 || t <= 0.0 || s <= 0.0 || k <= 0.0 {
         return Err("Invalid input".to_string());
     }
+    if !(cfg.sigma_low > 0.0 && cfg.sigma_low < cfg.sigma_high) {
+        return Err(format!(
+            "Invalid sigma bracket [{}, {}]",
+            cfg.sigma_low, cfg.sigma_high
+        ));
+    }
 
-    let intrinsic = calculate_intrinsic(s, k, is_call);
+    let intrinsic = calculate_intrinsic(s * (-q * t).exp(), k, is_call);
 
-    let adjusted_price = price_target.max(intrinsic);
+    Ok(solve_iv(
+        cfg,
+        price_target.max(intrinsic),
+        |sigma| price_with_yield(s, k, t, r, q, sigma, is_call),
+        |sigma| vega_with_yield(s, k, t, r, q, sigma),
+    ))
+}
 
-    let mut sigma = 0.2;
-    let mut sigma_low = 1e-4;
-    let mut sigma_high = 5.0;
+pub fn implied_volatility_with_yield(
+    price_target: f64,
+    s: f64,
+    k: f64,
+    t: f64,
+    r: f64,
+    q: f64,
+    is_call: bool,
+) -> Result<f64, String> {
+    let solution = implied_volatility_detailed_with_yield(
+        &IvSolverConfig::default(),
+        price_target,
+        s,
+        k,
+        t,
+        r,
+        q,
+        is_call,
+    )?;
+    if solution.converged {
+        Ok(solution.sigma)
+    } else {
+        Err("Implied volatility did not converge".to_string())
+    }
+}
+
+pub(crate) fn solve_iv<P, V>(
+    cfg: &IvSolverConfig,
+    target: f64,
+    price_fn: P,
+    vega_fn: V,
+) -> IvSolution
+where
+    P: Fn(f64) -> f64,
+    V: Fn(f64) -> f64,
+{
+    let mut sigma = cfg.initial_guess.clamp(cfg.sigma_low, cfg.sigma_high);
+    let mut sigma_low = cfg.sigma_low;
+    let mut sigma_high = cfg.sigma_high;
 
-    for _ in 0..100 {
-        let price = price(s, k, t, r, sigma, is_call);
-        let diff = price - adjusted_price;
+    for iteration in 0..cfg.max_iter {
+        let diff = price_fn(sigma) - target;
 
-        if diff.abs() < 1e-6 {
-            return Ok(sigma);
+        if diff.abs() < cfg.tol {
+            return IvSolution {
+                sigma,
+                iterations: iteration + 1,
+                converged: true,
+                final_residual: diff,
+            };
         }
 
         if diff > 0.0 {
@@ -85,7 +319,7 @@ pub fn implied_volatility(
             sigma_low = sigma;
         }
 
-        let v = vega(s, k, t, r, sigma);
+        let v = vega_fn(sigma);
 
         if v.abs() > 1e-8 {
             let new_sigma = sigma - diff / v;
@@ -99,17 +333,369 @@ pub fn implied_volatility(
         }
     }
 
-    Err("Implied volatility did not converge".to_string())
+    IvSolution {
+        sigma,
+        iterations: cfg.max_iter,
+        converged: false,
+        final_residual: price_fn(sigma) - target,
+    }
+}
+
+pub fn price_black76(f: f64, k: f64, t: f64, r: f64, sigma: f64, is_call: bool) -> f64 {
+    let n = get_normal();
+    let d1 = calculate_d1(f, k, t, 0.0, sigma);
+    let d2 = calculate_d2(d1, sigma, t);
+    let discount = (-r * t).exp();
+    if is_call {
+        discount * (f * n.cdf(d1) - k * n.cdf(d2))
+    } else {
+        discount * (k * n.cdf(-d2) - f * n.cdf(-d1))
+    }
+}
+
+pub fn delta_black76(f: f64, k: f64, t: f64, r: f64, sigma: f64, is_call: bool) -> f64 {
+    let n = get_normal();
+    let d1 = calculate_d1(f, k, t, 0.0, sigma);
+    let discount = (-r * t).exp();
+    if is_call {
+        discount * n.cdf(d1)
+    } else {
+        discount * (n.cdf(d1) - 1.0)
+    }
+}
+
+pub fn vega_black76(f: f64, k: f64, t: f64, r: f64, sigma: f64) -> f64 {
+    let n = get_normal();
+    let d1 = calculate_d1(f, k, t, 0.0, sigma);
+    (-r * t).exp() * f * n.pdf(d1) * t.sqrt()
+}
+
+pub fn implied_volatility_black76(
+    price_target: f64,
+    f: f64,
+    k: f64,
+    t: f64,
+    r: f64,
+    is_call: bool,
+) -> Result<f64, String> {
+    if price_target <= 0.0 || t <= 0.0 || f <= 0.0 || k <= 0.0 {
+        return Err("Invalid input".to_string());
+    }
+
+    let intrinsic = (-r * t).exp() * calculate_intrinsic(f, k, is_call);
+    let solution = solve_iv(
+        &IvSolverConfig::default(),
+        price_target.max(intrinsic),
+        |sigma| price_black76(f, k, t, r, sigma, is_call),
+        |sigma| vega_black76(f, k, t, r, sigma),
+    );
+
+    if solution.converged {
+        Ok(solution.sigma)
+    } else {
+        Err("Implied volatility did not converge".to_string())
+    }
+}
+
+pub fn implied_volatility_bid_ask(
+    bid: f64,
+    ask: f64,
+    s: f64,
+    k: f64,
+    t: f64,
+    r: f64,
+    is_call: bool,
+) -> Result<f64, IvError> {
+    implied_volatility_bid_ask_with_yield(bid, ask, s, k, t, r, 0.0, is_call)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn implied_volatility_bid_ask_with_yield(
+    bid: f64,
+    ask: f64,
+    s: f64,
+    k: f64,
+    t: f64,
+    r: f64,
+    q: f64,
+    is_call: bool,
+) -> Result<f64, IvError> {
+    let discounted_spot = s * (-q * t).exp();
+    let discounted_strike = k * (-r * t).exp();
+    let intrinsic = if is_call {
+        (discounted_spot - discounted_strike).max(0.0)
+    } else {
+        (discounted_strike - discounted_spot).max(0.0)
+    };
+
+    if t <= 0.0 || s <= 0.0 || k <= 0.0 {
+        return Err(IvError::Solver("Invalid input".to_string()));
+    }
+
+    // Each leg is checked against the discounted no-arbitrage bound before solving,
+    // and solved without the intrinsic clamp so a bad leg is reported, not masked.
+    let solve_leg = |price: f64| {
+        (price.is_finite() && price > intrinsic).then(|| {
+            let solution = solve_iv(
+                &IvSolverConfig::default(),
+                price,
+                |sigma| price_with_yield(s, k, t, r, q, sigma, is_call),
+                |sigma| vega_with_yield(s, k, t, r, q, sigma),
+            );
+            if solution.converged {
+                Ok(solution.sigma)
+            } else {
+                Err("Implied volatility did not converge".to_string())
+            }
+        })
+    };
+
+    match (solve_leg(bid), solve_leg(ask)) {
+        (None, None) => Err(IvError::BelowIntrinsic {
+            bid,
+            ask,
+            intrinsic,
+        }),
+        (Some(Ok(bid_iv)), Some(Ok(ask_iv))) => Ok((bid_iv + ask_iv) / 2.0),
+        (Some(Ok(iv)), _) | (_, Some(Ok(iv))) => Ok(iv),
+        (Some(Err(e)), _) | (_, Some(Err(e))) => Err(IvError::Solver(e)),
+    }
 }
 
 pub fn batch_implied_volatility(
     quotes: &[(f64, f64, f64, f64, bool)],
     r: f64,
+) -> Vec<Result<f64, String>> {
+    batch_implied_volatility_with_yield(quotes, r, 0.0)
+}
+
+pub fn batch_implied_volatility_with_yield(
+    quotes: &[(f64, f64, f64, f64, bool)],
+    r: f64,
+    q: f64,
 ) -> Vec<Result<f64, String>> {
     use rayon::prelude::*;
 
     quotes
         .par_iter()
-        .map(|(price, s, k, t, is_call)| implied_volatility(*price, *s, *k, *t, r, *is_call))
+        .map(|(price, s, k, t, is_call)| {
+            implied_volatility_with_yield(*price, *s, *k, *t, r, q, *is_call)
+        })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Hull, Options, Futures and Other Derivatives: index option with S=930,
+    // K=900, r=8%, q=3%, sigma=20%, two months to expiry.
+    const S: f64 = 930.0;
+    const K: f64 = 900.0;
+    const R: f64 = 0.08;
+    const Q: f64 = 0.03;
+    const SIGMA: f64 = 0.2;
+    const T: f64 = 2.0 / 12.0;
+
+    #[test]
+    fn prices_with_dividend_yield() {
+        assert!((price_with_yield(S, K, T, R, Q, SIGMA, true) - 51.833).abs() < 1e-3);
+        assert!((price_with_yield(S, K, T, R, Q, SIGMA, false) - 14.551).abs() < 1e-3);
+    }
+
+    #[test]
+    fn greeks_with_dividend_yield() {
+        assert!((delta_with_yield(S, K, T, R, Q, SIGMA, true) - 0.70342).abs() < 1e-5);
+        assert!((delta_with_yield(S, K, T, R, Q, SIGMA, false) + 0.29159).abs() < 1e-5);
+        assert!((vega_with_yield(S, K, T, R, Q, SIGMA) - 129.948).abs() < 1e-3);
+    }
+
+    #[test]
+    fn zero_yield_matches_plain_functions() {
+        assert_eq!(
+            price_with_yield(S, K, T, R, 0.0, SIGMA, true),
+            price(S, K, T, R, SIGMA, true)
+        );
+        assert_eq!(
+            delta_with_yield(S, K, T, R, 0.0, SIGMA, false),
+            delta(S, K, T, R, SIGMA, false)
+        );
+    }
+
+    #[test]
+    fn implied_volatility_with_yield_recovers_sigma() {
+        for is_call in [true, false] {
+            let target = price_with_yield(S, K, T, R, Q, SIGMA, is_call);
+            let iv = implied_volatility_with_yield(target, S, K, T, R, Q, is_call).unwrap();
+            assert!((iv - SIGMA).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn gamma_is_shared_by_calls_and_puts() {
+        let h = 1e-3;
+        for is_call in [true, false] {
+            let bumped = (delta(S + h, K, T, R, SIGMA, is_call)
+                - delta(S - h, K, T, R, SIGMA, is_call))
+                / (2.0 * h);
+            assert!((bumped - gamma(S, K, T, R, SIGMA)).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn theta_matches_finite_difference_at_the_money() {
+        let h = 1e-5;
+        for is_call in [true, false] {
+            let bumped = (price(S, S, T - h, R, SIGMA, is_call)
+                - price(S, S, T + h, R, SIGMA, is_call))
+                / (2.0 * h);
+            assert!((bumped - theta(S, S, T, R, SIGMA, is_call)).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn rho_matches_finite_difference_in_rate() {
+        let h = 1e-5;
+        for (k, t) in [(850.0, 0.1), (K, T), (1000.0, 1.5)] {
+            for is_call in [true, false] {
+                let bumped = (price(S, k, t, R + h, SIGMA, is_call)
+                    - price(S, k, t, R - h, SIGMA, is_call))
+                    / (2.0 * h);
+                let analytic = rho(S, k, t, R, SIGMA, is_call);
+                assert!(
+                    (analytic - bumped).abs() < 1e-4 * analytic.abs().max(1.0),
+                    "k={} t={} call={}: {} vs {}",
+                    k,
+                    t,
+                    is_call,
+                    analytic,
+                    bumped
+                );
+                assert_eq!(compute_greeks(S, k, t, R, SIGMA, is_call).rho, analytic);
+            }
+        }
+    }
+
+    #[test]
+    fn compute_greeks_matches_individual_functions() {
+        for is_call in [true, false] {
+            let greeks = compute_greeks(S, K, T, R, SIGMA, is_call);
+            assert!((greeks.delta - delta(S, K, T, R, SIGMA, is_call)).abs() < 1e-12);
+            assert!((greeks.gamma - gamma(S, K, T, R, SIGMA)).abs() < 1e-12);
+            assert!((greeks.theta - theta(S, K, T, R, SIGMA, is_call)).abs() < 1e-9);
+            assert!((greeks.vega - vega(S, K, T, R, SIGMA)).abs() < 1e-9);
+            assert!((greeks.rho - rho(S, K, T, R, SIGMA, is_call)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn detailed_solver_converges_quickly_across_the_grid() {
+        let (s, r, sigma) = (100.0, 0.03, 0.35);
+        let mut max_iterations = 0;
+        for t in [0.02, 0.25, 1.0, 3.0] {
+            for k in [70.0, 85.0, 95.0, 100.0, 105.0, 115.0, 130.0] {
+                // Out-of-the-money side, where vega is largest relative to price.
+                let is_call = k >= s;
+                let target = price(s, k, t, r, sigma, is_call);
+                if target < 1e-4 {
+                    continue;
+                }
+                let solution = implied_volatility_detailed(target, s, k, t, r, is_call).unwrap();
+                assert!(solution.converged, "k={} t={}: {:?}", k, t, solution);
+                assert!(solution.final_residual.abs() < 1e-6);
+                assert!((solution.sigma - sigma).abs() < 1e-4, "k={} t={}", k, t);
+                max_iterations = max_iterations.max(solution.iterations);
+            }
+        }
+        // Newton with a bisection fallback should need far fewer than max_iter.
+        assert!(max_iterations <= 15, "{}", max_iterations);
+    }
+
+    #[test]
+    fn wide_bracket_solves_very_high_vols() {
+        let (s, k, t, r, sigma) = (100.0, 100.0, 0.1, 0.03, 7.0);
+        let target = price(s, k, t, r, sigma, true);
+
+        let default = implied_volatility_detailed(target, s, k, t, r, true).unwrap();
+        assert!(!default.converged);
+        assert!(implied_volatility(target, s, k, t, r, true).is_err());
+
+        let cfg = IvSolverConfig {
+            sigma_high: 10.0,
+            ..IvSolverConfig::default()
+        };
+        let wide = implied_volatility_detailed_with(&cfg, target, s, k, t, r, true).unwrap();
+        assert!(wide.converged);
+        assert!((wide.sigma - sigma).abs() < 1e-4, "{:?}", wide);
+    }
+
+    #[test]
+    fn black76_satisfies_put_call_parity() {
+        let (f, r, t): (f64, f64, f64) = (5020.0, 0.045, 0.75);
+        let discount = (-r * t).exp();
+        for k in [4000.0, 5000.0, 5020.0, 6100.0] {
+            let call = price_black76(f, k, t, r, SIGMA, true);
+            let put = price_black76(f, k, t, r, SIGMA, false);
+            assert!((call - put - discount * (f - k)).abs() < 1e-8);
+
+            let delta_gap =
+                delta_black76(f, k, t, r, SIGMA, true) - delta_black76(f, k, t, r, SIGMA, false);
+            assert!((delta_gap - discount).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn black76_implied_volatility_recovers_sigma() {
+        let (f, r, t) = (78.5, 0.05, 0.4);
+        for (k, is_call) in [(70.0, false), (78.5, true), (90.0, true)] {
+            let target = price_black76(f, k, t, r, SIGMA, is_call);
+            let iv = implied_volatility_black76(target, f, k, t, r, is_call).unwrap();
+            assert!((iv - SIGMA).abs() < 1e-6);
+        }
+        assert!(implied_volatility_black76(1.0, -1.0, 70.0, t, r, true).is_err());
+    }
+
+    #[test]
+    fn bid_ask_solve_recovers_deep_itm_calls_and_puts() {
+        let (r, t, sigma): (f64, f64, f64) = (0.05, 1.0, 0.2);
+        for (s, k, is_call) in [(150.0, 100.0, true), (100.0, 150.0, false)] {
+            let mid = price(s, k, t, r, sigma, is_call);
+            let iv =
+                implied_volatility_bid_ask(mid - 0.01, mid + 0.01, s, k, t, r, is_call).unwrap();
+            assert!((iv - sigma).abs() < 1e-3, "is_call {}: {}", is_call, iv);
+        }
+
+        // The put trades below the undiscounted intrinsic K - S = 50, which the
+        // clamped solver would have bumped up to 50.
+        let put = price(100.0, 150.0, t, r, sigma, false);
+        assert!(put < 50.0);
+        let iv = implied_volatility_bid_ask(put, put, 100.0, 150.0, t, r, false).unwrap();
+        assert!((iv - sigma).abs() < 1e-5);
+    }
+
+    #[test]
+    fn bid_ask_solve_uses_the_leg_above_intrinsic() {
+        let (s, k, r, t, sigma): (f64, f64, f64, f64, f64) = (150.0, 100.0, 0.05, 1.0, 0.2);
+        let intrinsic = s - k * (-r * t).exp();
+        let ask = price(s, k, t, r, sigma, true);
+        let iv = implied_volatility_bid_ask(intrinsic - 1.0, ask, s, k, t, r, true).unwrap();
+        assert!((iv - sigma).abs() < 1e-5);
+
+        let err = implied_volatility_bid_ask(intrinsic - 1.0, intrinsic - 0.5, s, k, t, r, true)
+            .unwrap_err();
+        assert!(matches!(err, IvError::BelowIntrinsic { .. }), "{:?}", err);
+
+        let put_intrinsic = k * (-r * t).exp() - 60.0;
+        let err = implied_volatility_bid_ask(
+            put_intrinsic - 0.2,
+            put_intrinsic - 0.1,
+            60.0,
+            k,
+            t,
+            r,
+            false,
+        )
+        .unwrap_err();
+        assert!(matches!(err, IvError::BelowIntrinsic { .. }), "{:?}", err);
+    }
+}
@@ -0,0 +1,27 @@
+use crate::error::Result;
+use crate::models::{ForwardCurve, VolatilitySurface};
+
+pub fn implied_correlation(index_atm_vol: f64, components: &[(f64, f64)]) -> f64 {
+    let own: f64 = components.iter().map(|&(vol, w)| (w * vol).powi(2)).sum();
+    let total: f64 = components
+        .iter()
+        .map(|&(vol, w)| w * vol)
+        .sum::<f64>()
+        .powi(2);
+    let cross = total - own;
+
+    if cross.abs() <= f64::EPSILON {
+        return f64::NAN;
+    }
+    (index_atm_vol * index_atm_vol - own) / cross
+}
+
+pub fn atm_vols_at_tenor(
+    surfaces: &[(&VolatilitySurface, &ForwardCurve)],
+    tenor_days: i64,
+) -> Result<Vec<f64>> {
+    surfaces
+        .iter()
+        .map(|(surface, curve)| surface.atm_vol_at_tenor(tenor_days, curve))
+        .collect()
+}
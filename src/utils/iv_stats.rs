@@ -0,0 +1,45 @@
+use chrono::{DateTime, Duration, Utc};
+
+pub fn iv_rank(current: f64, history: &[f64]) -> f64 {
+    let valid = history.iter().copied().filter(|v| v.is_finite());
+    let (min, max) = valid.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+        (lo.min(v), hi.max(v))
+    });
+
+    if !min.is_finite() || !max.is_finite() || !current.is_finite() {
+        return f64::NAN;
+    }
+    if max - min <= f64::EPSILON {
+        return 0.5;
+    }
+    ((current - min) / (max - min)).clamp(0.0, 1.0)
+}
+
+pub fn iv_percentile(current: f64, history: &[f64]) -> f64 {
+    let valid: Vec<f64> = history.iter().copied().filter(|v| v.is_finite()).collect();
+    if valid.is_empty() || !current.is_finite() {
+        return f64::NAN;
+    }
+    let below = valid.iter().filter(|&&v| v < current).count();
+    below as f64 / valid.len() as f64
+}
+
+pub fn trailing_history(
+    series: &[(DateTime<Utc>, f64)],
+    as_of: DateTime<Utc>,
+    lookback_days: i64,
+) -> Vec<f64> {
+    let start = as_of - Duration::days(lookback_days);
+    series
+        .iter()
+        .filter(|(t, v)| *t >= start && *t <= as_of && v.is_finite())
+        .map(|(_, v)| *v)
+        .collect()
+}
+
+pub fn iv_rank_from_series(series: &[(DateTime<Utc>, f64)], lookback_days: i64) -> Option<f64> {
+    let (as_of, current) = series.iter().copied().max_by_key(|(t, _)| *t)?;
+    let history = trailing_history(series, as_of, lookback_days);
+    let rank = iv_rank(current, &history);
+    (!rank.is_nan()).then_some(rank)
+}
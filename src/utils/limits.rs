@@ -0,0 +1,51 @@
+use crate::config::ContractLimitMode;
+use crate::error::{OptionsError, Result};
+use crate::models::OptionContract;
+use std::cmp::Ordering;
+use tracing::info;
+
+pub fn enforce_contract_limit<T>(
+    mut entries: Vec<(OptionContract, T)>,
+    max_contracts: Option<usize>,
+    mode: ContractLimitMode,
+) -> Result<Vec<(OptionContract, T)>> {
+    let Some(max) = max_contracts else {
+        return Ok(entries);
+    };
+    let total = entries.len();
+    if total <= max {
+        return Ok(entries);
+    }
+
+    match mode {
+        ContractLimitMode::Error => Err(OptionsError::Other(format!(
+            "{} contracts exceeds the configured maximum of {}; narrow the strike range or pick an expiration, or raise MAX_CONTRACTS",
+            total, max
+        ))),
+        ContractLimitMode::Sample => {
+            entries.sort_by(|(a, _), (b, _)| {
+                a.expiration.cmp(&b.expiration).then_with(|| {
+                    a.strike
+                        .partial_cmp(&b.strike)
+                        .unwrap_or(Ordering::Equal)
+                })
+            });
+
+            let step = total as f64 / max as f64;
+            let keep: Vec<usize> = (0..max).map(|i| (i as f64 * step) as usize).collect();
+            let sampled: Vec<(OptionContract, T)> = entries
+                .into_iter()
+                .enumerate()
+                .filter(|(idx, _)| keep.binary_search(idx).is_ok())
+                .map(|(_, entry)| entry)
+                .collect();
+
+            info!(
+                "Sampled {} of {} contracts to stay within MAX_CONTRACTS",
+                sampled.len(),
+                total
+            );
+            Ok(sampled)
+        }
+    }
+}
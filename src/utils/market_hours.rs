@@ -0,0 +1,85 @@
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Utc, Weekday};
+
+const MARKET_CLOSE_HOUR_ET: u32 = 16;
+
+fn nth_weekday_of_month(year: i32, month: u32, weekday: Weekday, n: u8) -> Option<NaiveDate> {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n)
+}
+
+pub fn is_us_eastern_dst(date: NaiveDate) -> bool {
+    let year = date.year();
+    let (Some(start), Some(end)) = (
+        nth_weekday_of_month(year, 3, Weekday::Sun, 2),
+        nth_weekday_of_month(year, 11, Weekday::Sun, 1),
+    ) else {
+        return false;
+    };
+    date >= start && date < end
+}
+
+pub fn eastern_utc_offset_hours(date: NaiveDate) -> i64 {
+    if is_us_eastern_dst(date) {
+        -4
+    } else {
+        -5
+    }
+}
+
+pub fn market_close_utc(date: NaiveDate) -> DateTime<Utc> {
+    let utc_hour = MARKET_CLOSE_HOUR_ET as i64 - eastern_utc_offset_hours(date);
+    let naive = date
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is always a valid time")
+        + chrono::Duration::hours(utc_hour);
+    Utc.from_utc_datetime(&naive)
+}
+
+pub fn is_business_day(date: NaiveDate, holidays: &[NaiveDate]) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !holidays.contains(&date)
+}
+
+pub fn business_days_between(start: NaiveDate, end: NaiveDate, holidays: &[NaiveDate]) -> i64 {
+    start
+        .iter_days()
+        .skip(1)
+        .take_while(|d| *d <= end)
+        .filter(|d| is_business_day(*d, holidays))
+        .count() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close_hour(y: i32, m: u32, d: u32) -> u32 {
+        use chrono::Timelike;
+        market_close_utc(NaiveDate::from_ymd_opt(y, m, d).unwrap()).hour()
+    }
+
+    #[test]
+    fn market_close_follows_the_spring_dst_change() {
+        assert_eq!(close_hour(2024, 3, 8), 21);
+        assert_eq!(close_hour(2024, 3, 9), 21);
+        assert_eq!(close_hour(2024, 3, 10), 20);
+        assert_eq!(close_hour(2024, 3, 11), 20);
+    }
+
+    #[test]
+    fn market_close_follows_the_autumn_dst_change() {
+        assert_eq!(close_hour(2024, 11, 1), 20);
+        assert_eq!(close_hour(2024, 11, 2), 20);
+        assert_eq!(close_hour(2024, 11, 3), 21);
+        assert_eq!(close_hour(2024, 11, 4), 21);
+    }
+
+    #[test]
+    fn market_close_keeps_the_calendar_date() {
+        let date = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        assert_eq!(market_close_utc(date).date_naive(), date);
+        assert_eq!(eastern_utc_offset_hours(date), -5);
+        assert_eq!(
+            eastern_utc_offset_hours(NaiveDate::from_ymd_opt(2024, 7, 4).unwrap()),
+            -4
+        );
+    }
+}
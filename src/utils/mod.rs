@@ -1,6 +1,27 @@
+pub mod arbitrage;
 mod black_scholes;
+mod dispersion;
+mod iv_stats;
+mod limits;
+mod market_hours;
 mod plotting;
 pub mod polars_utils;
+mod pricing_model;
+pub mod probability;
+mod rates;
+mod realized_vol;
+mod recorder;
+pub mod svi;
+mod vol_scaling;
 
 pub use black_scholes::*;
+pub use dispersion::*;
+pub use iv_stats::*;
+pub use limits::*;
+pub use market_hours::*;
 pub use plotting::*;
+pub use pricing_model::*;
+pub use rates::*;
+pub use realized_vol::*;
+pub use recorder::*;
+pub use vol_scaling::*;
@@ -7,6 +7,51 @@ use plotters::backend::BitMapBackend;
 use plotters::prelude::*;
 use std::path::Path;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Colormap {
+    #[default]
+    Viridis,
+    Inferno,
+    Magma,
+    Plasma,
+    RedBlue,
+    Spectral,
+}
+
+impl Colormap {
+    pub fn gradient(&self) -> colorous::Gradient {
+        match self {
+            Colormap::Viridis => colorous::VIRIDIS,
+            Colormap::Inferno => colorous::INFERNO,
+            Colormap::Magma => colorous::MAGMA,
+            Colormap::Plasma => colorous::PLASMA,
+            Colormap::RedBlue => colorous::RED_BLUE,
+            Colormap::Spectral => colorous::SPECTRAL,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotStyle {
+    pub width: u32,
+    pub height: u32,
+    pub colormap: Colormap,
+    pub font: String,
+    pub title_size: u32,
+}
+
+impl Default for PlotStyle {
+    fn default() -> Self {
+        Self {
+            width: 1200,
+            height: 900,
+            colormap: Colormap::Viridis,
+            font: "sans-serif".to_string(),
+            title_size: 30,
+        }
+    }
+}
+
 pub fn plot_volatility_smile<P: AsRef<Path>>(
     strikes: &Array1<f64>,
     volatilities: &Array1<f64>,
@@ -35,6 +80,22 @@ pub fn plot_volatility_smile_in_memory(
     volatilities: &Array1<f64>,
     symbol: &str,
     expiration: &chrono::DateTime<chrono::Utc>,
+) -> Result<ColorImage> {
+    plot_volatility_smile_in_memory_styled(
+        strikes,
+        volatilities,
+        symbol,
+        expiration,
+        &PlotStyle::default(),
+    )
+}
+
+pub fn plot_volatility_smile_in_memory_styled(
+    strikes: &Array1<f64>,
+    volatilities: &Array1<f64>,
+    symbol: &str,
+    expiration: &chrono::DateTime<chrono::Utc>,
+    style: &PlotStyle,
 ) -> Result<ColorImage> {
     let mut valid_points: Vec<(f64, f64)> = Vec::new();
     for (i, &vol) in volatilities.iter().enumerate() {
@@ -75,9 +136,8 @@ pub fn plot_volatility_smile_in_memory(
 
     let exp_str = expiration.format("%Y-%m-%d").to_string();
 
-    let width = 1200u32;
-    let height = 900u32;
-    let mut buffer = vec![0u8; (width * height * 4) as usize];
+    let (width, height) = (style.width, style.height);
+    let mut buffer = vec![0u8; (width * height * 3) as usize];
     {
         let root = BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area();
         root.fill(&WHITE)
@@ -86,7 +146,7 @@ pub fn plot_volatility_smile_in_memory(
         let mut chart = ChartBuilder::on(&root)
             .caption(
                 format!("{} Volatility Smile - {}", symbol, exp_str),
-                ("sans-serif", 30).into_font(),
+                (style.font.as_str(), style.title_size).into_font(),
             )
             .margin(10)
             .x_label_area_size(40)
@@ -98,7 +158,7 @@ pub fn plot_volatility_smile_in_memory(
             .configure_mesh()
             .x_desc("Strike Price")
             .y_desc("Implied Volatility")
-            .axis_desc_style(("sans-serif", 15))
+            .axis_desc_style((style.font.as_str(), 15))
             .draw()
             .map_err(|e| OptionsError::Other(e.to_string()))?;
 
@@ -122,8 +182,8 @@ pub fn plot_volatility_smile_in_memory(
                 "Generated: {}",
                 chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
             ),
-            &TextStyle::from(("sans-serif", 15)).color(&BLACK),
-            (10, 570),
+            &TextStyle::from((style.font.as_str(), 15)).color(&BLACK),
+            (10, height as i32 - 30),
         )
         .map_err(|e| OptionsError::Other(e.to_string()))?;
 
@@ -131,7 +191,7 @@ pub fn plot_volatility_smile_in_memory(
             .map_err(|e| OptionsError::Other(e.to_string()))?;
     }
 
-    Ok(ColorImage::from_rgba_unmultiplied(
+    Ok(ColorImage::from_rgb(
         [width as usize, height as usize],
         &buffer,
     ))
@@ -146,6 +206,26 @@ pub fn plot_volatility_term_structure<P: AsRef<Path>>(
 ) -> Result<()> {
     let output_path = output_path.as_ref();
 
+    let img = plot_volatility_term_structure_in_memory(times, volatilities, symbol, strike)?;
+    let pixels: Vec<u8> = img.pixels.iter().flat_map(|p| p.to_array()).collect();
+    image::save_buffer_with_format(
+        output_path,
+        &pixels,
+        img.size[0] as u32,
+        img.size[1] as u32,
+        image::ColorType::Rgba8,
+        ImageFormat::Png,
+    )?;
+
+    Ok(())
+}
+
+pub fn plot_volatility_term_structure_in_memory(
+    times: &Array1<f64>,
+    volatilities: &Array1<f64>,
+    symbol: &str,
+    strike: f64,
+) -> Result<ColorImage> {
     let mut valid_points: Vec<(f64, f64)> = Vec::new();
     for (i, &vol) in volatilities.iter().enumerate() {
         if !vol.is_nan() {
@@ -183,61 +263,69 @@ pub fn plot_volatility_term_structure<P: AsRef<Path>>(
     let vol_min = (min_vol - 0.1 * vol_range).max(0.0);
     let vol_max = max_vol + 0.1 * vol_range;
 
-    let root = BitMapBackend::new(output_path, (800, 600)).into_drawing_area();
-    root.fill(&WHITE)
-        .map_err(|e| OptionsError::Other(e.to_string()))?;
+    let width = 800u32;
+    let height = 600u32;
+    let mut buffer = vec![0u8; (width * height * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area();
+        root.fill(&WHITE)
+            .map_err(|e| OptionsError::Other(e.to_string()))?;
 
-    let mut chart = ChartBuilder::on(&root)
-        .caption(
-            format!(
-                "{} Volatility Term Structure - Strike ${:.2}",
-                symbol, strike
-            ),
-            ("sans-serif", 30).into_font(),
-        )
-        .margin(10)
-        .x_label_area_size(40)
-        .y_label_area_size(60)
-        .build_cartesian_2d(time_min..time_max, vol_min..vol_max)
-        .map_err(|e| OptionsError::Other(e.to_string()))?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption(
+                format!(
+                    "{} Volatility Term Structure - Strike ${:.2}",
+                    symbol, strike
+                ),
+                ("sans-serif", 30).into_font(),
+            )
+            .margin(10)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(time_min..time_max, vol_min..vol_max)
+            .map_err(|e| OptionsError::Other(e.to_string()))?;
 
-    chart
-        .configure_mesh()
-        .x_desc("Time to Expiration (Years)")
-        .y_desc("Implied Volatility")
-        .axis_desc_style(("sans-serif", 15))
-        .draw()
-        .map_err(|e| OptionsError::Other(e.to_string()))?;
+        chart
+            .configure_mesh()
+            .x_desc("Time to Expiration (Years)")
+            .y_desc("Implied Volatility")
+            .axis_desc_style(("sans-serif", 15))
+            .draw()
+            .map_err(|e| OptionsError::Other(e.to_string()))?;
 
-    chart
-        .draw_series(LineSeries::new(
-            valid_points.iter().map(|&(t, v)| (t, v)),
-            &BLUE,
-        ))
-        .map_err(|e| OptionsError::Other(e.to_string()))?;
+        chart
+            .draw_series(LineSeries::new(
+                valid_points.iter().map(|&(t, v)| (t, v)),
+                &BLUE,
+            ))
+            .map_err(|e| OptionsError::Other(e.to_string()))?;
+
+        chart
+            .draw_series(
+                valid_points
+                    .iter()
+                    .map(|&(t, v)| Circle::new((t, v), 3, BLUE.filled())),
+            )
+            .map_err(|e| OptionsError::Other(e.to_string()))?;
 
-    chart
-        .draw_series(
-            valid_points
-                .iter()
-                .map(|&(t, v)| Circle::new((t, v), 3, BLUE.filled())),
+        root.draw_text(
+            &format!(
+                "Generated: {}",
+                chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+            ),
+            &TextStyle::from(("sans-serif", 15)).color(&BLACK),
+            (10, 570),
         )
         .map_err(|e| OptionsError::Other(e.to_string()))?;
 
-    root.draw_text(
-        &format!(
-            "Generated: {}",
-            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
-        ),
-        &TextStyle::from(("sans-serif", 15)).color(&BLACK),
-        (10, 570),
-    )
-    .map_err(|e| OptionsError::Other(e.to_string()))?;
-
-    root.present()
-        .map_err(|e| OptionsError::Other(e.to_string()))?;
+        root.present()
+            .map_err(|e| OptionsError::Other(e.to_string()))?;
+    }
 
-    Ok(())
+    Ok(ColorImage::from_rgb(
+        [width as usize, height as usize],
+        &buffer,
+    ))
 }
 
 pub fn plot_volatility_surface<P: AsRef<Path>>(
@@ -260,7 +348,81 @@ pub fn plot_volatility_surface<P: AsRef<Path>>(
     Ok(())
 }
 
+fn placeholder_image(width: u32, height: u32, title: &str, message: &str) -> Result<ColorImage> {
+    let mut buffer = vec![0u8; (width * height * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area();
+        root.fill(&WHITE)
+            .map_err(|e| OptionsError::Other(e.to_string()))?;
+
+        root.draw_text(
+            title,
+            &TextStyle::from(("sans-serif", 30)).color(&BLACK),
+            (20, 20),
+        )
+        .map_err(|e| OptionsError::Other(e.to_string()))?;
+
+        root.draw_text(
+            message,
+            &TextStyle::from(("sans-serif", 20)).color(&BLACK),
+            (20, height as i32 / 2),
+        )
+        .map_err(|e| OptionsError::Other(e.to_string()))?;
+
+        root.present()
+            .map_err(|e| OptionsError::Other(e.to_string()))?;
+    }
+
+    Ok(ColorImage::from_rgb(
+        [width as usize, height as usize],
+        &buffer,
+    ))
+}
+
 pub fn plot_volatility_surface_in_memory(surface: &VolatilitySurface) -> Result<ColorImage> {
+    plot_volatility_surface_in_memory_at(surface, None)
+}
+
+pub fn plot_volatility_surface_in_memory_styled(
+    surface: &VolatilitySurface,
+    style: &PlotStyle,
+) -> Result<ColorImage> {
+    render_volatility_surface(surface, None, style)
+}
+
+pub fn plot_volatility_surface_in_memory_at(
+    surface: &VolatilitySurface,
+    render_resolution: Option<(usize, usize)>,
+) -> Result<ColorImage> {
+    render_volatility_surface(surface, render_resolution, &PlotStyle::default())
+}
+
+fn render_volatility_surface(
+    surface: &VolatilitySurface,
+    render_resolution: Option<(usize, usize)>,
+    style: &PlotStyle,
+) -> Result<ColorImage> {
+    if surface.strikes.is_empty()
+        || surface.expirations.is_empty()
+        || surface.volatilities.iter().all(|v| !v.is_finite())
+    {
+        return placeholder_image(
+            style.width,
+            style.height,
+            &format!("{} Volatility Surface", surface.symbol),
+            "No data available yet",
+        );
+    }
+
+    let resampled;
+    let surface = match render_resolution {
+        Some((n_expiries, n_strikes)) => {
+            resampled = surface.resample(n_expiries, n_strikes)?;
+            &resampled
+        }
+        None => surface,
+    };
+
     let now = chrono::Utc::now();
     let times_to_expiration: Vec<f64> = surface
         .expirations
@@ -305,9 +467,8 @@ pub fn plot_volatility_surface_in_memory(surface: &VolatilitySurface) -> Result<
     let vol_min = (min_vol - 0.1 * vol_range).max(0.0);
     let vol_max = max_vol + 0.1 * vol_range;
 
-    let width = 1200u32;
-    let height = 900u32;
-    let mut buffer = vec![0u8; (width * height * 4) as usize];
+    let (width, height) = (style.width, style.height);
+    let mut buffer = vec![0u8; (width * height * 3) as usize];
     {
         let root = BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area();
         root.fill(&WHITE)
@@ -316,7 +477,7 @@ pub fn plot_volatility_surface_in_memory(surface: &VolatilitySurface) -> Result<
         let mut chart = ChartBuilder::on(&root)
             .caption(
                 format!("{} Volatility Surface", surface.symbol),
-                ("sans-serif", 30).into_font(),
+                (style.font.as_str(), style.title_size).into_font(),
             )
             .margin(10)
             .x_label_area_size(40)
@@ -328,11 +489,11 @@ pub fn plot_volatility_surface_in_memory(surface: &VolatilitySurface) -> Result<
             .configure_mesh()
             .x_desc("Strike Price")
             .y_desc("Time to Expiration (Years)")
-            .axis_desc_style(("sans-serif", 15))
+            .axis_desc_style((style.font.as_str(), 15))
             .draw()
             .map_err(|e| OptionsError::Other(e.to_string()))?;
 
-        let color_gradient = colorous::VIRIDIS;
+        let color_gradient = style.colormap.gradient();
 
         for (i, &time) in times_to_expiration.iter().enumerate() {
             for (j, &strike) in surface.strikes.iter().enumerate() {
@@ -362,9 +523,9 @@ pub fn plot_volatility_surface_in_memory(surface: &VolatilitySurface) -> Result<
         }
 
         let color_bar_width = 20;
-        let color_bar_height = 400;
-        let color_bar_x = 750;
-        let color_bar_y = 100;
+        let color_bar_height = height as i32 * 4 / 9;
+        let color_bar_x = width as i32 * 5 / 8;
+        let color_bar_y = height as i32 / 9;
 
         for i in 0..color_bar_height {
             let normalized_pos = 1.0 - (i as f64 / color_bar_height as f64);
@@ -383,14 +544,14 @@ pub fn plot_volatility_surface_in_memory(surface: &VolatilitySurface) -> Result<
 
         root.draw_text(
             &format!("{:.2}", vol_max),
-            &TextStyle::from(("sans-serif", 12)).color(&BLACK),
+            &TextStyle::from((style.font.as_str(), 12)).color(&BLACK),
             (color_bar_x + color_bar_width + 5, color_bar_y),
         )
         .map_err(|e| OptionsError::Other(e.to_string()))?;
 
         root.draw_text(
             &format!("{:.2}", vol_min),
-            &TextStyle::from(("sans-serif", 12)).color(&BLACK),
+            &TextStyle::from((style.font.as_str(), 12)).color(&BLACK),
             (
                 color_bar_x + color_bar_width + 5,
                 color_bar_y + color_bar_height,
@@ -400,7 +561,7 @@ pub fn plot_volatility_surface_in_memory(surface: &VolatilitySurface) -> Result<
 
         root.draw_text(
             "IV",
-            &TextStyle::from(("sans-serif", 12)).color(&BLACK),
+            &TextStyle::from((style.font.as_str(), 12)).color(&BLACK),
             (
                 color_bar_x + color_bar_width + 5,
                 color_bar_y + color_bar_height / 2,
@@ -413,8 +574,8 @@ pub fn plot_volatility_surface_in_memory(surface: &VolatilitySurface) -> Result<
                 "Generated: {}",
                 chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
             ),
-            &TextStyle::from(("sans-serif", 15)).color(&BLACK),
-            (10, 570),
+            &TextStyle::from((style.font.as_str(), 15)).color(&BLACK),
+            (10, height as i32 - 30),
         )
         .map_err(|e| OptionsError::Other(e.to_string()))?;
 
@@ -422,8 +583,67 @@ pub fn plot_volatility_surface_in_memory(surface: &VolatilitySurface) -> Result<
             .map_err(|e| OptionsError::Other(e.to_string()))?;
     }
 
-    Ok(ColorImage::from_rgba_unmultiplied(
+    Ok(ColorImage::from_rgb(
         [width as usize, height as usize],
         &buffer,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distinct_colors(img: &ColorImage) -> usize {
+        let mut colors: Vec<[u8; 4]> = img.pixels.iter().map(|p| p.to_array()).collect();
+        colors.sort_unstable();
+        colors.dedup();
+        colors.len()
+    }
+
+    #[test]
+    fn renders_term_structure_in_memory() {
+        let times = Array1::from_vec(vec![0.05, 0.1, 0.25, 0.5, 1.0]);
+        let vols = Array1::from_vec(vec![0.3, 0.27, f64::NAN, 0.24, 0.23]);
+
+        let img = plot_volatility_term_structure_in_memory(&times, &vols, "SPY", 500.0).unwrap();
+        assert!(img.size[0] > 0 && img.size[1] > 0);
+        assert_eq!(img.pixels.len(), img.size[0] * img.size[1]);
+        assert!(distinct_colors(&img) > 2);
+
+        let empty = Array1::from_vec(vec![f64::NAN; 5]);
+        assert!(plot_volatility_term_structure_in_memory(&times, &empty, "SPY", 500.0).is_err());
+    }
+
+    #[test]
+    fn styled_plots_use_the_requested_size() {
+        let style = PlotStyle {
+            width: 640,
+            height: 360,
+            colormap: Colormap::Plasma,
+            ..PlotStyle::default()
+        };
+        let expiration = chrono::Utc::now() + chrono::Duration::days(30);
+        let strikes = Array1::from_vec(vec![90.0, 100.0, 110.0]);
+        let vols = Array1::from_vec(vec![0.28, 0.24, 0.25]);
+
+        let smile =
+            plot_volatility_smile_in_memory_styled(&strikes, &vols, "SPY", &expiration, &style)
+                .unwrap();
+        assert_eq!(smile.size, [640, 360]);
+
+        let surface = VolatilitySurface {
+            symbol: "SPY".to_string(),
+            expirations: vec![expiration, expiration + chrono::Duration::days(30)],
+            strikes: strikes.to_vec(),
+            volatilities: ndarray::array![[0.28, 0.24, 0.25], [0.27, 0.23, 0.24]],
+            timestamp: chrono::Utc::now(),
+            version: 1,
+        };
+        let img = plot_volatility_surface_in_memory_styled(&surface, &style).unwrap();
+        assert_eq!(img.size, [640, 360]);
+        assert!(distinct_colors(&img) > 2);
+
+        let default = plot_volatility_smile_in_memory(&strikes, &vols, "SPY", &expiration).unwrap();
+        assert_eq!(default.size, [1200, 900]);
+    }
+}
@@ -1,8 +1,13 @@
 use crate::error::{OptionsError, Result};
-use crate::models::volatility::{ImpliedVolatility, VolatilitySurface};
+use crate::models::volatility::{
+    batch_implied_volatility_quotes_with_model, ImpliedVolatility, VolatilitySurface,
+};
 use crate::models::{OptionContract, OptionQuote, OptionType};
+use crate::utils::{BlackScholes, OptionModel};
 use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
 use polars::prelude::*;
+use std::future::Future;
 use std::path::Path;
 
 pub fn quotes_to_dataframe(quotes: &[OptionQuote]) -> Result<DataFrame> {
@@ -365,6 +370,15 @@ pub fn calculate_volatility_surface_with_polars(
     quotes: &[OptionQuote],
     symbol: &str,
     risk_free_rate: f64,
+) -> Result<VolatilitySurface> {
+    calculate_volatility_surface_with_polars_model(quotes, symbol, risk_free_rate, &BlackScholes)
+}
+
+pub fn calculate_volatility_surface_with_polars_model(
+    quotes: &[OptionQuote],
+    symbol: &str,
+    risk_free_rate: f64,
+    model: &dyn OptionModel,
 ) -> Result<VolatilitySurface> {
     let df = quotes_to_dataframe(quotes)?;
 
@@ -389,12 +403,11 @@ pub fn calculate_volatility_surface_with_polars(
 
     let filtered_quotes = dataframe_to_quotes(&filtered_df)?;
 
-    let mut ivs = Vec::new();
-    for q in &filtered_quotes {
-        if let Ok(iv) = ImpliedVolatility::from_quote(q, risk_free_rate, 0.0) {
-            ivs.push(iv);
-        }
-    }
+    let ivs: Vec<ImpliedVolatility> =
+        batch_implied_volatility_quotes_with_model(&filtered_quotes, risk_free_rate, 0.0, model)
+            .into_iter()
+            .filter_map(|iv| iv.ok())
+            .collect();
 
     if ivs.is_empty() {
         return Err(OptionsError::VolatilityError(
@@ -408,3 +421,168 @@ pub fn calculate_volatility_surface_with_polars(
 
     Ok(surface)
 }
+
+#[derive(Debug, Clone, Copy)]
+pub struct CombinedBuildOptions {
+    pub max_concurrency: usize,
+    pub preserve_order: bool,
+}
+
+impl Default for CombinedBuildOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 4,
+            preserve_order: true,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CombinedBuildResult {
+    pub dataframe: Option<DataFrame>,
+    pub succeeded: Vec<String>,
+    pub errors: Vec<(String, OptionsError)>,
+}
+
+pub async fn build_combined_quotes_dataframe<F, Fut>(
+    symbols: &[String],
+    options: CombinedBuildOptions,
+    fetch: F,
+) -> CombinedBuildResult
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Vec<OptionQuote>>>,
+{
+    let fetch = &fetch;
+    let mut results: Vec<(usize, String, Result<Vec<OptionQuote>>)> =
+        stream::iter(symbols.iter().cloned().enumerate())
+            .map(|(i, symbol)| async move {
+                let result = fetch(symbol.clone()).await;
+                (i, symbol, result)
+            })
+            .buffer_unordered(options.max_concurrency.max(1))
+            .collect()
+            .await;
+
+    if options.preserve_order {
+        results.sort_by_key(|(i, _, _)| *i);
+    }
+
+    let mut dataframe: Option<DataFrame> = None;
+    let mut succeeded = Vec::new();
+    let mut errors = Vec::new();
+
+    for (_, symbol, result) in results {
+        let df = result.and_then(|quotes| {
+            if quotes.is_empty() {
+                Err(OptionsError::Other(format!(
+                    "No option quotes returned for {}",
+                    symbol
+                )))
+            } else {
+                quotes_to_dataframe(&quotes)
+            }
+        });
+
+        match df {
+            Ok(df) => {
+                let merged = match dataframe.as_mut() {
+                    Some(acc) => acc.vstack_mut(&df).map(|_| ()).map_err(OptionsError::from),
+                    None => {
+                        dataframe = Some(df);
+                        Ok(())
+                    }
+                };
+                match merged {
+                    Ok(()) => succeeded.push(symbol),
+                    Err(e) => errors.push((symbol, e)),
+                }
+            }
+            Err(e) => errors.push((symbol, e)),
+        }
+    }
+
+    CombinedBuildResult {
+        dataframe,
+        succeeded,
+        errors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quotes_for(symbol: &str, count: usize) -> Vec<OptionQuote> {
+        let expiration = Utc::now() + chrono::Duration::days(30);
+        (0..count)
+            .map(|i| {
+                let contract = OptionContract::new(
+                    symbol.to_string(),
+                    OptionType::Call,
+                    100.0 + i as f64,
+                    expiration,
+                )
+                .unwrap();
+                OptionQuote::new(contract, 1.0, 1.1, 1.05, 10, 10, 100.0)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn combined_build_splits_failures_and_keeps_order() {
+        let symbols: Vec<String> = ["AAPL", "BAD", "MSFT", "SPY"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        // Earlier symbols finish last, so completion order is the reverse of input.
+        let fetch = |symbol: String| async move {
+            let delay = match symbol.as_str() {
+                "AAPL" => 60,
+                "MSFT" => 30,
+                _ => 0,
+            };
+            tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+            if symbol == "BAD" {
+                Err(OptionsError::Other("no chain".to_string()))
+            } else {
+                Ok(quotes_for(&symbol, 2))
+            }
+        };
+
+        let result = build_combined_quotes_dataframe(
+            &symbols,
+            CombinedBuildOptions {
+                max_concurrency: 4,
+                preserve_order: true,
+            },
+            fetch,
+        )
+        .await;
+
+        assert_eq!(result.succeeded, ["AAPL", "MSFT", "SPY"]);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].0, "BAD");
+
+        let df = result.dataframe.unwrap();
+        let rows: Vec<&str> = df
+            .column("symbol")
+            .unwrap()
+            .utf8()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(rows, ["AAPL", "AAPL", "MSFT", "MSFT", "SPY", "SPY"]);
+
+        let unordered = build_combined_quotes_dataframe(
+            &symbols,
+            CombinedBuildOptions {
+                max_concurrency: 4,
+                preserve_order: false,
+            },
+            fetch,
+        )
+        .await;
+        assert_eq!(unordered.succeeded, ["SPY", "MSFT", "AAPL"]);
+    }
+}
@@ -0,0 +1,278 @@
+use super::black_scholes::{self, get_normal, IvSolverConfig};
+use statrs::distribution::{Continuous, ContinuousCDF};
+
+pub trait OptionModel: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn price(&self, s: f64, k: f64, t: f64, r: f64, sigma: f64, is_call: bool) -> f64;
+
+    fn delta(&self, s: f64, k: f64, t: f64, r: f64, sigma: f64, is_call: bool) -> f64;
+
+    fn vega(&self, s: f64, k: f64, t: f64, r: f64, sigma: f64) -> f64;
+
+    fn vol_bounds(&self, s: f64, k: f64) -> (f64, f64);
+
+    fn initial_guess(&self, s: f64, k: f64) -> f64;
+
+    fn implied_vol(
+        &self,
+        price_target: f64,
+        s: f64,
+        k: f64,
+        t: f64,
+        r: f64,
+        is_call: bool,
+    ) -> Result<f64, String> {
+        if price_target <= 0.0 || t <= 0.0 || s <= 0.0 || k <= 0.0 {
+            return Err("Invalid input".to_string());
+        }
+
+        // Same intrinsic floor as the Black-Scholes path, so every model agrees
+        // on which quotes are clamped.
+        let adjusted_price = price_target.max(black_scholes::calculate_intrinsic(s, k, is_call));
+
+        let (sigma_low, sigma_high) = self.vol_bounds(s, k);
+        let cfg = IvSolverConfig {
+            sigma_low,
+            sigma_high,
+            initial_guess: self.initial_guess(s, k),
+            ..IvSolverConfig::default()
+        };
+        let solution = black_scholes::solve_iv(
+            &cfg,
+            adjusted_price,
+            |sigma| self.price(s, k, t, r, sigma, is_call),
+            |sigma| self.vega(s, k, t, r, sigma),
+        );
+
+        if solution.converged {
+            return Ok(solution.sigma);
+        }
+
+        Err("Implied volatility did not converge".to_string())
+    }
+
+    // A continuous yield only scales the spot, so the defaults price off the
+    // dividend-adjusted spot s * e^{-qt}.
+    #[allow(clippy::too_many_arguments)]
+    fn delta_with_yield(
+        &self,
+        s: f64,
+        k: f64,
+        t: f64,
+        r: f64,
+        q: f64,
+        sigma: f64,
+        is_call: bool,
+    ) -> f64 {
+        let carry = (-q * t).exp();
+        carry * self.delta(s * carry, k, t, r, sigma, is_call)
+    }
+
+    fn vega_with_yield(&self, s: f64, k: f64, t: f64, r: f64, q: f64, sigma: f64) -> f64 {
+        self.vega(s * (-q * t).exp(), k, t, r, sigma)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn implied_vol_with_yield(
+        &self,
+        price_target: f64,
+        s: f64,
+        k: f64,
+        t: f64,
+        r: f64,
+        q: f64,
+        is_call: bool,
+    ) -> Result<f64, String> {
+        self.implied_vol(price_target, s * (-q * t).exp(), k, t, r, is_call)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlackScholes;
+
+impl OptionModel for BlackScholes {
+    fn name(&self) -> &'static str {
+        "black-scholes"
+    }
+
+    fn price(&self, s: f64, k: f64, t: f64, r: f64, sigma: f64, is_call: bool) -> f64 {
+        black_scholes::price(s, k, t, r, sigma, is_call)
+    }
+
+    fn delta(&self, s: f64, k: f64, t: f64, r: f64, sigma: f64, is_call: bool) -> f64 {
+        black_scholes::delta(s, k, t, r, sigma, is_call)
+    }
+
+    fn vega(&self, s: f64, k: f64, t: f64, r: f64, sigma: f64) -> f64 {
+        black_scholes::vega(s, k, t, r, sigma)
+    }
+
+    fn vol_bounds(&self, _s: f64, _k: f64) -> (f64, f64) {
+        (1e-4, 5.0)
+    }
+
+    fn initial_guess(&self, _s: f64, _k: f64) -> f64 {
+        0.2
+    }
+
+    fn implied_vol(
+        &self,
+        price_target: f64,
+        s: f64,
+        k: f64,
+        t: f64,
+        r: f64,
+        is_call: bool,
+    ) -> Result<f64, String> {
+        black_scholes::implied_volatility(price_target, s, k, t, r, is_call)
+    }
+
+    fn delta_with_yield(
+        &self,
+        s: f64,
+        k: f64,
+        t: f64,
+        r: f64,
+        q: f64,
+        sigma: f64,
+        is_call: bool,
+    ) -> f64 {
+        black_scholes::delta_with_yield(s, k, t, r, q, sigma, is_call)
+    }
+
+    fn vega_with_yield(&self, s: f64, k: f64, t: f64, r: f64, q: f64, sigma: f64) -> f64 {
+        black_scholes::vega_with_yield(s, k, t, r, q, sigma)
+    }
+
+    fn implied_vol_with_yield(
+        &self,
+        price_target: f64,
+        s: f64,
+        k: f64,
+        t: f64,
+        r: f64,
+        q: f64,
+        is_call: bool,
+    ) -> Result<f64, String> {
+        black_scholes::implied_volatility_with_yield(price_target, s, k, t, r, q, is_call)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Bachelier;
+
+impl Bachelier {
+    fn forward(s: f64, t: f64, r: f64) -> f64 {
+        s * (r * t).exp()
+    }
+}
+
+impl OptionModel for Bachelier {
+    fn name(&self) -> &'static str {
+        "bachelier"
+    }
+
+    fn price(&self, s: f64, k: f64, t: f64, r: f64, sigma: f64, is_call: bool) -> f64 {
+        let n = get_normal();
+        let f = Self::forward(s, t, r);
+        let discount = (-r * t).exp();
+        let std_dev = sigma * t.sqrt();
+        if std_dev <= 0.0 {
+            let intrinsic = if is_call {
+                (f - k).max(0.0)
+            } else {
+                (k - f).max(0.0)
+            };
+            return discount * intrinsic;
+        }
+        let d = (f - k) / std_dev;
+        if is_call {
+            discount * ((f - k) * n.cdf(d) + std_dev * n.pdf(d))
+        } else {
+            discount * ((k - f) * n.cdf(-d) + std_dev * n.pdf(d))
+        }
+    }
+
+    fn delta(&self, s: f64, k: f64, t: f64, r: f64, sigma: f64, is_call: bool) -> f64 {
+        let n = get_normal();
+        let f = Self::forward(s, t, r);
+        let d = (f - k) / (sigma * t.sqrt());
+        if is_call {
+            n.cdf(d)
+        } else {
+            n.cdf(d) - 1.0
+        }
+    }
+
+    fn vega(&self, s: f64, k: f64, t: f64, r: f64, sigma: f64) -> f64 {
+        let n = get_normal();
+        let f = Self::forward(s, t, r);
+        let d = (f - k) / (sigma * t.sqrt());
+        (-r * t).exp() * t.sqrt() * n.pdf(d)
+    }
+
+    fn vol_bounds(&self, s: f64, _k: f64) -> (f64, f64) {
+        (1e-8 * s, 10.0 * s)
+    }
+
+    fn initial_guess(&self, s: f64, _k: f64) -> f64 {
+        0.2 * s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_round_trips(model: &dyn OptionModel, s: f64, sigmas: &[f64]) {
+        let (t, r) = (0.5, 0.03);
+        for &sigma in sigmas {
+            for k in [0.8 * s, s, 1.2 * s] {
+                for is_call in [true, false] {
+                    let price = model.price(s, k, t, r, sigma, is_call);
+                    if price <= black_scholes::calculate_intrinsic(s, k, is_call) {
+                        // Floored to intrinsic; covered by the clamp test below.
+                        continue;
+                    }
+                    let iv = model.implied_vol(price, s, k, t, r, is_call).unwrap();
+                    let repriced = model.price(s, k, t, r, iv, is_call);
+                    assert!(
+                        (repriced - price).abs() < 1e-5,
+                        "{} k={} call={}: {} vs {}",
+                        model.name(),
+                        k,
+                        is_call,
+                        repriced,
+                        price
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn black_scholes_price_round_trips_through_implied_vol() {
+        assert_round_trips(&BlackScholes, 100.0, &[0.1, 0.25, 0.6]);
+    }
+
+    #[test]
+    fn bachelier_price_round_trips_through_implied_vol() {
+        assert_round_trips(&Bachelier, 100.0, &[10.0, 25.0, 60.0]);
+    }
+
+    #[test]
+    fn default_implied_vol_clamps_like_black_scholes() {
+        // Deep ITM put priced below K - S: the BS path floors it at the undiscounted
+        // intrinsic, and the trait default must do the same.
+        let (s, k, t, r) = (100.0, 150.0, 1.0, 0.05);
+        let price = 48.0;
+        let bs = BlackScholes.implied_vol(price, s, k, t, r, false).unwrap();
+        let floored = black_scholes::implied_volatility(50.0, s, k, t, r, false).unwrap();
+        assert!((bs - floored).abs() < 1e-9);
+
+        let bachelier = Bachelier.implied_vol(price, s, k, t, r, false).unwrap();
+        let repriced = Bachelier.price(s, k, t, r, bachelier, false);
+        assert!((repriced - 50.0).abs() < 1e-5, "{}", repriced);
+    }
+}
@@ -0,0 +1,371 @@
+use crate::error::{OptionsError, Result};
+use crate::models::{OptionQuote, OptionType};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskNeutralDensity {
+    pub expiration: DateTime<Utc>,
+    pub strikes: Vec<f64>,
+    pub density: Vec<f64>,
+}
+
+impl RiskNeutralDensity {
+    pub fn integrate_between(&self, lo: f64, hi: f64) -> f64 {
+        if self.strikes.len() < 2 || hi <= lo {
+            return 0.0;
+        }
+
+        let mut total = 0.0;
+        for i in 0..self.strikes.len() - 1 {
+            let (k0, k1) = (self.strikes[i], self.strikes[i + 1]);
+            let a = k0.max(lo);
+            let b = k1.min(hi);
+            if b <= a {
+                continue;
+            }
+            let fa = self.density_at(a);
+            let fb = self.density_at(b);
+            total += 0.5 * (fa + fb) * (b - a);
+        }
+        total
+    }
+
+    pub fn density_at(&self, strike: f64) -> f64 {
+        let n = self.strikes.len();
+        if n == 0 || strike < self.strikes[0] || strike > self.strikes[n - 1] {
+            return 0.0;
+        }
+        let idx = self.strikes.partition_point(|&k| k <= strike);
+        if idx == 0 {
+            return self.density[0];
+        }
+        if idx >= n {
+            return self.density[n - 1];
+        }
+        let (k0, k1) = (self.strikes[idx - 1], self.strikes[idx]);
+        let (f0, f1) = (self.density[idx - 1], self.density[idx]);
+        if k1 - k0 <= 0.0 {
+            return f0;
+        }
+        f0 + (f1 - f0) * (strike - k0) / (k1 - k0)
+    }
+
+    /// Cumulative probability at each strike. The final value is only close to 1.0
+    /// when the listed strikes span the bulk of the distribution; a truncated strike
+    /// range leaves tail mass unaccounted for.
+    pub fn cdf(&self) -> Vec<f64> {
+        let mut cumulative = Vec::with_capacity(self.strikes.len());
+        let mut total = 0.0;
+        for i in 0..self.strikes.len() {
+            if i > 0 {
+                let h = self.strikes[i] - self.strikes[i - 1];
+                total += 0.5 * (self.density[i - 1] + self.density[i]) * h;
+            }
+            cumulative.push(total.clamp(0.0, 1.0));
+        }
+        cumulative
+    }
+
+    pub fn prob_above(&self, level: f64) -> f64 {
+        let (Some(&lo), Some(&hi)) = (self.strikes.first(), self.strikes.last()) else {
+            return f64::NAN;
+        };
+        if level <= lo {
+            return 1.0;
+        }
+        if level >= hi {
+            return 0.0;
+        }
+        self.integrate_between(level, hi).clamp(0.0, 1.0)
+    }
+
+    pub fn prob_between(&self, lo: f64, hi: f64) -> f64 {
+        let (Some(&k_min), Some(&k_max)) = (self.strikes.first(), self.strikes.last()) else {
+            return f64::NAN;
+        };
+        let (lo, hi) = (lo.max(k_min), hi.min(k_max));
+        if hi <= lo {
+            return 0.0;
+        }
+        self.integrate_between(lo, hi).clamp(0.0, 1.0)
+    }
+
+    fn expectation<F: Fn(f64) -> f64>(&self, g: F) -> f64 {
+        self.strikes
+            .windows(2)
+            .zip(self.density.windows(2))
+            .map(|(k, f)| 0.5 * (g(k[0]) * f[0] + g(k[1]) * f[1]) * (k[1] - k[0]))
+            .sum()
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.strikes.len() < 2 {
+            return f64::NAN;
+        }
+        self.expectation(|k| k)
+    }
+
+    pub fn variance(&self) -> f64 {
+        let mean = self.mean();
+        if !mean.is_finite() {
+            return f64::NAN;
+        }
+        self.expectation(|k| (k - mean).powi(2))
+    }
+
+    pub fn quantile(&self, p: f64) -> f64 {
+        if self.strikes.is_empty() || !(0.0..=1.0).contains(&p) {
+            return f64::NAN;
+        }
+        let cdf = self.cdf();
+        let idx = cdf.partition_point(|&c| c < p);
+        if idx == 0 {
+            return self.strikes[0];
+        }
+        if idx >= cdf.len() {
+            return self.strikes[cdf.len() - 1];
+        }
+        let (c0, c1) = (cdf[idx - 1], cdf[idx]);
+        let (k0, k1) = (self.strikes[idx - 1], self.strikes[idx]);
+        if c1 - c0 <= 0.0 {
+            return k1;
+        }
+        k0 + (k1 - k0) * (p - c0) / (c1 - c0)
+    }
+
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        writeln!(file, "strike,density,cdf")?;
+        for ((strike, density), cdf) in self.strikes.iter().zip(&self.density).zip(self.cdf()) {
+            writeln!(file, "{},{},{}", strike, density, cdf)?;
+        }
+        file.flush()?;
+        Ok(())
+    }
+}
+
+const MIN_DENSITY_STRIKES: usize = 5;
+
+pub fn risk_neutral_density(quotes: &[OptionQuote], r: f64) -> Result<RiskNeutralDensity> {
+    let underlying = quotes
+        .iter()
+        .map(|q| q.underlying_price)
+        .find(|&s| s > 0.0)
+        .unwrap_or(0.0);
+    risk_neutral_density_otm(quotes, underlying, r)
+}
+
+pub fn risk_neutral_density_otm(
+    quotes: &[OptionQuote],
+    underlying: f64,
+    r: f64,
+) -> Result<RiskNeutralDensity> {
+    let Some(first) = quotes.iter().find(|q| q.mid_price() > 0.0) else {
+        return Err(OptionsError::VolatilityError(
+            "No quotes available for risk-neutral density".to_string(),
+        ));
+    };
+    let expiration = first.contract.expiration;
+    let t = first.contract.time_to_expiration();
+    let discount = (-r * t).exp();
+    let forward = underlying / discount;
+
+    // Out-of-the-money puts are mapped onto the call price curve through
+    // put-call parity. Without an underlying price only calls can be used.
+    let mut points: Vec<(f64, f64)> = quotes
+        .iter()
+        .filter(|q| q.contract.expiration == expiration && q.mid_price() > 0.0)
+        .filter_map(|q| {
+            let k = q.contract.strike;
+            match q.contract.option_type {
+                OptionType::Call if underlying <= 0.0 || k >= forward => Some((k, q.mid_price())),
+                OptionType::Put if underlying > 0.0 && k < forward => {
+                    Some((k, q.mid_price() + underlying - k * discount))
+                }
+                _ => None,
+            }
+        })
+        .collect();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut strikes: Vec<f64> = Vec::with_capacity(points.len());
+    let mut prices: Vec<f64> = Vec::with_capacity(points.len());
+    let mut counts: Vec<f64> = Vec::with_capacity(points.len());
+    for (k, price) in points {
+        if strikes.last() == Some(&k) {
+            let last = prices.len() - 1;
+            prices[last] += price;
+            counts[last] += 1.0;
+        } else {
+            strikes.push(k);
+            prices.push(price);
+            counts.push(1.0);
+        }
+    }
+    for (p, c) in prices.iter_mut().zip(&counts) {
+        *p /= c;
+    }
+
+    if strikes.len() < MIN_DENSITY_STRIKES {
+        return Err(OptionsError::VolatilityError(format!(
+            "At least {} strikes are required for risk-neutral density, found {}",
+            MIN_DENSITY_STRIKES,
+            strikes.len()
+        )));
+    }
+
+    let growth = (r * t).exp();
+
+    let n = strikes.len();
+    let mut density = vec![0.0; n];
+    for i in 1..n - 1 {
+        let h1 = strikes[i] - strikes[i - 1];
+        let h2 = strikes[i + 1] - strikes[i];
+        let second =
+            2.0 * ((prices[i + 1] - prices[i]) / h2 - (prices[i] - prices[i - 1]) / h1) / (h1 + h2);
+        density[i] = (growth * second).max(0.0);
+    }
+
+    let mut rnd = RiskNeutralDensity {
+        expiration,
+        strikes,
+        density,
+    };
+
+    let total = rnd.integrate_between(f64::NEG_INFINITY, f64::INFINITY);
+    if total <= 0.0 || !total.is_finite() {
+        return Err(OptionsError::VolatilityError(
+            "Risk-neutral density integrates to zero".to_string(),
+        ));
+    }
+    for d in rnd.density.iter_mut() {
+        *d /= total;
+    }
+
+    Ok(rnd)
+}
+
+pub fn probability_itm(density: &RiskNeutralDensity, strike: f64, is_call: bool) -> f64 {
+    let p = if is_call {
+        density.integrate_between(strike, f64::INFINITY)
+    } else {
+        density.integrate_between(f64::NEG_INFINITY, strike)
+    };
+    p.clamp(0.0, 1.0)
+}
+
+pub fn break_even(option: &OptionQuote) -> f64 {
+    let premium = option.mid_price();
+    match option.contract.option_type {
+        OptionType::Call => option.contract.strike + premium,
+        OptionType::Put => option.contract.strike - premium,
+    }
+}
+
+pub fn probability_of_profit(density: &RiskNeutralDensity, option: &OptionQuote) -> f64 {
+    probability_itm(density, break_even(option), option.contract.is_call())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OptionContract;
+    use crate::utils::{market_close_utc, price};
+
+    const SPOT: f64 = 100.0;
+    const R: f64 = 0.03;
+    const SIGMA: f64 = 0.2;
+
+    fn lognormal_chain() -> (Vec<OptionQuote>, f64) {
+        let expiration = market_close_utc((Utc::now() + chrono::Duration::days(91)).date_naive());
+        let mut quotes = Vec::new();
+        let mut t = 0.0;
+        for i in 0..=150 {
+            let strike = 50.0 + i as f64;
+            for option_type in [OptionType::Call, OptionType::Put] {
+                let contract =
+                    OptionContract::new("XYZ".to_string(), option_type, strike, expiration)
+                        .unwrap();
+                t = contract.time_to_expiration();
+                let mid = price(SPOT, strike, t, R, SIGMA, contract.is_call());
+                quotes.push(OptionQuote::new(contract, mid, mid, mid, 0, 0, SPOT));
+            }
+        }
+        (quotes, t)
+    }
+
+    fn lognormal_pdf(k: f64, t: f64) -> f64 {
+        let s = SIGMA * t.sqrt();
+        let z = ((k / SPOT).ln() - (R - 0.5 * SIGMA * SIGMA) * t) / s;
+        (-0.5 * z * z).exp() / (k * s * (2.0 * std::f64::consts::PI).sqrt())
+    }
+
+    fn triangle() -> RiskNeutralDensity {
+        let strikes: Vec<f64> = (0..=20).map(|i| 90.0 + i as f64).collect();
+        let density = strikes
+            .iter()
+            .map(|&k| (10.0 - (k - 100.0_f64).abs()) / 100.0)
+            .collect();
+        RiskNeutralDensity {
+            expiration: Utc::now(),
+            strikes,
+            density,
+        }
+    }
+
+    #[test]
+    fn recovers_a_lognormal_density_from_otm_quotes() {
+        let (mut quotes, t) = lognormal_chain();
+        quotes.push(quotes[80].clone());
+
+        let rnd = risk_neutral_density_otm(&quotes, SPOT, R).unwrap();
+        assert_eq!(rnd.strikes.len(), 151);
+        for k in [80.0, 90.0, 100.0, 110.0, 125.0] {
+            let i = rnd.strikes.iter().position(|&s| s == k).unwrap();
+            assert!(
+                (rnd.density[i] - lognormal_pdf(k, t)).abs() < 5e-4,
+                "strike {}",
+                k
+            );
+        }
+        assert!((rnd.mean() - SPOT * (R * t).exp()).abs() < 0.05);
+    }
+
+    #[test]
+    fn requires_enough_strikes() {
+        let (quotes, _) = lognormal_chain();
+        let otm_near_spot: Vec<OptionQuote> = quotes
+            .into_iter()
+            .filter(|q| (q.contract.strike - SPOT).abs() < 2.0)
+            .collect();
+        assert!(risk_neutral_density_otm(&otm_near_spot, SPOT, R).is_err());
+    }
+
+    #[test]
+    fn moments_of_a_symmetric_density() {
+        let rnd = triangle();
+        assert!((rnd.mean() - 100.0).abs() < 1e-9);
+        assert!((rnd.quantile(0.5) - rnd.mean()).abs() < 1e-9);
+        assert!((rnd.variance() - 100.0 / 6.0).abs() < 0.5);
+        assert!((rnd.cdf().last().unwrap() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn probability_above_the_median_is_one_half() {
+        let (quotes, _) = lognormal_chain();
+        let rnd = risk_neutral_density_otm(&quotes, SPOT, R).unwrap();
+        let median = rnd.quantile(0.5);
+        assert!((rnd.prob_above(median) - 0.5).abs() < 1e-3);
+
+        let symmetric = triangle();
+        assert!((symmetric.prob_above(100.0) - 0.5).abs() < 1e-9);
+        assert!((symmetric.prob_between(95.0, 105.0) - 0.75).abs() < 1e-9);
+        assert_eq!(symmetric.prob_above(80.0), 1.0);
+        assert_eq!(symmetric.prob_above(120.0), 0.0);
+        assert!((symmetric.prob_between(0.0, 1000.0) - 1.0).abs() < 1e-9);
+        assert_eq!(symmetric.prob_between(105.0, 95.0), 0.0);
+    }
+}
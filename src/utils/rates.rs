@@ -0,0 +1,193 @@
+use crate::error::{OptionsError, Result};
+use chrono::{Datelike, NaiveDate, Utc};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+const TREASURY_CSV_URL: &str = "https://home.treasury.gov/resource-center/data-chart-center/interest-rates/daily-treasury-rates.csv";
+
+const TREASURY_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Failures are cached too, so an unreachable feed costs one attempt per day
+// rather than one per surface refresh.
+static YIELD_CURVE_CACHE: Lazy<DashMap<NaiveDate, std::result::Result<YieldCurve, String>>> =
+    Lazy::new(DashMap::new);
+
+static TREASURY_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(TREASURY_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+});
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct YieldCurve {
+    pub date: NaiveDate,
+    pub points: Vec<(f64, f64)>,
+}
+
+impl YieldCurve {
+    pub fn from_points(date: NaiveDate, mut points: Vec<(f64, f64)>) -> Result<Self> {
+        points.retain(|(t, r)| t.is_finite() && *t > 0.0 && r.is_finite());
+        if points.is_empty() {
+            return Err(OptionsError::ParseError(
+                "Yield curve needs at least one valid point".to_string(),
+            ));
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        points.dedup_by(|a, b| a.0 == b.0);
+        Ok(Self { date, points })
+    }
+
+    pub fn rate_for(&self, maturity_years: f64) -> f64 {
+        let (first, last) = (self.points[0], self.points[self.points.len() - 1]);
+        if maturity_years <= first.0 {
+            return first.1;
+        }
+        if maturity_years >= last.0 {
+            return last.1;
+        }
+        let idx = self.points.partition_point(|&(t, _)| t <= maturity_years);
+        let ((t0, r0), (t1, r1)) = (self.points[idx - 1], self.points[idx]);
+        r0 + (r1 - r0) * (maturity_years - t0) / (t1 - t0)
+    }
+}
+
+fn tenor_years(label: &str) -> Option<f64> {
+    let label = label.trim().trim_matches('"');
+    let (value, unit) = label.split_once(' ')?;
+    let value: f64 = value.parse().ok()?;
+    let unit = unit.to_ascii_lowercase();
+    if unit.starts_with("wk") || unit.starts_with("week") {
+        Some(value * 7.0 / 365.0)
+    } else if unit.starts_with("mo") {
+        Some(value / 12.0)
+    } else if unit.starts_with("yr") || unit.starts_with("year") {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+pub fn parse_treasury_csv(text: &str) -> Result<YieldCurve> {
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let header = lines
+        .next()
+        .ok_or_else(|| OptionsError::ParseError("Empty Treasury yield CSV".to_string()))?;
+    let tenors: Vec<Option<f64>> = header.split(',').skip(1).map(tenor_years).collect();
+
+    let row = lines
+        .next()
+        .ok_or_else(|| OptionsError::ParseError("Treasury yield CSV has no rows".to_string()))?;
+    let mut fields = row.split(',');
+    let date_str = fields.next().unwrap_or_default().trim().trim_matches('"');
+    let date = NaiveDate::parse_from_str(date_str, "%m/%d/%Y")
+        .or_else(|_| NaiveDate::parse_from_str(date_str, "%Y-%m-%d"))
+        .map_err(|e| {
+            OptionsError::ParseError(format!("Invalid Treasury date '{}': {}", date_str, e))
+        })?;
+
+    let points = tenors
+        .into_iter()
+        .zip(fields)
+        .filter_map(|(tenor, value)| {
+            let rate = value.trim().trim_matches('"').parse::<f64>().ok()?;
+            Some((tenor?, rate / 100.0))
+        })
+        .collect();
+
+    YieldCurve::from_points(date, points)
+}
+
+async fn fetch_treasury_curve(year: i32) -> Result<YieldCurve> {
+    let url = format!(
+        "{}/{}/all?type=daily_treasury_yield_curve&field_tdr_date_value={}&_format=csv",
+        TREASURY_CSV_URL, year, year
+    );
+    debug!("Fetching Treasury par yield curve from {}", url);
+    let text = TREASURY_CLIENT
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| OptionsError::Other(format!("Failed to fetch Treasury yields: {}", e)))?
+        .error_for_status()
+        .map_err(|e| OptionsError::Other(format!("Treasury yields request failed: {}", e)))?
+        .text()
+        .await
+        .map_err(|e| OptionsError::Other(format!("Failed to read Treasury yields: {}", e)))?;
+    parse_treasury_csv(&text)
+}
+
+pub async fn treasury_yield_curve() -> Result<YieldCurve> {
+    let today = Utc::now().date_naive();
+    if let Some(cached) = YIELD_CURVE_CACHE.get(&today) {
+        return cached.clone().map_err(OptionsError::Other);
+    }
+
+    // Early in January the current year's file can be empty until the first
+    // business day has been published.
+    let result = match fetch_treasury_curve(today.year()).await {
+        Ok(curve) => Ok(curve),
+        Err(_) => fetch_treasury_curve(today.year() - 1).await,
+    }
+    .map_err(|e| e.to_string());
+    match &result {
+        Ok(curve) => info!(
+            "Loaded Treasury par yield curve for {} ({} points)",
+            curve.date,
+            curve.points.len()
+        ),
+        Err(e) => warn!("Treasury yield curve unavailable for {}: {}", today, e),
+    }
+
+    YIELD_CURVE_CACHE.retain(|date, _| *date == today);
+    YIELD_CURVE_CACHE.insert(today, result.clone());
+    result.map_err(OptionsError::Other)
+}
+
+pub async fn treasury_rate_for(maturity_years: f64) -> Result<f64> {
+    Ok(treasury_yield_curve().await?.rate_for(maturity_years))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve() -> YieldCurve {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        YieldCurve::from_points(
+            date,
+            vec![(3.0 / 12.0, 0.0540), (1.0 / 12.0, 0.0550), (1.0, 0.0510)],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn interpolates_linearly_between_one_and_three_months() {
+        let curve = curve();
+        let (t1, t3) = (1.0 / 12.0, 3.0 / 12.0);
+        for w in [0.0, 0.25, 0.5, 0.75, 1.0] {
+            let t = t1 + w * (t3 - t1);
+            let expected = 0.0550 + w * (0.0540 - 0.0550);
+            assert!((curve.rate_for(t) - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn extrapolates_flat_outside_the_curve() {
+        let curve = curve();
+        assert_eq!(curve.rate_for(1.0 / 365.0), 0.0550);
+        assert_eq!(curve.rate_for(30.0), 0.0510);
+    }
+
+    #[test]
+    fn parses_treasury_csv() {
+        let csv = "Date,\"1 Mo\",\"3 Mo\",\"1 Yr\"\n06/03/2024,5.50,5.40,5.10\n05/31/2024,5.49,5.41,5.12\n";
+        let curve = parse_treasury_csv(csv).unwrap();
+        assert_eq!(curve.date, NaiveDate::from_ymd_opt(2024, 6, 3).unwrap());
+        assert_eq!(curve.points.len(), 3);
+        assert!((curve.rate_for(1.0) - 0.0510).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::api::StockBar;
+use crate::utils::TRADING_DAYS_PER_YEAR;
+
+pub fn realized_volatility(closes: &[f64], periods_per_year: f64) -> f64 {
+    let returns: Vec<f64> = closes
+        .windows(2)
+        .filter(|w| w[0] > 0.0 && w[1] > 0.0)
+        .map(|w| (w[1] / w[0]).ln())
+        .collect();
+    if returns.len() < 2 || periods_per_year <= 0.0 {
+        return f64::NAN;
+    }
+
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (variance * periods_per_year).sqrt()
+}
+
+pub fn parkinson_volatility(bars: &[StockBar]) -> f64 {
+    let ranges: Vec<f64> = bars
+        .iter()
+        .filter(|b| b.l > 0.0 && b.h >= b.l)
+        .map(|b| (b.h / b.l).ln().powi(2))
+        .collect();
+    if ranges.len() < 2 {
+        return f64::NAN;
+    }
+
+    let variance = ranges.iter().sum::<f64>() / (4.0 * ranges.len() as f64 * 2f64.ln());
+    (variance * TRADING_DAYS_PER_YEAR).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(h: f64, l: f64) -> StockBar {
+        StockBar {
+            t: chrono::Utc::now(),
+            o: l,
+            h,
+            l,
+            c: h,
+            v: 0,
+            vw: 0.0,
+        }
+    }
+
+    #[test]
+    fn close_to_close_matches_hand_computed_value() {
+        // Log returns ln(1.1), ln(0.9), ln(1.1), sample variance annualised over 252 periods.
+        let vol = realized_volatility(&[100.0, 110.0, 99.0, 108.9], 252.0);
+        assert!((vol - 1.839_177).abs() < 1e-6);
+    }
+
+    #[test]
+    fn parkinson_matches_hand_computed_value() {
+        // ln(h/l) of 0.02 and 0.04: sqrt((0.0004 + 0.0016) / (8 ln 2) * 252).
+        let bars = [
+            bar(100.0 * 0.02f64.exp(), 100.0),
+            bar(50.0 * 0.04f64.exp(), 50.0),
+        ];
+        assert!((parkinson_volatility(&bars) - 0.301_479).abs() < 1e-6);
+    }
+
+    #[test]
+    fn short_inputs_are_nan() {
+        assert!(realized_volatility(&[], 252.0).is_nan());
+        assert!(realized_volatility(&[100.0, 101.0], 252.0).is_nan());
+        assert!(parkinson_volatility(&[bar(101.0, 100.0)]).is_nan());
+    }
+}
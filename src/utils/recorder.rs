@@ -0,0 +1,256 @@
+use crate::error::{OptionsError, Result};
+use crate::models::volatility::VolatilitySurface;
+use crate::models::SurfaceUpdate;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedSurface {
+    pub recorded_at: DateTime<Utc>,
+    pub symbol: String,
+    pub version: u64,
+    pub strikes: Vec<f64>,
+    pub expiries: Vec<NaiveDate>,
+    pub sigma: Vec<Option<f64>>,
+}
+
+impl From<&VolatilitySurface> for RecordedSurface {
+    fn from(surface: &VolatilitySurface) -> Self {
+        let update = SurfaceUpdate::from(surface);
+        Self {
+            recorded_at: Utc::now(),
+            symbol: surface.symbol.clone(),
+            version: surface.version,
+            strikes: update.strikes,
+            expiries: update.expiries,
+            sigma: update
+                .sigma
+                .into_iter()
+                .map(|v| v.is_finite().then_some(v))
+                .collect(),
+        }
+    }
+}
+
+impl From<RecordedSurface> for SurfaceUpdate {
+    fn from(record: RecordedSurface) -> Self {
+        Self {
+            strikes: record.strikes,
+            expiries: record.expiries,
+            sigma: record
+                .sigma
+                .into_iter()
+                .map(|v| v.unwrap_or(f64::NAN))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RotationPolicy {
+    pub max_bytes: Option<u64>,
+    pub max_age: Option<Duration>,
+}
+
+pub struct SurfaceRecorder {
+    path: PathBuf,
+    policy: RotationPolicy,
+    writer: BufWriter<File>,
+    bytes_written: u64,
+    opened_at: DateTime<Utc>,
+    rotations: u32,
+}
+
+impl SurfaceRecorder {
+    pub fn new<P: AsRef<Path>>(path: P, policy: RotationPolicy) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let (writer, bytes_written) = Self::open(&path)?;
+        Ok(Self {
+            path,
+            policy,
+            writer,
+            bytes_written,
+            opened_at: Utc::now(),
+            rotations: 0,
+        })
+    }
+
+    fn open(path: &Path) -> Result<(BufWriter<File>, u64)> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let len = file.metadata()?.len();
+        Ok((BufWriter::new(file), len))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&mut self, surface: &VolatilitySurface) -> Result<()> {
+        self.rotate_if_needed()?;
+
+        let line = serde_json::to_string(&RecordedSurface::from(surface))?;
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        self.bytes_written += line.len() as u64 + 1;
+
+        debug!(
+            "Recorded surface {} v{} to {}",
+            surface.symbol,
+            surface.version,
+            self.path.display()
+        );
+        Ok(())
+    }
+
+    fn rotate_if_needed(&mut self) -> Result<()> {
+        let too_big = self
+            .policy
+            .max_bytes
+            .is_some_and(|max| self.bytes_written >= max);
+        let too_old = self.policy.max_age.is_some_and(|age| {
+            (Utc::now() - self.opened_at)
+                .to_std()
+                .is_ok_and(|elapsed| elapsed >= age)
+        });
+
+        if !(too_big || too_old) || self.bytes_written == 0 {
+            return Ok(());
+        }
+
+        self.writer.flush()?;
+        // Segments can fill within the second they were opened, so the rotation
+        // count disambiguates; never rename over an existing segment.
+        let rotated = loop {
+            self.rotations += 1;
+            let candidate = PathBuf::from(format!(
+                "{}.{}-{}",
+                self.path.display(),
+                self.opened_at.format("%Y%m%d%H%M%S"),
+                self.rotations
+            ));
+            if !candidate.exists() {
+                break candidate;
+            }
+        };
+        std::fs::rename(&self.path, &rotated)?;
+        info!("Rotated surface recording to {}", rotated.display());
+
+        let (writer, bytes_written) = Self::open(&self.path)?;
+        self.writer = writer;
+        self.bytes_written = bytes_written;
+        self.opened_at = Utc::now();
+        Ok(())
+    }
+}
+
+pub fn read_recorded_surfaces<P: AsRef<Path>>(
+    path: P,
+) -> Result<impl Iterator<Item = Result<RecordedSurface>>> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(reader
+        .lines()
+        .filter(|line| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+        .map(|line| {
+            let line = line?;
+            serde_json::from_str::<RecordedSurface>(&line).map_err(|e| {
+                OptionsError::ParseError(format!("Failed to parse recorded surface: {}", e))
+            })
+        }))
+}
+
+pub fn read_surface_updates<P: AsRef<Path>>(
+    path: P,
+) -> Result<impl Iterator<Item = Result<SurfaceUpdate>>> {
+    Ok(read_recorded_surfaces(path)?.map(|r| r.map(SurfaceUpdate::from)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use ndarray::array;
+
+    fn surface(version: u64) -> VolatilitySurface {
+        VolatilitySurface {
+            symbol: "SPY".to_string(),
+            expirations: vec![
+                Utc.with_ymd_and_hms(2024, 6, 21, 20, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2024, 7, 19, 20, 0, 0).unwrap(),
+            ],
+            strikes: vec![500.0, 510.0],
+            volatilities: array![[0.2, f64::NAN], [0.21, 0.19]],
+            timestamp: Utc::now(),
+            version,
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn round_trips_surfaces_through_ndjson() {
+        let dir = temp_dir("recorder-round-trip");
+        let path = dir.join("surfaces.ndjson");
+        let mut recorder = SurfaceRecorder::new(&path, RotationPolicy::default()).unwrap();
+        recorder.record(&surface(1)).unwrap();
+        recorder.record(&surface(2)).unwrap();
+
+        let records: Vec<RecordedSurface> = read_recorded_surfaces(&path)
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].version, 2);
+        assert_eq!(
+            records[0].sigma,
+            vec![Some(0.2), None, Some(0.21), Some(0.19)]
+        );
+
+        let update = read_surface_updates(&path)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        let expected = SurfaceUpdate::from(&surface(1));
+        assert_eq!(update.strikes, expected.strikes);
+        assert_eq!(update.expiries, expected.expiries);
+        assert!(update.sigma[1].is_nan());
+        assert_eq!(update.sigma[3], expected.sigma[3]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rotation_within_one_second_keeps_every_segment() {
+        let dir = temp_dir("recorder-rotation");
+        let path = dir.join("surfaces.ndjson");
+        let policy = RotationPolicy {
+            max_bytes: Some(1),
+            max_age: None,
+        };
+        let mut recorder = SurfaceRecorder::new(&path, policy).unwrap();
+        for version in 1..=4 {
+            recorder.record(&surface(version)).unwrap();
+        }
+
+        let mut versions: Vec<u64> = std::fs::read_dir(&dir)
+            .unwrap()
+            .flat_map(|entry| read_recorded_surfaces(entry.unwrap().path()).unwrap())
+            .map(|r| r.unwrap().version)
+            .collect();
+        versions.sort();
+        assert_eq!(versions, vec![1, 2, 3, 4]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,270 @@
+use crate::error::{OptionsError, Result};
+use serde::{Deserialize, Serialize};
+
+const MIN_SVI_POINTS: usize = 5;
+const MIN_SVI_SIGMA: f64 = 1e-4;
+const NELDER_MEAD_MAX_ITER: usize = 2000;
+const NELDER_MEAD_TOL: f64 = 1e-14;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SviParams {
+    pub a: f64,
+    pub b: f64,
+    pub rho: f64,
+    pub m: f64,
+    pub sigma: f64,
+}
+
+impl SviParams {
+    pub fn total_variance(&self, k: f64) -> f64 {
+        let x = k - self.m;
+        self.a + self.b * (self.rho * x + (x * x + self.sigma * self.sigma).sqrt())
+    }
+
+    pub fn implied_volatility(&self, k: f64, t: f64) -> Option<f64> {
+        let w = self.total_variance(k);
+        if t <= 0.0 || w < 0.0 {
+            return None;
+        }
+        Some((w / t).sqrt())
+    }
+}
+
+fn solve3(mut a: [[f64; 3]; 3], mut b: [f64; 3]) -> Option<[f64; 3]> {
+    for col in 0..3 {
+        let pivot = (col..3).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-14 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        for row in col + 1..3 {
+            let f = a[row][col] / a[col][col];
+            let pivot_row = a[col];
+            for (cell, p) in a[row].iter_mut().zip(pivot_row).skip(col) {
+                *cell -= f * p;
+            }
+            b[row] -= f * b[col];
+        }
+    }
+    let mut x = [0.0; 3];
+    for row in (0..3).rev() {
+        let tail: f64 = (row + 1..3).map(|c| a[row][c] * x[c]).sum();
+        x[row] = (b[row] - tail) / a[row][row];
+    }
+    Some(x)
+}
+
+// For fixed (m, sigma) the raw SVI slice is linear in (a, b * rho, b), so the
+// inner fit is an ordinary least-squares solve and only (m, sigma) are searched.
+fn inner_fit(k: &[f64], w: &[f64], m: f64, sigma: f64) -> Option<(SviParams, f64)> {
+    let mut ata = [[0.0; 3]; 3];
+    let mut atb = [0.0; 3];
+    for (&ki, &wi) in k.iter().zip(w) {
+        let x = ki - m;
+        let row = [1.0, x, (x * x + sigma * sigma).sqrt()];
+        for (r, &xr) in row.iter().enumerate() {
+            for (cell, &xc) in ata[r].iter_mut().zip(&row) {
+                *cell += xr * xc;
+            }
+            atb[r] += xr * wi;
+        }
+    }
+    let [a, d, c] = solve3(ata, atb)?;
+    let b = c.max(0.0);
+    let rho = if b > 0.0 {
+        (d / b).clamp(-0.999, 0.999)
+    } else {
+        0.0
+    };
+    let params = SviParams {
+        a,
+        b,
+        rho,
+        m,
+        sigma,
+    };
+    let sse = k
+        .iter()
+        .zip(w)
+        .map(|(&ki, &wi)| (params.total_variance(ki) - wi).powi(2))
+        .sum();
+    Some((params, sse))
+}
+
+fn nelder_mead<F: Fn([f64; 2]) -> f64>(f: F, start: [f64; 2], step: [f64; 2]) -> [f64; 2] {
+    let mut simplex = [
+        start,
+        [start[0] + step[0], start[1]],
+        [start[0], start[1] + step[1]],
+    ];
+    let mut values = simplex.map(&f);
+
+    for _ in 0..NELDER_MEAD_MAX_ITER {
+        let mut order = [0, 1, 2];
+        order.sort_by(|&i, &j| values[i].total_cmp(&values[j]));
+        simplex = order.map(|i| simplex[i]);
+        values = order.map(|i| values[i]);
+
+        if (values[2] - values[0]).abs() < NELDER_MEAD_TOL {
+            break;
+        }
+
+        let centroid = [
+            (simplex[0][0] + simplex[1][0]) / 2.0,
+            (simplex[0][1] + simplex[1][1]) / 2.0,
+        ];
+        let along = |t: f64| {
+            [
+                centroid[0] + t * (simplex[2][0] - centroid[0]),
+                centroid[1] + t * (simplex[2][1] - centroid[1]),
+            ]
+        };
+
+        let reflected = along(-1.0);
+        let fr = f(reflected);
+        if fr < values[0] {
+            let expanded = along(-2.0);
+            let fe = f(expanded);
+            if fe < fr {
+                simplex[2] = expanded;
+                values[2] = fe;
+            } else {
+                simplex[2] = reflected;
+                values[2] = fr;
+            }
+        } else if fr < values[1] {
+            simplex[2] = reflected;
+            values[2] = fr;
+        } else {
+            let contracted = if fr < values[2] {
+                along(-0.5)
+            } else {
+                along(0.5)
+            };
+            let fc = f(contracted);
+            if fc < values[2].min(fr) {
+                simplex[2] = contracted;
+                values[2] = fc;
+            } else {
+                for i in 1..3 {
+                    simplex[i] = [
+                        (simplex[0][0] + simplex[i][0]) / 2.0,
+                        (simplex[0][1] + simplex[i][1]) / 2.0,
+                    ];
+                    values[i] = f(simplex[i]);
+                }
+            }
+        }
+    }
+
+    let best = (0..3)
+        .min_by(|&i, &j| values[i].total_cmp(&values[j]))
+        .unwrap_or(0);
+    simplex[best]
+}
+
+pub fn fit_svi(log_moneyness: &[f64], total_variance: &[f64]) -> Result<SviParams> {
+    if log_moneyness.len() != total_variance.len() {
+        return Err(OptionsError::VolatilityError(format!(
+            "SVI fit needs matching inputs, got {} log-moneyness and {} variance points",
+            log_moneyness.len(),
+            total_variance.len()
+        )));
+    }
+
+    let (k, w): (Vec<f64>, Vec<f64>) = log_moneyness
+        .iter()
+        .zip(total_variance)
+        .filter(|(k, w)| k.is_finite() && w.is_finite())
+        .map(|(&k, &w)| (k, w))
+        .unzip();
+    if k.len() < MIN_SVI_POINTS {
+        return Err(OptionsError::VolatilityError(format!(
+            "SVI fit needs at least {} points, got {}",
+            MIN_SVI_POINTS,
+            k.len()
+        )));
+    }
+
+    let objective = |p: [f64; 2]| {
+        inner_fit(&k, &w, p[0], p[1].exp().max(MIN_SVI_SIGMA)).map_or(f64::INFINITY, |(_, sse)| sse)
+    };
+
+    let k_min = k.iter().copied().fold(f64::INFINITY, f64::min);
+    let k_max = k.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let width = (k_max - k_min).max(1e-3);
+    let k_at_min_w = k
+        .iter()
+        .zip(&w)
+        .min_by(|a, b| a.1.total_cmp(b.1))
+        .map_or(0.0, |(&k, _)| k);
+
+    let mut best: Option<(SviParams, f64)> = None;
+    for m0 in [k_at_min_w, k_min + 0.25 * width, k_max - 0.25 * width] {
+        for sigma0 in [0.05 * width, 0.25 * width, width] {
+            let start = [m0, sigma0.max(MIN_SVI_SIGMA).ln()];
+            let [m, log_sigma] = nelder_mead(objective, start, [0.1 * width, 0.5]);
+            if let Some(fit) = inner_fit(&k, &w, m, log_sigma.exp().max(MIN_SVI_SIGMA)) {
+                if best.as_ref().is_none_or(|(_, sse)| fit.1 < *sse) {
+                    best = Some(fit);
+                }
+            }
+        }
+    }
+
+    best.map(|(params, _)| params)
+        .ok_or_else(|| OptionsError::VolatilityError("SVI fit failed to converge".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KNOWN: SviParams = SviParams {
+        a: 0.02,
+        b: 0.1,
+        rho: -0.4,
+        m: 0.05,
+        sigma: 0.15,
+    };
+
+    fn sample(params: &SviParams, n: usize) -> (Vec<f64>, Vec<f64>) {
+        let k: Vec<f64> = (0..n)
+            .map(|i| -0.6 + 1.2 * i as f64 / (n - 1) as f64)
+            .collect();
+        let w = k.iter().map(|&k| params.total_variance(k)).collect();
+        (k, w)
+    }
+
+    #[test]
+    fn recovers_known_parameters() {
+        let (k, w) = sample(&KNOWN, 25);
+        let fit = fit_svi(&k, &w).unwrap();
+
+        assert!((fit.a - KNOWN.a).abs() < 1e-4, "{:?}", fit);
+        assert!((fit.b - KNOWN.b).abs() < 1e-4, "{:?}", fit);
+        assert!((fit.rho - KNOWN.rho).abs() < 1e-3, "{:?}", fit);
+        assert!((fit.m - KNOWN.m).abs() < 1e-3, "{:?}", fit);
+        assert!((fit.sigma - KNOWN.sigma).abs() < 1e-3, "{:?}", fit);
+    }
+
+    #[test]
+    fn ignores_non_finite_points() {
+        let (mut k, mut w) = sample(&KNOWN, 25);
+        k.push(f64::NAN);
+        w.push(0.1);
+        w[3] = f64::INFINITY;
+        let fit = fit_svi(&k, &w).unwrap();
+
+        for &ki in &[-0.5, 0.0, 0.5] {
+            assert!((fit.total_variance(ki) - KNOWN.total_variance(ki)).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn rejects_short_or_mismatched_inputs() {
+        assert!(fit_svi(&[0.0, 0.1, 0.2, 0.3], &[0.04; 4]).is_err());
+        assert!(fit_svi(&[0.0, 0.1, 0.2, 0.3, 0.4], &[0.04; 4]).is_err());
+    }
+}
@@ -0,0 +1,24 @@
+pub const CALENDAR_DAYS_PER_YEAR: f64 = 365.0;
+pub const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+
+pub fn annualized_to_period(iv: f64, period_days: f64, year_basis: f64) -> f64 {
+    if period_days <= 0.0 || year_basis <= 0.0 {
+        return 0.0;
+    }
+    iv * (period_days / year_basis).sqrt()
+}
+
+pub fn period_to_annualized(period_vol: f64, period_days: f64, year_basis: f64) -> f64 {
+    if period_days <= 0.0 || year_basis <= 0.0 {
+        return f64::NAN;
+    }
+    period_vol * (year_basis / period_days).sqrt()
+}
+
+pub fn daily_vol(iv: f64) -> f64 {
+    annualized_to_period(iv, 1.0, CALENDAR_DAYS_PER_YEAR)
+}
+
+pub fn weekly_vol(iv: f64) -> f64 {
+    annualized_to_period(iv, 7.0, CALENDAR_DAYS_PER_YEAR)
+}
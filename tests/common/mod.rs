@@ -0,0 +1,154 @@
+//! Minimal HTTP/1.1 stub that serves canned responses by path prefix, so REST
+//! clients can be exercised without network access.
+
+use options_rs::config::AlpacaConfig;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+#[derive(Debug, Clone)]
+pub struct StubResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl StubResponse {
+    pub fn json(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+type Routes = Arc<Mutex<HashMap<String, Vec<StubResponse>>>>;
+
+pub struct StubServer {
+    pub addr: SocketAddr,
+    routes: Routes,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl StubServer {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let routes: Routes = Arc::default();
+        let requests: Arc<Mutex<Vec<String>>> = Arc::default();
+
+        let (task_routes, task_requests) = (routes.clone(), requests.clone());
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    break;
+                };
+                let (routes, requests) = (task_routes.clone(), task_requests.clone());
+                tokio::spawn(async move {
+                    let Some(target) = read_request_target(&mut socket).await else {
+                        return;
+                    };
+                    requests.lock().unwrap().push(target.clone());
+                    let response = next_response(&routes, &target);
+                    let _ = socket.write_all(&encode(&response)).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+
+        Self {
+            addr,
+            routes,
+            requests,
+        }
+    }
+
+    /// Queues responses for requests whose path starts with `prefix`. They are
+    /// served in order and the last one repeats.
+    pub fn route(&self, prefix: &str, responses: Vec<StubResponse>) {
+        self.routes
+            .lock()
+            .unwrap()
+            .insert(prefix.to_string(), responses);
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn read_request_target(socket: &mut tokio::net::TcpStream) -> Option<String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = socket.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let head = String::from_utf8_lossy(&buf);
+    head.lines()
+        .next()?
+        .split_whitespace()
+        .nth(1)
+        .map(str::to_string)
+}
+
+fn next_response(routes: &Routes, target: &str) -> StubResponse {
+    let mut routes = routes.lock().unwrap();
+    let queue = routes
+        .iter_mut()
+        .filter(|(prefix, _)| target.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, queue)| queue);
+    match queue {
+        Some(queue) if queue.len() > 1 => queue.remove(0),
+        Some(queue) if !queue.is_empty() => queue[0].clone(),
+        _ => StubResponse::json(404, r#"{"message":"not found"}"#),
+    }
+}
+
+fn encode(response: &StubResponse) -> Vec<u8> {
+    let mut head = format!(
+        "HTTP/1.1 {} Stub\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    let mut bytes = head.into_bytes();
+    bytes.extend_from_slice(response.body.as_bytes());
+    bytes
+}
+
+pub fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("missing fixture {}: {}", path, e))
+}
+
+pub fn alpaca_config() -> AlpacaConfig {
+    AlpacaConfig {
+        api_key: "test-key".to_string(),
+        api_secret: "test-secret".to_string(),
+        base_url: String::new(),
+        data_url: String::new(),
+        paper_url: String::new(),
+        user_agent: "options-rs-tests".to_string(),
+        send_request_id: false,
+    }
+}
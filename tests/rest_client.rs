@@ -0,0 +1,397 @@
+mod common;
+
+use common::{alpaca_config, fixture, StubResponse, StubServer};
+use options_rs::api::{Account, ChainSymbolFilter};
+use options_rs::{OptionsError, RestClient};
+
+async fn client(server: &StubServer) -> RestClient {
+    RestClient::with_base_url(alpaca_config(), &server.base_url(), &server.base_url())
+}
+
+#[tokio::test]
+async fn follows_contract_pagination() {
+    let server = StubServer::start().await;
+    server.route(
+        "/v2/options/contracts",
+        vec![
+            StubResponse::json(200, &fixture("contracts_page1.json")),
+            StubResponse::json(200, &fixture("contracts_page2.json")),
+        ],
+    );
+
+    let contracts = client(&server)
+        .await
+        .get_all_options_contracts("SPY", &ChainSymbolFilter::default())
+        .await
+        .unwrap();
+
+    let symbols: Vec<&str> = contracts.iter().map(|c| c.symbol.as_str()).collect();
+    assert_eq!(
+        symbols,
+        [
+            "SPY240621C00500000",
+            "SPY240621P00500000",
+            "SPY240621C00512500"
+        ]
+    );
+    let strikes: Vec<f64> = contracts
+        .iter()
+        .map(|c| c.strike_price.parse().unwrap())
+        .collect();
+    assert_eq!(strikes, [500.0, 500.0, 512.5]);
+    assert_eq!(contracts[2].open_interest, None);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(!requests[0].contains("page_token="));
+    assert!(requests[1].contains("page_token=MTcxODkyODAwMDAwMA=="));
+}
+
+#[tokio::test]
+async fn parses_snapshot_greeks_and_quotes() {
+    let server = StubServer::start().await;
+    server.route(
+        "/v1beta1/options/snapshots/SPY",
+        vec![StubResponse::json(200, &fixture("snapshots_spy.json"))],
+    );
+
+    let data = client(&server)
+        .await
+        .get_option_chain_snapshots(
+            "SPY",
+            Some("indicative"),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+    let call = &data.snapshots["SPY240621C00500000"];
+    let greeks = call.greeks.as_ref().unwrap();
+    assert_eq!(greeks.delta, 0.8123);
+    assert_eq!(greeks.rho, 0.1877);
+    assert_eq!(call.implied_volatility, Some(0.1534));
+    let quote = call.last_quote.as_ref().unwrap();
+    assert_eq!((quote.bid, quote.ask), (30.35, 30.55));
+    assert_eq!(call.last_trade.as_ref().unwrap().price, 30.42);
+    assert_eq!(call.daily_bar.as_ref().unwrap().v, 5120);
+
+    let put = &data.snapshots["SPY240621P00500000"];
+    assert!(!put.has_greeks());
+    assert_eq!(put.last_quote.as_ref().unwrap().bid, 1.06);
+    assert!(server.requests()[0].contains("feed=indicative"));
+}
+
+#[tokio::test]
+async fn parses_latest_stock_quote() {
+    let server = StubServer::start().await;
+    server.route(
+        "/v2/stocks/SPY/quotes/latest",
+        vec![StubResponse::json(200, &fixture("stock_quote_spy.json"))],
+    );
+
+    let resp = client(&server)
+        .await
+        .get_latest_single_stock_quote("SPY", None, None)
+        .await
+        .unwrap();
+
+    assert_eq!(resp.symbol, "SPY");
+    assert_eq!((resp.quote.bid, resp.quote.ask), (527.79, 527.85));
+}
+
+#[tokio::test]
+async fn retries_after_rate_limit() {
+    let server = StubServer::start().await;
+    let limited =
+        StubResponse::json(429, r#"{"message":"too many requests"}"#).header("retry-after", "0");
+    server.route(
+        "/v2/stocks/SPY/quotes/latest",
+        vec![
+            limited.clone(),
+            limited,
+            StubResponse::json(200, &fixture("stock_quote_spy.json")),
+        ],
+    );
+
+    let resp = client(&server)
+        .await
+        .get_latest_single_stock_quote("SPY", None, None)
+        .await
+        .unwrap();
+
+    assert_eq!(resp.quote.bid, 527.79);
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn maps_unauthorized_forbidden_and_not_found() {
+    let server = StubServer::start().await;
+    server.route(
+        "/v2/stocks/SPY/quotes/latest",
+        vec![StubResponse::json(401, r#"{"message":"unauthorized."}"#)],
+    );
+    server.route(
+        "/v2/stocks/QQQ/quotes/latest",
+        vec![StubResponse::json(
+            403,
+            r#"{"message":"subscription does not permit querying recent SIP data"}"#,
+        )],
+    );
+    let client = client(&server).await;
+
+    match client
+        .get_latest_single_stock_quote("SPY", None, None)
+        .await
+    {
+        Err(OptionsError::Unauthorized { url }) => {
+            assert!(url.ends_with("/v2/stocks/SPY/quotes/latest"))
+        }
+        other => panic!("expected Unauthorized, got {:?}", other),
+    }
+    match client
+        .get_latest_single_stock_quote("QQQ", None, None)
+        .await
+    {
+        Err(OptionsError::Forbidden { url, body }) => {
+            assert!(url.ends_with("/v2/stocks/QQQ/quotes/latest"));
+            assert!(body.contains("subscription does not permit"));
+        }
+        other => panic!("expected Forbidden, got {:?}", other),
+    }
+    match client
+        .get_latest_single_stock_quote("IWM", None, None)
+        .await
+    {
+        Err(OptionsError::NotFound { url }) => assert!(url.contains("/v2/stocks/IWM/")),
+        other => panic!("expected NotFound, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn reports_rate_limit_after_retries() {
+    let server = StubServer::start().await;
+    server.route(
+        "/v2/stocks/SPY/quotes/latest",
+        vec![StubResponse::json(429, "{}").header("retry-after", "0")],
+    );
+
+    let result = client(&server)
+        .await
+        .with_max_retries(1)
+        .get_latest_single_stock_quote("SPY", None, None)
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(OptionsError::RateLimited {
+            retry_after: Some(wait)
+        }) if wait.is_zero()
+    ));
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn reports_network_errors_with_url() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let client = RestClient::with_base_url(alpaca_config(), &base_url, &base_url);
+    match client
+        .get_latest_single_stock_quote("SPY", None, None)
+        .await
+    {
+        Err(OptionsError::Network { url, .. }) => {
+            assert!(url.ends_with("/v2/stocks/SPY/quotes/latest"))
+        }
+        other => panic!("expected Network, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn parses_account_with_string_balances() {
+    let server = StubServer::start().await;
+    server.route(
+        "/v2/account",
+        vec![StubResponse::json(200, &fixture("account.json"))],
+    );
+
+    let account = client(&server).await.get_account().await.unwrap();
+    assert_eq!(account.id, "904837e3-3b76-47ec-b432-046db621571b");
+    assert_eq!(account.equity, 103820.56);
+    assert_eq!(server.requests(), ["/v2/account"]);
+
+    let numeric: Account = serde_json::from_str(r#"{"id":"a","equity":12.5}"#).unwrap();
+    assert_eq!(numeric.equity, 12.5);
+    assert!(serde_json::from_str::<Account>(r#"{"id":"a","equity":"n/a"}"#).is_err());
+}
+
+#[tokio::test]
+async fn streams_snapshots_across_pages_once_each() {
+    use futures::StreamExt;
+
+    let server = StubServer::start().await;
+    server.route(
+        "/v1beta1/options/snapshots/SPY",
+        vec![
+            StubResponse::json(200, &fixture("snapshots_page1.json")),
+            StubResponse::json(200, &fixture("snapshots_page2.json")),
+        ],
+    );
+    let client = client(&server).await;
+    let filter = ChainSymbolFilter::default();
+
+    let snapshots: Vec<_> = client
+        .stream_option_chain_snapshots("SPY", Some("indicative"), Some(2), &filter)
+        .collect()
+        .await;
+
+    let mut symbols: Vec<String> = snapshots.into_iter().map(|s| s.unwrap().symbol).collect();
+    symbols.sort();
+    assert_eq!(
+        symbols,
+        [
+            "SPY240621C00500000",
+            "SPY240621C00512500",
+            "SPY240621P00500000"
+        ]
+    );
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(!requests[0].contains("page_token="));
+    assert!(requests[1].contains("page_token=U1BZMjQwNjIxQzAwNTEyNTAw"));
+}
+
+#[tokio::test]
+async fn parses_stock_bars_and_page_token() {
+    let server = StubServer::start().await;
+    server.route(
+        "/v2/stocks/SPY/bars",
+        vec![
+            StubResponse::json(200, &fixture("stock_bars_spy.json")),
+            StubResponse::json(200, &fixture("stock_bars_spy_page2.json")),
+        ],
+    );
+    let client = client(&server).await;
+    let start = chrono::DateTime::parse_from_rfc3339("2024-05-30T00:00:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    let end = start + chrono::Duration::days(5);
+
+    let first = client
+        .get_stock_bars("SPY", start, end, "1Day", Some(2), None)
+        .await
+        .unwrap();
+    assert_eq!(first.symbol, "SPY");
+    assert_eq!(first.bars.len(), 2);
+    assert_eq!(first.bars[1].c, 527.37);
+    assert_eq!(first.bars[1].v, 90785823);
+    assert_eq!(first.bars[0].t, start + chrono::Duration::hours(4));
+    let token = first.next_page_token.unwrap();
+    assert_eq!(token, "U1BZfEZ8MjAyNC0wNi0wM1QwNDowMDowMC4wMDAwMDAwMDBa");
+
+    let second = client
+        .get_stock_bars("SPY", start, end, "1Day", Some(2), Some(&token))
+        .await
+        .unwrap();
+    assert_eq!(second.bars.len(), 1);
+    assert!(second.next_page_token.is_none());
+
+    let requests = server.requests();
+    assert!(requests[0].contains("start=2024-05-30T00:00:00Z"));
+    assert!(requests[0].contains("timeframe=1Day&limit=2"));
+    assert!(!requests[0].contains("page_token="));
+    assert!(requests[1].ends_with(&format!("page_token={}", token)));
+}
+
+#[tokio::test]
+async fn parses_latest_option_trades() {
+    let server = StubServer::start().await;
+    server.route(
+        "/v1beta1/options/trades/latest",
+        vec![
+            StubResponse::json(200, &fixture("latest_trades_spy.json")),
+            StubResponse::json(200, "{}"),
+        ],
+    );
+    let client = client(&server).await;
+
+    let resp = client
+        .get_latest_options_trades(&["SPY240621C00500000", "SPY240621P00500000"])
+        .await
+        .unwrap();
+    assert_eq!(resp.trades.len(), 2);
+    let call = &resp.trades["SPY240621C00500000"];
+    assert_eq!((call.price, call.size), (30.42, 2));
+    assert_eq!(call.exchange_code, "C");
+    assert_eq!(call.condition.as_deref(), Some("I"));
+    assert_eq!(
+        call.t,
+        chrono::DateTime::parse_from_rfc3339("2024-06-03T19:58:12.003498Z").unwrap()
+    );
+    let put = &resp.trades["SPY240621P00500000"];
+    assert_eq!((put.price, put.size), (1.07, 15));
+    assert!(server.requests()[0].ends_with("symbols=SPY240621C00500000,SPY240621P00500000"));
+
+    let empty = client
+        .get_latest_options_trades(&["SPY240621C00999000"])
+        .await
+        .unwrap();
+    assert!(empty.trades.is_empty());
+}
+
+#[tokio::test]
+async fn chain_symbols_sorts_by_atm_and_respects_max() {
+    let server = StubServer::start().await;
+    let route_contracts = || {
+        server.route(
+            "/v2/options/contracts",
+            vec![
+                StubResponse::json(200, &fixture("contracts_page1.json")),
+                StubResponse::json(200, &fixture("contracts_page2.json")),
+            ],
+        )
+    };
+    server.route(
+        "/v2/stocks/SPY/quotes/latest",
+        vec![StubResponse::json(200, &fixture("stock_quote_spy.json"))],
+    );
+    let client = client(&server).await;
+
+    route_contracts();
+    let filter = ChainSymbolFilter {
+        sort_by_atm: true,
+        ..Default::default()
+    };
+    let symbols = client.chain_symbols("SPY", &filter, Some(2)).await.unwrap();
+    // Spot is 527.82, so the 512.5 strike is nearest the money.
+    assert_eq!(symbols, ["SPY240621C00512500", "SPY240621C00500000"]);
+    assert!(symbols
+        .iter()
+        .all(|s| options_rs::models::OptionContract::from_occ_symbol(s).is_some()));
+
+    route_contracts();
+    let puts = ChainSymbolFilter {
+        option_type: Some(options_rs::models::OptionType::Put),
+        ..Default::default()
+    };
+    let symbols = client.chain_symbols("SPY", &puts, None).await.unwrap();
+    assert_eq!(symbols, ["SPY240621P00500000"]);
+
+    let quote_requests = server
+        .requests()
+        .iter()
+        .filter(|r| r.contains("/quotes/latest"))
+        .count();
+    assert_eq!(quote_requests, 1);
+}